@@ -1,14 +1,18 @@
+use crate::analysis::flow::{InterruptConfig, LayoutConfig};
 use crate::analysis::language::*;
 use crate::analysis::refactoring::*;
+use crate::analysis::utils::{InstructionDatabase, IsaProfile, MemoryModel};
+use crate::LspError;
 use tower_lsp::lsp_types::*;
 
-use super::DiagnosticsEngine;
+use super::{DiagnosticsEngine, DocumentCache, LabelNaming, Suppressions, WorkspaceConfig};
 
 #[derive(Debug)]
 pub struct SemanticAnalyzer {
     diagnostics_engine: DiagnosticsEngine,
     completion_provider: CompletionProvider,
     hover_provider: HoverProvider,
+    inlay_hint_provider: InlayHintProvider,
     navigation_provider: NavigationProvider,
     symbol_provider: SymbolProvider,
     semantic_tokens_provider: SemanticTokensProvider,
@@ -16,31 +20,141 @@ pub struct SemanticAnalyzer {
     code_actions_provider: CodeActionsProvider,
     rename_provider: RenameProvider,
     validation_provider: ValidationProvider,
+    config: WorkspaceConfig,
+}
+
+impl Default for SemanticAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SemanticAnalyzer {
     pub fn new() -> Self {
+        Self::with_memory_model(MemoryModel::default())
+    }
+
+    pub fn with_memory_model(memory_model: MemoryModel) -> Self {
+        // every provider reads the same parsed documents
+        let documents = DocumentCache::new();
+
         Self {
-            diagnostics_engine: DiagnosticsEngine::new(),
-            completion_provider: CompletionProvider::new(),
-            hover_provider: HoverProvider::new(),
-            navigation_provider: NavigationProvider::new(),
-            symbol_provider: SymbolProvider::new(),
-            semantic_tokens_provider: SemanticTokensProvider::new(),
+            diagnostics_engine: DiagnosticsEngine::with_cache(documents.clone())
+                .with_memory_model(memory_model),
+            completion_provider: CompletionProvider::with_cache(documents.clone()),
+            hover_provider: HoverProvider::with_cache(documents.clone())
+                .with_memory_model(memory_model),
+            inlay_hint_provider: InlayHintProvider::with_cache(documents.clone())
+                .with_memory_model(memory_model),
+            navigation_provider: NavigationProvider::with_cache(documents.clone()),
+            symbol_provider: SymbolProvider::with_cache(documents.clone()),
+            semantic_tokens_provider: SemanticTokensProvider::with_cache(documents.clone()),
             signature_help_provider: SignatureHelpProvider::new(),
-            code_actions_provider: CodeActionsProvider::new(),
-            rename_provider: RenameProvider::new(),
-            validation_provider: ValidationProvider::new(),
+            code_actions_provider: CodeActionsProvider::with_cache(documents.clone()),
+            rename_provider: RenameProvider::with_cache(documents.clone()),
+            validation_provider: ValidationProvider::with_cache(documents)
+                .with_memory_model(memory_model),
+            config: WorkspaceConfig::default(),
         }
     }
 
+    /// analyzer set up from a project configuration file; the instruction set
+    /// it names is loaded with `WorkspaceConfig::instruction_database`
+    pub fn with_config(config: WorkspaceConfig) -> Self {
+        let mut analyzer = Self::with_memory_model(config.memory_model())
+            .with_instruction_db(config.builtin_instruction_database())
+            .with_isa_profile(config.isa)
+            .with_label_naming(config.labels.naming)
+            .with_mnemonic_normalization(config.mnemonics.normalize)
+            .with_interrupt_config(config.interrupts.clone())
+            .with_exported_labels(config.exported_labels())
+            .with_layout_config(config.layout);
+        analyzer.config = config;
+        analyzer
+    }
+
+    /// instruction set that is validated and completed
+    pub fn with_isa_profile(mut self, isa: IsaProfile) -> Self {
+        self.diagnostics_engine = self.diagnostics_engine.with_isa_profile(isa);
+        self.completion_provider = self.completion_provider.with_isa_profile(isa);
+        self
+    }
+
+    /// instructions every provider knows, the embedded Machine W set by default
+    pub fn with_instruction_db(mut self, instruction_db: InstructionDatabase) -> Self {
+        self.diagnostics_engine = self
+            .diagnostics_engine
+            .with_instruction_db(instruction_db.clone());
+        self.completion_provider = self
+            .completion_provider
+            .with_instruction_db(instruction_db.clone());
+        self.hover_provider = self.hover_provider.with_instruction_db(instruction_db.clone());
+        self.inlay_hint_provider = self
+            .inlay_hint_provider
+            .with_instruction_db(instruction_db.clone());
+        self.semantic_tokens_provider = self
+            .semantic_tokens_provider
+            .with_instruction_db(instruction_db.clone());
+        self.signature_help_provider = self
+            .signature_help_provider
+            .with_instruction_db(instruction_db.clone());
+        self.code_actions_provider = self
+            .code_actions_provider
+            .with_instruction_db(instruction_db.clone());
+        self.rename_provider = self.rename_provider.with_instruction_db(instruction_db.clone());
+        self.validation_provider = self.validation_provider.with_instruction_db(instruction_db);
+        self
+    }
+
+    /// case style label definitions are checked against
+    pub fn with_label_naming(mut self, label_naming: LabelNaming) -> Self {
+        self.validation_provider = self.validation_provider.with_label_naming(label_naming);
+        self
+    }
+
+    /// whether mnemonics not in their canonical spelling are reported
+    pub fn with_mnemonic_normalization(mut self, normalize: bool) -> Self {
+        self.diagnostics_engine = self.diagnostics_engine.with_mnemonic_normalization(normalize);
+        self
+    }
+
+    /// interrupt lines and handler labels for the interrupt checks and hover
+    pub fn with_interrupt_config(mut self, interrupts: InterruptConfig) -> Self {
+        self.diagnostics_engine = self
+            .diagnostics_engine
+            .with_interrupt_config(interrupts.clone());
+        self.hover_provider = self.hover_provider.with_interrupt_config(interrupts);
+        self
+    }
+
+    /// labels entered from outside the document, such as configured interrupt
+    /// handlers, which are never reported or removed as unused
+    pub fn with_exported_labels(mut self, exported_labels: Vec<String>) -> Self {
+        self.diagnostics_engine = self
+            .diagnostics_engine
+            .with_exported_labels(exported_labels.clone());
+        self.semantic_tokens_provider = self
+            .semantic_tokens_provider
+            .with_exported_labels(exported_labels.clone());
+        self.code_actions_provider = self.code_actions_provider.with_exported_labels(exported_labels);
+        self
+    }
+
+    /// levels of the store-into-code, jump-into-data and fall-into-data checks
+    pub fn with_layout_config(mut self, layout: LayoutConfig) -> Self {
+        self.diagnostics_engine = self.diagnostics_engine.with_layout_config(layout);
+        self
+    }
+
     // Diagnostics
     pub fn analyze_document(&self, content: &str, uri: &Url) -> Vec<Diagnostic> {
         let mut diagnostics = self.diagnostics_engine.analyze_document(content, uri);
 
         diagnostics.extend(self.validation_provider.validate_symbol_usage(content));
 
-        diagnostics
+        let diagnostics = Suppressions::parse(content.lines())
+            .apply(diagnostics, |code| self.config.disables(code));
+        self.config.apply(diagnostics)
     }
 
     // Completion
@@ -53,6 +167,11 @@ impl SemanticAnalyzer {
         self.hover_provider.get_hover_info(content, position)
     }
 
+    // Inlay hints
+    pub fn get_inlay_hints(&self, content: &str, range: Range) -> Vec<InlayHint> {
+        self.inlay_hint_provider.get_inlay_hints(content, range)
+    }
+
     // Navigation
     pub fn get_definition(
         &self,
@@ -75,6 +194,10 @@ impl SemanticAnalyzer {
             .find_references(content, position, uri, include_declaration)
     }
 
+    pub fn check_label_ambiguity(&self, content: &str, position: Position) -> Result<(), LspError> {
+        self.navigation_provider.check_unambiguous(content, position)
+    }
+
     // Symbols
     pub fn get_document_symbols(&self, content: &str) -> Vec<SymbolInformation> {
         self.symbol_provider.get_document_symbols(content)
@@ -90,6 +213,10 @@ impl SemanticAnalyzer {
             .filter_workspace_symbols(symbols, query, uri)
     }
 
+    pub fn check_rename_target(&self, content: &str, position: Position) -> Result<(), LspError> {
+        self.rename_provider.check_unambiguous(content, position)
+    }
+
     pub fn get_rename_range(&self, content: &str, position: Position) -> Option<Range> {
         self.rename_provider.get_rename_range(content, position)
     }
@@ -0,0 +1,288 @@
+use crate::analysis::flow::{CheckLevel, InterruptConfig, LayoutConfig};
+use crate::analysis::utils::{
+    DiagnosticCode, InstructionDatabase, IsaProfile, MemoryModel, RuleGroup, DEFAULT_ADDRESS_BITS, DEFAULT_WORD_BITS,
+};
+use crate::LspError;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity};
+
+/// project configuration files looked up in the workspace root, first found wins
+pub const CONFIG_FILE_NAMES: &[&str] = &[".asmodeus-lsp.toml", ".asmodeus-lsp.json"];
+
+/// severity a project gives a diagnostic code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeverityLevel {
+    Error,
+    Warning,
+    #[serde(alias = "info")]
+    Information,
+    Hint,
+    Off,
+}
+
+impl SeverityLevel {
+    /// none when the code is switched off
+    pub fn severity(self) -> Option<DiagnosticSeverity> {
+        match self {
+            Self::Error => Some(DiagnosticSeverity::ERROR),
+            Self::Warning => Some(DiagnosticSeverity::WARNING),
+            Self::Information => Some(DiagnosticSeverity::INFORMATION),
+            Self::Hint => Some(DiagnosticSeverity::HINT),
+            Self::Off => None,
+        }
+    }
+}
+
+/// rule groups, all enabled by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RuleGroups {
+    pub style: bool,
+    pub dataflow: bool,
+    pub interrupts: bool,
+}
+
+impl Default for RuleGroups {
+    fn default() -> Self {
+        Self {
+            style: true,
+            dataflow: true,
+            interrupts: true,
+        }
+    }
+}
+
+impl RuleGroups {
+    pub fn is_enabled(&self, group: RuleGroup) -> bool {
+        match group {
+            RuleGroup::Style => self.style,
+            RuleGroup::Dataflow => self.dataflow,
+            RuleGroup::Interrupts => self.interrupts,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct MemoryConfig {
+    pub word_bits: u32,
+    pub address_bits: u32,
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        Self {
+            word_bits: DEFAULT_WORD_BITS,
+            address_bits: DEFAULT_ADDRESS_BITS,
+        }
+    }
+}
+
+/// case style label names must follow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum LabelNaming {
+    #[default]
+    #[serde(rename = "any")]
+    Any,
+    #[serde(rename = "snake_case")]
+    SnakeCase,
+    #[serde(rename = "camelCase")]
+    CamelCase,
+    #[serde(rename = "SCREAMING_SNAKE_CASE")]
+    ScreamingSnakeCase,
+}
+
+impl LabelNaming {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Any => "any",
+            Self::SnakeCase => "snake_case",
+            Self::CamelCase => "camelCase",
+            Self::ScreamingSnakeCase => "SCREAMING_SNAKE_CASE",
+        }
+    }
+
+    /// whether name is written in the style; leading underscores are ignored
+    pub fn accepts(self, name: &str) -> bool {
+        let name = name.trim_start_matches('_');
+        match self {
+            Self::Any => true,
+            Self::SnakeCase => name
+                .chars()
+                .all(|c| c.is_lowercase() || c.is_ascii_digit() || c == '_'),
+            Self::ScreamingSnakeCase => name
+                .chars()
+                .all(|c| c.is_uppercase() || c.is_ascii_digit() || c == '_'),
+            Self::CamelCase => {
+                name.chars().next().is_none_or(|first| first.is_lowercase())
+                    && name.chars().all(|c| c.is_alphanumeric())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LabelConfig {
+    pub naming: LabelNaming,
+    /// labels used from outside the document, never reported or removed as
+    /// unused
+    pub exported: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct MnemonicConfig {
+    /// `pob` is an unknown instruction rather than `POB`
+    pub case_sensitive: bool,
+    /// report mnemonics not written in their canonical spelling
+    pub normalize: bool,
+}
+
+/// `.asmodeus-lsp.toml` or `.asmodeus-lsp.json` of a workspace
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct WorkspaceConfig {
+    /// severity per diagnostic code, e.g. `SEM037 = "off"`
+    pub severity: BTreeMap<String, SeverityLevel>,
+    pub rules: RuleGroups,
+    pub memory: MemoryConfig,
+    pub isa: IsaProfile,
+    /// instruction set definition replacing the embedded Machine W one,
+    /// relative to the configuration file
+    pub instruction_set: Option<PathBuf>,
+    pub mnemonics: MnemonicConfig,
+    pub labels: LabelConfig,
+    /// interrupt lines and handler labels entered from outside the document
+    pub interrupts: InterruptConfig,
+    /// levels of the store-into-code, jump-into-data and fall-into-data checks
+    pub layout: LayoutConfig,
+}
+
+impl WorkspaceConfig {
+    pub fn from_toml(text: &str) -> Result<Self, LspError> {
+        toml::from_str::<Self>(text)
+            .map_err(|error| LspError::ConfigError(error.to_string()))?
+            .validated()
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, LspError> {
+        serde_json::from_str::<Self>(text)?.validated()
+    }
+
+    /// parses path as TOML or, with a `.json` extension, as JSON
+    pub fn from_file(path: &Path) -> Result<Self, LspError> {
+        let text = std::fs::read_to_string(path)?;
+        let mut config = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => Self::from_json(&text),
+            _ => Self::from_toml(&text),
+        }?;
+        if let (Some(instruction_set), Some(dir)) = (&config.instruction_set, path.parent()) {
+            config.instruction_set = Some(dir.join(instruction_set));
+        }
+        Ok(config)
+    }
+
+    /// first configuration file in root, none when there is no file
+    pub fn find(root: &Path) -> Option<PathBuf> {
+        CONFIG_FILE_NAMES
+            .iter()
+            .map(|name| root.join(name))
+            .find(|path| path.is_file())
+    }
+
+    /// whether path names a configuration file
+    pub fn is_config_file(path: &Path) -> bool {
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| CONFIG_FILE_NAMES.contains(&name))
+    }
+
+    fn validated(self) -> Result<Self, LspError> {
+        for code in self.severity.keys() {
+            if DiagnosticCode::from_code(code).is_none() {
+                return Err(LspError::ConfigError(format!(
+                    "unknown diagnostic code '{}' in severity",
+                    code
+                )));
+            }
+        }
+        if !(1..=32).contains(&self.interrupts.lines) {
+            return Err(LspError::ConfigError(format!(
+                "interrupt lines must be between 1 and 32, not {}",
+                self.interrupts.lines
+            )));
+        }
+        Ok(self)
+    }
+
+    /// the configured instruction set, or the embedded one when none is set
+    pub fn instruction_database(&self) -> Result<InstructionDatabase, LspError> {
+        match &self.instruction_set {
+            Some(path) => InstructionDatabase::from_file(path)
+                .map(|instruction_db| {
+                    instruction_db.with_case_sensitive(self.mnemonics.case_sensitive)
+                })
+                .map_err(|error| LspError::ConfigError(format!("{}: {}", path.display(), error))),
+            None => Ok(self.builtin_instruction_database()),
+        }
+    }
+
+    /// exported labels and configured interrupt handlers, which are entered
+    /// from outside the document
+    pub fn exported_labels(&self) -> Vec<String> {
+        let mut labels = self.labels.exported.clone();
+        for handler in &self.interrupts.handlers {
+            if !labels.contains(handler) {
+                labels.push(handler.clone());
+            }
+        }
+        labels
+    }
+
+    /// the embedded instruction set, with the configured case sensitivity
+    pub fn builtin_instruction_database(&self) -> InstructionDatabase {
+        InstructionDatabase::new().with_case_sensitive(self.mnemonics.case_sensitive)
+    }
+
+    pub fn memory_model(&self) -> MemoryModel {
+        MemoryModel::new(self.memory.word_bits, self.memory.address_bits)
+    }
+
+    /// whether the code is never reported, by its severity, its rule group or
+    /// its layout check level
+    pub fn disables(&self, code: DiagnosticCode) -> bool {
+        let layout_level = match code {
+            DiagnosticCode::Sem034 => Some(self.layout.store_into_code),
+            DiagnosticCode::Sem035 => Some(self.layout.jump_into_data),
+            DiagnosticCode::Sem036 => Some(self.layout.fall_into_data),
+            _ => None,
+        };
+        code.group().is_some_and(|group| !self.rules.is_enabled(group))
+            || self.severity.get(code.as_str()) == Some(&SeverityLevel::Off)
+            || layout_level == Some(CheckLevel::Off)
+    }
+
+    /// drops diagnostics whose code or rule group is switched off and applies
+    /// severity overrides; diagnostics without a code are kept as they are
+    pub fn apply(&self, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        diagnostics
+            .into_iter()
+            .filter_map(|mut diagnostic| {
+                let Some(code) = DiagnosticCode::of(&diagnostic) else {
+                    return Some(diagnostic);
+                };
+                if code.group().is_some_and(|group| !self.rules.is_enabled(group)) {
+                    return None;
+                }
+                if let Some(level) = self.severity.get(code.as_str()) {
+                    diagnostic.severity = Some(level.severity()?);
+                }
+                Some(diagnostic)
+            })
+            .collect()
+    }
+}
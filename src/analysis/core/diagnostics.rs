@@ -1,50 +1,192 @@
+use crate::analysis::core::{DocumentCache, ParsedDocument, StatementError};
+use crate::analysis::flow::{FlowAnalysisProvider, InterruptConfig, LayoutConfig};
 use crate::analysis::language::ValidationProvider;
 use crate::analysis::utils::{
-    create_parser_diagnostic, 
+    char_len, create_parser_diagnostic, get_word_at_position, word_range, DiagnosticCode,
+    InstructionDatabase, IsaProfile, MemoryModel,
 };
+use lexariel::LexerError;
+use parseid::ParserError;
 use tower_lsp::lsp_types::*;
 
 #[derive(Debug)]
 pub struct DiagnosticsEngine {
     validation_provider: ValidationProvider,
+    flow_provider: FlowAnalysisProvider,
+    documents: DocumentCache,
+}
+
+impl Default for DiagnosticsEngine {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl DiagnosticsEngine {
     pub fn new() -> Self {
+        Self::with_cache(DocumentCache::new())
+    }
+
+    pub fn with_cache(documents: DocumentCache) -> Self {
         Self {
-            validation_provider: ValidationProvider::new(),
+            validation_provider: ValidationProvider::with_cache(documents.clone()),
+            flow_provider: FlowAnalysisProvider::with_cache(documents.clone()),
+            documents,
         }
     }
 
+    pub fn with_instruction_db(mut self, instruction_db: InstructionDatabase) -> Self {
+        self.validation_provider = self
+            .validation_provider
+            .with_instruction_db(instruction_db.clone());
+        self.flow_provider = self.flow_provider.with_instruction_db(instruction_db);
+        self
+    }
+
+    pub fn with_memory_model(mut self, memory_model: MemoryModel) -> Self {
+        self.validation_provider = self.validation_provider.with_memory_model(memory_model);
+        self.flow_provider = self.flow_provider.with_memory_model(memory_model);
+        self
+    }
+
+    pub fn with_interrupt_config(mut self, interrupts: InterruptConfig) -> Self {
+        self.flow_provider = self.flow_provider.with_interrupt_config(interrupts);
+        self
+    }
+
+    pub fn with_exported_labels(mut self, exported_labels: Vec<String>) -> Self {
+        self.validation_provider = self.validation_provider.with_exported_labels(exported_labels);
+        self
+    }
+
+    pub fn with_isa_profile(mut self, isa: IsaProfile) -> Self {
+        self.validation_provider = self.validation_provider.with_isa_profile(isa);
+        self
+    }
+
+    pub fn with_mnemonic_normalization(mut self, normalize: bool) -> Self {
+        self.validation_provider = self.validation_provider.with_mnemonic_normalization(normalize);
+        self
+    }
+
+    pub fn with_layout_config(mut self, layout: LayoutConfig) -> Self {
+        self.flow_provider = self.flow_provider.with_layout_config(layout);
+        self
+    }
+
     /// returns diagnostics
-    pub fn analyze_document(&self, content: &str, _uri: &Url) -> Vec<Diagnostic> {
+    pub fn analyze_document(&self, content: &str, uri: &Url) -> Vec<Diagnostic> {
         let mut diagnostics = Vec::new();
+        let document = self.documents.get(content);
+
+        // a malformed literal explains the lexer or parser error on its line
+        let literal_diagnostics = self.validation_provider.validate_numeric_literals(content);
+        let malformed_lines: Vec<u32> = literal_diagnostics
+            .iter()
+            .filter(|d| DiagnosticCode::of(d) == Some(DiagnosticCode::Sem015))
+            .map(|d| d.range.start.line)
+            .collect();
 
-        match lexariel::tokenize(content) {
-            Ok(tokens) => {
-                // tokens into AST
-                match parseid::parse(tokens) {
-                    Ok(ast) => {
-                        diagnostics.extend(self.validation_provider.validate_semantics(&ast));
-                    }
-                    Err(parse_error) => {
-                        diagnostics.push(self.parser_error_to_diagnostic(parse_error));
-                    }
-                }
+        // broken lines are reported and skipped, everything else is still validated
+        for lexer_error in document.lexer_errors() {
+            let diagnostic = self.lexer_error_to_diagnostic(&document, lexer_error);
+            if !malformed_lines.contains(&diagnostic.range.start.line) {
+                diagnostics.push(diagnostic);
             }
-            Err(lexer_error) => {
-                diagnostics.push(self.lexer_error_to_diagnostic(lexer_error));
+        }
+        for statement_error in document.parser_errors() {
+            let diagnostic = self.parser_error_to_diagnostic(&document, statement_error);
+            if !malformed_lines.contains(&diagnostic.range.start.line) {
+                diagnostics.push(diagnostic);
             }
         }
+        diagnostics.extend(literal_diagnostics);
+        diagnostics.extend(self.validation_provider.validate_semantics(content));
+        diagnostics.extend(self.validation_provider.validate_label_definitions(content, uri));
+        diagnostics.extend(self.validation_provider.validate_label_references(content, uri));
+        diagnostics.extend(self.validation_provider.validate_unused_labels(content));
+        diagnostics.extend(self.flow_provider.validate_control_flow(content, uri));
 
         diagnostics
     }
 
-    fn lexer_error_to_diagnostic(&self, error: lexariel::LexerError) -> Diagnostic {
-        create_parser_diagnostic(format!("Lexer error: {}", error), "LEX001")
+    fn lexer_error_to_diagnostic(&self, document: &ParsedDocument, error: &LexerError) -> Diagnostic {
+        create_parser_diagnostic(
+            lexer_error_range(document, error),
+            format!("Lexer error: {}", error),
+            DiagnosticCode::Lex001)
     }
 
-    fn parser_error_to_diagnostic(&self, error: parseid::ParserError) -> Diagnostic {
-        create_parser_diagnostic(format!("Parser error: {}", error), "PAR001")
+    fn parser_error_to_diagnostic(
+        &self,
+        document: &ParsedDocument,
+        statement_error: &StatementError,
+    ) -> Diagnostic {
+        create_parser_diagnostic(
+            parser_error_range(document, statement_error),
+            format!("Parser error: {}", statement_error.error),
+            DiagnosticCode::Par001)
+    }
+}
+
+/// underlines the offending text; lexer positions are 1-based char columns
+fn lexer_error_range(document: &ParsedDocument, error: &LexerError) -> Range {
+    match error {
+        LexerError::UnknownToken { line, column, token } => {
+            source_range(document, *line, *column, char_len(token))
+        }
+        LexerError::InvalidNumberFormat { line, column, value } => {
+            source_range(document, *line, *column, char_len(value))
+        }
+        LexerError::UnterminatedString { line, column } => {
+            // runs to the end of the line
+            let line_len = document.line(line.saturating_sub(1)).map(char_len).unwrap_or(0);
+            let rest = line_len.saturating_sub(column.saturating_sub(1));
+            source_range(document, *line, *column, rest)
+        }
+        LexerError::InvalidCharacter { line, column, .. } => {
+            source_range(document, *line, *column, 1)
+        }
     }
 }
+
+fn parser_error_range(document: &ParsedDocument, statement_error: &StatementError) -> Range {
+    match &statement_error.error {
+        ParserError::UnexpectedToken { line, column, .. }
+        | ParserError::InvalidAddressingMode { line, column, .. }
+        | ParserError::MissingOperand { line, column, .. }
+        | ParserError::InvalidMacroDefinition { line, column, .. } => {
+            token_range(document, *line, *column)
+        }
+        // no position in the error, the statement ended after its last token
+        ParserError::UnexpectedEof { .. } => {
+            let token = &statement_error.statement_end;
+            token_range(document, token.line, token.column)
+        }
+        ParserError::LexerError(error) => lexer_error_range(document, error),
+    }
+}
+
+/// range of the token starting at (line, column), or of the word found there
+fn token_range(document: &ParsedDocument, line: usize, column: usize) -> Range {
+    let length = match document.token_at(line, column) {
+        Some(token) if !token.value.is_empty() => char_len(&token.value),
+        _ => document
+            .line(line.saturating_sub(1))
+            .and_then(|text| get_word_at_position(text, column.saturating_sub(1)))
+            .map(|(word, _, _)| char_len(&word))
+            .unwrap_or(1),
+    };
+
+    source_range(document, line, column, length)
+}
+
+/// 1-based position and char length to a range clamped to the line
+fn source_range(document: &ParsedDocument, line: usize, column: usize, length: usize) -> Range {
+    let line_num = line.saturating_sub(1);
+    let line_len = document.line(line_num).map(char_len).unwrap_or(0);
+    let start = column.saturating_sub(1).min(line_len);
+    let end = (start + length.max(1)).min(line_len).max(start);
+
+    word_range(line_num as u32, start, end)
+}
@@ -1,3 +1,4 @@
+use crate::analysis::utils::{LineIndex, PositionEncoding};
 use tower_lsp::lsp_types::*;
 
 #[derive(Debug, Clone)]
@@ -6,28 +7,87 @@ pub struct DocumentState {
     pub content: String,
     pub version: i32,
     pub diagnostics: Vec<Diagnostic>,
+    line_index: LineIndex,
+    position_encoding: PositionEncoding,
 }
 
 impl DocumentState {
     pub fn new(uri: Url, content: String, version: i32) -> Self {
+        let line_index = LineIndex::new(&content);
         Self {
             uri,
             content,
             version,
             diagnostics: Vec::new(),
+            line_index,
+            position_encoding: PositionEncoding::default(),
         }
     }
 
+    /// encoding of the columns in incoming ranged changes
+    pub fn with_position_encoding(mut self, encoding: PositionEncoding) -> Self {
+        self.position_encoding = encoding;
+        self
+    }
+
     pub fn get_uri(&self) -> &Url {
         &self.uri
     }
 
+    pub fn line_index(&self) -> &LineIndex {
+        &self.line_index
+    }
+
     pub fn update_content(&mut self, content: String, version: i32) {
+        self.line_index = LineIndex::new(&content);
         self.content = content;
-        self.version = version;
+        self.set_version(version);
         self.diagnostics.clear(); // clear old diagnostics
     }
 
+    /// applies `didChange` events in order; ranged events are incremental edits,
+    /// events without range replace the whole text
+    pub fn apply_changes(&mut self, changes: Vec<TextDocumentContentChangeEvent>, version: i32) {
+        for change in changes {
+            match change.range {
+                Some(range) => self.apply_edit(range, &change.text),
+                None => {
+                    self.line_index = LineIndex::new(&change.text);
+                    self.content = change.text;
+                }
+            }
+        }
+
+        self.set_version(version);
+        self.diagnostics.clear(); // clear old diagnostics
+    }
+
+    fn apply_edit(&mut self, range: Range, new_text: &str) {
+        let encoding = self.position_encoding;
+        let start = self.line_index.offset(&self.content, range.start, encoding);
+        let end = self
+            .line_index
+            .offset(&self.content, range.end, encoding)
+            .max(start);
+
+        self.content.replace_range(start..end, new_text);
+        self.line_index.apply_edit(start, end, new_text);
+    }
+
+    /// versions only move forward, even if the client reorders notifications
+    fn set_version(&mut self, version: i32) {
+        if version < self.version {
+            tracing::warn!(
+                "Ignoring out-of-order version {} for {} (current {})",
+                version,
+                self.uri,
+                self.version
+            );
+            return;
+        }
+        self.version = version;
+    }
+
     pub fn set_diagnostics(&mut self, diagnostics: Vec<Diagnostic>) {
         self.diagnostics = diagnostics;
     }
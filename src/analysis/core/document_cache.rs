@@ -0,0 +1,80 @@
+use super::ParsedDocument;
+use crate::analysis::utils::{InstructionDatabase, MemoryModel};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+/// how many recently seen document versions stay parsed
+const CACHE_CAPACITY: usize = 16;
+
+/// parsed documents keyed by a hash of their text, shared by all providers of
+/// an analyzer
+///
+/// every version of a document has distinct text, so a lookup by content is a
+/// lookup by version that works for callers that only hold the text
+#[derive(Debug, Clone, Default)]
+pub struct DocumentCache {
+    state: Arc<Mutex<CacheState>>,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: VecDeque<(u64, Arc<ParsedDocument>)>,
+    instruction_db: InstructionDatabase,
+    memory_model: MemoryModel,
+}
+
+impl DocumentCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// instruction set documents are analyzed with; drops documents parsed
+    /// with the previous one
+    pub fn set_instruction_db(&self, instruction_db: InstructionDatabase) {
+        let mut state = self.state.lock().unwrap();
+        state.instruction_db = instruction_db;
+        state.entries.clear();
+    }
+
+    /// memory model documents are analyzed with; drops documents parsed with
+    /// the previous one
+    pub fn set_memory_model(&self, memory_model: MemoryModel) {
+        let mut state = self.state.lock().unwrap();
+        state.memory_model = memory_model;
+        state.entries.clear();
+    }
+
+    /// parsed document for content, parsing it on first use
+    pub fn get(&self, content: &str) -> Arc<ParsedDocument> {
+        let key = content_hash(content);
+        let mut state = self.state.lock().unwrap();
+
+        let found = state
+            .entries
+            .iter()
+            .position(|(hash, doc)| *hash == key && doc.content() == content);
+        if let Some(idx) = found {
+            let entry = state.entries.remove(idx).unwrap();
+            let document = entry.1.clone();
+            state.entries.push_front(entry);
+            return document;
+        }
+
+        let document = Arc::new(ParsedDocument::parse_with(
+            content,
+            state.instruction_db.clone(),
+            state.memory_model,
+        ));
+        state.entries.push_front((key, document.clone()));
+        state.entries.truncate(CACHE_CAPACITY);
+        document
+    }
+}
+
+fn content_hash(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
@@ -1,7 +1,20 @@
 pub mod analyzer;
-pub mod document;
+pub mod config;
 pub mod diagnostics;
+pub mod document;
+pub mod document_cache;
+pub mod parsed_document;
+pub mod suppressions;
+pub mod symbol_table;
 
 pub use analyzer::SemanticAnalyzer;
-pub use document::DocumentState;
+pub use config::{
+    LabelConfig, LabelNaming, MemoryConfig, RuleGroups, SeverityLevel, WorkspaceConfig,
+    CONFIG_FILE_NAMES,
+};
 pub use diagnostics::DiagnosticsEngine;
+pub use document::DocumentState;
+pub use document_cache::DocumentCache;
+pub use parsed_document::{ParsedDocument, StatementError};
+pub use suppressions::{suppression_pragmas, SuppressionKind, SuppressionPragma, Suppressions};
+pub use symbol_table::{LabelReference, LabelSymbol, SymbolTable};
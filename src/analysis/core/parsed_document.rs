@@ -0,0 +1,346 @@
+use super::{LabelSymbol, SymbolTable};
+use crate::analysis::flow::{
+    handler_pragmas, AddressLayout, ControlFlowGraph, StackAnalysis, ValueAnalysis,
+};
+use crate::analysis::utils::{
+    find_word_occurrences, strip_comment, InstructionDatabase, LineIndex, MemoryModel,
+};
+use lexariel::{LexerError, Token, TokenKind};
+use parseid::{ParserError, Program, ProgramElement};
+use std::sync::OnceLock;
+
+/// parser error together with the statement that failed
+#[derive(Debug)]
+pub struct StatementError {
+    pub error: ParserError,
+    /// last token of the statement, where errors without a position are placed
+    pub statement_end: Token,
+}
+
+/// everything derived from one version of a document, computed once and
+/// shared by all providers; the flow analyses run on first use
+#[derive(Debug)]
+pub struct ParsedDocument {
+    content: String,
+    line_index: LineIndex,
+    tokens: Vec<Token>,
+    lexer_errors: Vec<LexerError>,
+    ast: Program,
+    parser_errors: Vec<StatementError>,
+    symbols: SymbolTable,
+    cfg: ControlFlowGraph,
+    instruction_db: InstructionDatabase,
+    memory_model: MemoryModel,
+    stack: OnceLock<StackAnalysis>,
+    values: OnceLock<ValueAnalysis>,
+    layout: OnceLock<AddressLayout>,
+}
+
+impl ParsedDocument {
+    pub fn parse(content: &str) -> Self {
+        Self::parse_with(content, InstructionDatabase::new(), MemoryModel::default())
+    }
+
+    /// parsed for the given instruction set and memory model, which the
+    /// value analysis runs with
+    pub fn parse_with(
+        content: &str,
+        instruction_db: InstructionDatabase,
+        memory_model: MemoryModel,
+    ) -> Self {
+        let line_index = LineIndex::new(content);
+        let (tokens, lexer_errors) = tokenize_lines(content, &line_index);
+
+        // lines that failed to lex are left out, the rest still gets an AST
+        let (ast, parser_errors) = parse_statements(&tokens);
+
+        let lines: Vec<&str> = (0..line_index.line_count())
+            .filter_map(|line| line_index.line_text(content, line))
+            .collect();
+        let symbols = SymbolTable::build(&lines, &ast);
+        let cfg = ControlFlowGraph::build(&ast, &instruction_db);
+
+        Self {
+            content: content.to_string(),
+            line_index,
+            tokens,
+            lexer_errors,
+            ast,
+            parser_errors,
+            symbols,
+            cfg,
+            instruction_db,
+            memory_model,
+            stack: OnceLock::new(),
+            values: OnceLock::new(),
+            layout: OnceLock::new(),
+        }
+    }
+
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    pub fn line_index(&self) -> &LineIndex {
+        &self.line_index
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_index.line_count()
+    }
+
+    /// line text without terminator, 0-based
+    pub fn line(&self, line: usize) -> Option<&str> {
+        self.line_index.line_text(&self.content, line)
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        (0..self.line_count()).filter_map(|line| self.line(line))
+    }
+
+    /// token stream ending with `Eof`; lines with lexer errors contribute no tokens
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    /// token starting at 1-based (line, column), as reported in lexer/parser errors
+    pub fn token_at(&self, line: usize, column: usize) -> Option<&Token> {
+        self.tokens
+            .iter()
+            .find(|token| token.line == line && token.column == column)
+    }
+
+    /// span of the operand following the token at 1-based (line, column), as
+    /// (0-based line, start, end) char columns
+    pub fn operand_span(&self, line: usize, column: usize) -> Option<(usize, usize, usize)> {
+        let mut rest = self
+            .tokens
+            .iter()
+            .filter(|token| token.line == line && token.kind != TokenKind::Eof)
+            .skip_while(|token| token.column <= column);
+
+        let first = rest.next()?;
+        let last = rest.last().unwrap_or(first);
+        let start = first.column - 1;
+        let end = last.column - 1 + last.value.chars().count();
+        Some((line - 1, start, end))
+    }
+
+    /// lexer errors in line order, at most one per line
+    pub fn lexer_errors(&self) -> &[LexerError] {
+        &self.lexer_errors
+    }
+
+    /// program built from every line that parsed
+    pub fn ast(&self) -> &Program {
+        &self.ast
+    }
+
+    /// parser errors in document order, at most one per statement
+    pub fn parser_errors(&self) -> &[StatementError] {
+        &self.parser_errors
+    }
+
+    pub fn symbols(&self) -> &SymbolTable {
+        &self.symbols
+    }
+
+    /// control flow of the top-level code
+    pub fn cfg(&self) -> &ControlFlowGraph {
+        &self.cfg
+    }
+
+    /// SDP/PZS stack depth along the control flow
+    pub fn stack(&self) -> &StackAnalysis {
+        self.stack.get_or_init(|| StackAnalysis::analyze(&self.cfg))
+    }
+
+    /// AK and memory values along the control flow
+    pub fn values(&self) -> &ValueAnalysis {
+        self.values.get_or_init(|| {
+            ValueAnalysis::analyze(&self.cfg, &self.instruction_db, &self.memory_model)
+        })
+    }
+
+    /// addresses of the instructions and data cells
+    pub fn layout(&self) -> &AddressLayout {
+        self.layout.get_or_init(|| AddressLayout::build(&self.cfg))
+    }
+
+    /// labels nothing in the code refers to; labels naming the entry point or
+    /// an interrupt handler pragma, and exported ones, are used from outside
+    pub fn unused_labels(&self, exported: &[String]) -> Vec<&LabelSymbol> {
+        let entry = self
+            .cfg
+            .entry()
+            .map(|block| self.cfg.block(block).labels.as_slice())
+            .unwrap_or_default();
+        let handlers = handler_pragmas(self.lines());
+
+        self.symbols
+            .labels()
+            .iter()
+            .filter(|label| !entry.contains(&label.name) && !exported.contains(&label.name))
+            .filter(|label| !handlers.iter().any(|handler| handler.name == label.name))
+            .filter(|label| {
+                self.word_occurrences(&label.name)
+                    .into_iter()
+                    .all(|(line, start, _)| self.symbols.is_definition_at(line, start))
+            })
+            .collect()
+    }
+
+    /// opcode of every instruction and name of every macro call, with its
+    /// (line, start, end) char columns
+    pub fn mnemonics(&self) -> Vec<(&str, usize, usize, usize)> {
+        self.ast
+            .elements
+            .iter()
+            .filter_map(|element| match element {
+                ProgramElement::Instruction(instruction) => {
+                    Some((instruction.opcode.as_str(), instruction.line, instruction.column))
+                }
+                ProgramElement::MacroCall(call) => Some((call.name.as_str(), call.line, call.column)),
+                _ => None,
+            })
+            .map(|(name, line, column)| {
+                (name, line - 1, column - 1, column - 1 + name.chars().count())
+            })
+            .collect()
+    }
+
+    /// whole-word occurrences outside comments as (line, start, end) char columns
+    pub fn word_occurrences(&self, word: &str) -> Vec<(usize, usize, usize)> {
+        self.lines()
+            .enumerate()
+            .flat_map(|(line_num, line)| {
+                find_word_occurrences(strip_comment(line), word)
+                    .into_iter()
+                    .map(move |(_, start, end)| (line_num, start, end))
+            })
+            .collect()
+    }
+}
+
+/// lexes line by line; tokens never span lines, so this matches lexing the
+/// whole text while letting every line report its own error
+fn tokenize_lines(content: &str, line_index: &LineIndex) -> (Vec<Token>, Vec<LexerError>) {
+    let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+
+    for line_num in 0..line_index.line_count() {
+        let line = line_index.line_text(content, line_num).unwrap_or("");
+        match lexariel::tokenize(line) {
+            Ok(line_tokens) => {
+                tokens.extend(
+                    line_tokens
+                        .into_iter()
+                        .filter(|token| token.kind != TokenKind::Eof)
+                        .map(|token| Token {
+                            line: line_num + 1,
+                            ..token
+                        }),
+                );
+            }
+            Err(error) => errors.push(shift_lexer_error(error, line_num)),
+        }
+    }
+
+    let eof_line = line_index.line_count();
+    let eof_column = line_index
+        .line_text(content, eof_line - 1)
+        .map(|line| line.chars().count() + 1)
+        .unwrap_or(1);
+    tokens.push(Token::new(TokenKind::Eof, String::new(), eof_line, eof_column));
+
+    (tokens, errors)
+}
+
+/// parses statement by statement; parseid does not stop at line ends, so
+/// feeding it one statement at a time keeps an error from swallowing the
+/// lines after it and lets the rest of the file still end up in the AST
+fn parse_statements(tokens: &[Token]) -> (Program, Vec<StatementError>) {
+    let mut program = Program::new();
+    let mut errors = Vec::new();
+
+    for statement in split_statements(tokens) {
+        let last = statement[statement.len() - 1].clone();
+        let mut statement_tokens = statement.to_vec();
+        statement_tokens.push(Token::new(
+            TokenKind::Eof,
+            String::new(),
+            last.line,
+            last.column + last.value.chars().count(),
+        ));
+
+        match parseid::parse(statement_tokens) {
+            Ok(parsed) => program.elements.extend(parsed.elements),
+            Err(error) => errors.push(StatementError {
+                error,
+                statement_end: last,
+            }),
+        }
+    }
+
+    (program, errors)
+}
+
+/// one statement per line, except macro definitions which run to `KONM`
+fn split_statements(tokens: &[Token]) -> Vec<&[Token]> {
+    let tokens = match tokens.iter().position(|token| token.kind == TokenKind::Eof) {
+        Some(eof) => &tokens[..eof],
+        None => tokens,
+    };
+
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut in_macro = false;
+
+    for idx in 0..tokens.len() {
+        let token = &tokens[idx];
+        if is_directive(token, "MAKRO") {
+            in_macro = true;
+        } else if is_directive(token, "KONM") {
+            in_macro = false;
+        }
+
+        let line_ends = tokens
+            .get(idx + 1)
+            .map(|next| next.line != token.line)
+            .unwrap_or(true);
+        if line_ends && !in_macro {
+            statements.push(&tokens[start..=idx]);
+            start = idx + 1;
+        }
+    }
+
+    // unterminated macro, parser reports the missing KONM
+    if start < tokens.len() {
+        statements.push(&tokens[start..]);
+    }
+
+    statements
+}
+
+fn is_directive(token: &Token, name: &str) -> bool {
+    token.kind == TokenKind::Directive && token.value.eq_ignore_ascii_case(name)
+}
+
+/// moves an error reported for a single line to its place in the document
+fn shift_lexer_error(error: LexerError, line_num: usize) -> LexerError {
+    let line = line_num + 1;
+    match error {
+        LexerError::UnknownToken { column, token, .. } => {
+            LexerError::UnknownToken { line, column, token }
+        }
+        LexerError::InvalidNumberFormat { column, value, .. } => {
+            LexerError::InvalidNumberFormat { line, column, value }
+        }
+        LexerError::UnterminatedString { column, .. } => {
+            LexerError::UnterminatedString { line, column }
+        }
+        LexerError::InvalidCharacter { column, character, .. } => {
+            LexerError::InvalidCharacter { line, column, character }
+        }
+    }
+}
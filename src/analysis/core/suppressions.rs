@@ -0,0 +1,228 @@
+use crate::analysis::utils::{comment_text, create_diagnostic, word_range, DiagnosticCode};
+use std::ops::RangeInclusive;
+use tower_lsp::lsp_types::Diagnostic;
+
+/// comment pragmas silencing diagnostics, longest first so that
+/// `asmod-disable` does not swallow the other two
+const PRAGMAS: &[(&str, SuppressionKind)] = &[
+    ("asmod-disable-next-line", SuppressionKind::NextLine),
+    ("asmod-disable-file", SuppressionKind::File),
+    ("asmod-disable", SuppressionKind::Disable),
+    ("asmod-enable", SuppressionKind::Enable),
+];
+
+/// codes end here, the rest of the comment is the reason for the suppression
+const REASON_SEPARATOR: &str = "--";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuppressionKind {
+    /// `; asmod-disable-next-line CODE...`
+    NextLine,
+    /// `; asmod-disable CODE...`, up to the matching `asmod-enable`
+    Disable,
+    /// `; asmod-enable CODE...`
+    Enable,
+    /// `; asmod-disable-file CODE...`
+    File,
+}
+
+impl SuppressionKind {
+    pub fn pragma(self) -> &'static str {
+        PRAGMAS
+            .iter()
+            .find(|(_, kind)| *kind == self)
+            .map(|(pragma, _)| *pragma)
+            .unwrap()
+    }
+}
+
+/// code named by a suppression pragma, or the pragma itself when it names no
+/// code and so covers every code; columns are char columns, line is 0-based
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuppressionPragma {
+    pub kind: SuppressionKind,
+    pub code: Option<String>,
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl SuppressionPragma {
+    fn matches(&self, code: Option<DiagnosticCode>) -> bool {
+        match (&self.code, code) {
+            (None, _) => true,
+            (Some(named), Some(code)) => named.eq_ignore_ascii_case(code.as_str()),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// every code named by a suppression pragma, in document order
+pub fn suppression_pragmas<'a>(lines: impl IntoIterator<Item = &'a str>) -> Vec<SuppressionPragma> {
+    let mut pragmas = Vec::new();
+
+    for (line_num, line) in lines.into_iter().enumerate() {
+        let Some(comment) = comment_text(line) else {
+            continue;
+        };
+        let comment = comment.trim_start();
+        let Some((kind, rest)) = PRAGMAS.iter().find_map(|(pragma, kind)| {
+            let rest = comment.strip_prefix(pragma)?;
+            (rest.is_empty() || rest.starts_with(char::is_whitespace)).then_some((*kind, rest))
+        }) else {
+            continue;
+        };
+
+        let codes = rest.split(REASON_SEPARATOR).next().unwrap_or_default();
+        // byte offset of the codes within line
+        let codes_offset = line.len() - rest.len();
+        let mut search_from = 0;
+        let mut named_any = false;
+        for code in codes.split_whitespace() {
+            let byte_pos = codes_offset + search_from + codes[search_from..].find(code).unwrap();
+            search_from = byte_pos - codes_offset + code.len();
+            let start = line[..byte_pos].chars().count();
+            pragmas.push(SuppressionPragma {
+                kind,
+                code: Some(code.to_string()),
+                line: line_num,
+                start,
+                end: start + code.chars().count(),
+            });
+            named_any = true;
+        }
+
+        if !named_any {
+            let end = line[..codes_offset].chars().count();
+            pragmas.push(SuppressionPragma {
+                kind,
+                code: None,
+                line: line_num,
+                start: end - kind.pragma().chars().count(),
+                end,
+            });
+        }
+    }
+
+    pragmas
+}
+
+/// disabling pragma and the 0-based lines it covers
+#[derive(Debug, Clone)]
+struct Suppression {
+    pragma: SuppressionPragma,
+    lines: RangeInclusive<u32>,
+}
+
+/// the suppressions of a document, applied to its diagnostics
+#[derive(Debug, Clone, Default)]
+pub struct Suppressions {
+    entries: Vec<Suppression>,
+}
+
+impl Suppressions {
+    pub fn parse<'a>(lines: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut entries: Vec<Suppression> = Vec::new();
+        // entries opened by `asmod-disable` and not yet enabled again
+        let mut open: Vec<usize> = Vec::new();
+
+        for pragma in suppression_pragmas(lines) {
+            let line = pragma.line as u32;
+            let lines = match pragma.kind {
+                SuppressionKind::NextLine => line + 1..=line + 1,
+                SuppressionKind::File => 0..=u32::MAX,
+                SuppressionKind::Disable => {
+                    open.push(entries.len());
+                    line..=u32::MAX
+                }
+                SuppressionKind::Enable => {
+                    open.retain(|&index| {
+                        let entry = &mut entries[index];
+                        let closes = match (&pragma.code, &entry.pragma.code) {
+                            (None, _) => true,
+                            (Some(enabled), Some(disabled)) => enabled.eq_ignore_ascii_case(disabled),
+                            (Some(_), None) => false,
+                        };
+                        if closes {
+                            entry.lines = *entry.lines.start()..=line;
+                        }
+                        !closes
+                    });
+                    continue;
+                }
+            };
+            entries.push(Suppression { pragma, lines });
+        }
+
+        Self { entries }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// indices of the entries covering the diagnostic
+    fn covering<'a>(&'a self, diagnostic: &Diagnostic) -> impl Iterator<Item = usize> + 'a {
+        let code = DiagnosticCode::of(diagnostic);
+        let line = diagnostic.range.start.line;
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(move |(_, entry)| entry.lines.contains(&line) && entry.pragma.matches(code))
+            .map(|(index, _)| index)
+    }
+
+    /// whether a pragma covers the diagnostic
+    pub fn suppresses(&self, diagnostic: &Diagnostic) -> bool {
+        self.covering(diagnostic).next().is_some()
+    }
+
+    /// drops suppressed diagnostics, then reports the pragmas that silenced
+    /// nothing; pragmas naming a code that is `disabled` anyway are not
+    /// reported, it had nothing to silence
+    pub fn apply(
+        &self,
+        diagnostics: Vec<Diagnostic>,
+        disabled: impl Fn(DiagnosticCode) -> bool,
+    ) -> Vec<Diagnostic> {
+        if self.is_empty() {
+            return diagnostics;
+        }
+
+        let mut used = vec![false; self.entries.len()];
+        let mut kept = Vec::new();
+        for diagnostic in diagnostics {
+            let mut suppressed = false;
+            for index in self.covering(&diagnostic) {
+                used[index] = true;
+                suppressed = true;
+            }
+            if !suppressed {
+                kept.push(diagnostic);
+            }
+        }
+
+        let unused = self.entries.iter().zip(used).filter(|(_, used)| !used);
+        for (entry, _) in unused {
+            let pragma = &entry.pragma;
+            let named = pragma
+                .code
+                .as_ref()
+                .map(|code| (code, DiagnosticCode::from_code(&code.to_uppercase())));
+            let message = match named {
+                Some((_, Some(code))) if disabled(code) => continue,
+                Some((code, None)) => {
+                    format!("Unknown diagnostic code '{}' in suppression", code)
+                }
+                Some((code, Some(_))) => format!("Suppression of '{}' is never used", code),
+                None => format!("Suppression '{}' is never used", pragma.kind.pragma()),
+            };
+            kept.push(create_diagnostic(
+                word_range(pragma.line as u32, pragma.start, pragma.end),
+                DiagnosticCode::Sem038,
+                message));
+        }
+
+        kept
+    }
+}
@@ -0,0 +1,168 @@
+use crate::analysis::utils::{
+    char_len, find_word_occurrences, indentation_width, is_valid_symbol_name, strip_comment,
+};
+use parseid::{AddressingMode, Program, ProgramElement};
+use std::collections::HashMap;
+
+/// label definition; columns are char columns, line is 0-based
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelSymbol {
+    pub name: String,
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// label used as an instruction operand, e.g. `loop` in `SOB loop`
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelReference {
+    pub name: String,
+    pub opcode: String,
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// labels defined and referenced in a document
+///
+/// a label definition is whatever precedes the first `:` in the code part of
+/// a line; candidates that are not valid symbol names are kept separately so
+/// validation can report them
+#[derive(Debug, Clone, Default)]
+pub struct SymbolTable {
+    labels: Vec<LabelSymbol>,
+    invalid_labels: Vec<LabelSymbol>,
+    references: Vec<LabelReference>,
+    by_name: HashMap<String, Vec<usize>>,
+}
+
+impl SymbolTable {
+    pub fn build(lines: &[&str], program: &Program) -> Self {
+        let mut table = Self {
+            references: collect_references(lines, program),
+            ..Self::default()
+        };
+
+        for (line_num, line) in lines.iter().enumerate() {
+            let candidate = match scan_label_definition(line, line_num) {
+                Some(candidate) => candidate,
+                None => continue,
+            };
+
+            if is_valid_symbol_name(&candidate.name) {
+                table
+                    .by_name
+                    .entry(candidate.name.clone())
+                    .or_default()
+                    .push(table.labels.len());
+                table.labels.push(candidate);
+            } else {
+                table.invalid_labels.push(candidate);
+            }
+        }
+
+        table
+    }
+
+    /// valid label definitions in document order
+    pub fn labels(&self) -> &[LabelSymbol] {
+        &self.labels
+    }
+
+    /// definitions whose name is not a valid symbol name
+    pub fn invalid_labels(&self) -> &[LabelSymbol] {
+        &self.invalid_labels
+    }
+
+    /// label operands in document order
+    pub fn references(&self) -> &[LabelReference] {
+        &self.references
+    }
+
+    /// first definition of label
+    pub fn find_label(&self, name: &str) -> Option<&LabelSymbol> {
+        self.definitions(name).next()
+    }
+
+    /// every definition of label, in document order
+    pub fn definitions<'a>(&'a self, name: &str) -> impl Iterator<Item = &'a LabelSymbol> + 'a {
+        self.by_name
+            .get(name)
+            .into_iter()
+            .flatten()
+            .map(|&idx| &self.labels[idx])
+    }
+
+    pub fn is_defined(&self, name: &str) -> bool {
+        self.by_name.contains_key(name)
+    }
+
+    /// whether a label definition starts at (line, column)
+    pub fn is_definition_at(&self, line: usize, column: usize) -> bool {
+        self.labels
+            .iter()
+            .any(|label| label.line == line && label.start == column)
+    }
+}
+
+fn scan_label_definition(line: &str, line_num: usize) -> Option<LabelSymbol> {
+    let code = strip_comment(line);
+    let trimmed = code.trim();
+    let colon_pos = trimmed.find(':')?;
+    let name = &trimmed[..colon_pos];
+    let start = indentation_width(code);
+
+    Some(LabelSymbol {
+        name: name.to_string(),
+        line: line_num,
+        start,
+        end: start + char_len(name),
+    })
+}
+
+/// operands naming a label; macro bodies are skipped since their operands may
+/// be macro parameters
+fn collect_references(lines: &[&str], program: &Program) -> Vec<LabelReference> {
+    let mut references = Vec::new();
+
+    for element in &program.elements {
+        let instruction = match element {
+            ProgramElement::Instruction(instruction) => instruction,
+            _ => continue,
+        };
+        let operand = match &instruction.operand {
+            Some(operand) => operand,
+            None => continue,
+        };
+
+        let name = match &operand.addressing_mode {
+            AddressingMode::Direct | AddressingMode::Indirect | AddressingMode::MultipleIndirect => {
+                operand.value.as_str()
+            }
+            AddressingMode::Indexed { address, .. } => address.as_str(),
+            _ => continue,
+        };
+        if !is_valid_symbol_name(name) {
+            continue;
+        }
+
+        // operand sits after the opcode on the instruction's line
+        let line_num = instruction.line.saturating_sub(1);
+        let code = strip_comment(lines.get(line_num).copied().unwrap_or(""));
+        let after_opcode = instruction.column.saturating_sub(1) + char_len(&instruction.opcode);
+        if let Some((_, start, end)) = find_word_occurrences(code, name)
+            .into_iter()
+            .find(|(_, start, _)| *start >= after_opcode)
+        {
+            references.push(LabelReference {
+                name: name.to_string(),
+                opcode: instruction.opcode.clone(),
+                line: line_num,
+                start,
+                end,
+            });
+        }
+    }
+
+    references
+}
@@ -0,0 +1,393 @@
+use parseid::{Instruction, MacroCall, Operand, Program, ProgramElement};
+use crate::analysis::utils::InstructionDatabase;
+use std::collections::{HashMap, VecDeque};
+
+pub type BlockId = usize;
+
+/// executable statement; macro calls are treated as straight-line code
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    /// with the mnemonic its opcode spells in the instruction set, `None`
+    /// for an unknown instruction
+    Instruction(Instruction, Option<String>),
+    MacroCall(MacroCall),
+}
+
+impl Statement {
+    /// 1-based, as reported by parseid
+    pub fn line(&self) -> usize {
+        match self {
+            Self::Instruction(instruction, _) => instruction.line,
+            Self::MacroCall(call) => call.line,
+        }
+    }
+
+    /// 1-based char column
+    pub fn column(&self) -> usize {
+        match self {
+            Self::Instruction(instruction, _) => instruction.column,
+            Self::MacroCall(call) => call.column,
+        }
+    }
+
+    /// opcode, or macro name for macro calls
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Instruction(instruction, _) => &instruction.opcode,
+            Self::MacroCall(call) => &call.name,
+        }
+    }
+
+    pub fn operand(&self) -> Option<&Operand> {
+        match self {
+            Self::Instruction(instruction, _) => instruction.operand.as_ref(),
+            Self::MacroCall(_) => None,
+        }
+    }
+
+    /// whether the statement is the instruction with the mnemonic, however
+    /// the instruction set lets it be spelled; `lad` is `ŁAD`
+    pub fn is_opcode(&self, mnemonic: &str) -> bool {
+        self.mnemonic() == Some(mnemonic)
+    }
+
+    /// mnemonic the instruction set resolved the opcode to, `None` for macro
+    /// calls and unknown instructions
+    pub fn mnemonic(&self) -> Option<&str> {
+        match self {
+            Self::Instruction(_, resolved) => resolved.as_deref(),
+            Self::MacroCall(_) => None,
+        }
+    }
+}
+
+/// how control leaves a basic block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Terminator {
+    /// continues into the next block
+    FallThrough,
+    /// `SOB`
+    Jump,
+    /// `SOM` / `SOZ`, taken or falling through
+    Branch,
+    /// `STP`
+    Stop,
+    /// `PWR`, return from interrupt
+    Return,
+    /// last statement runs past the end of the program
+    FallsOffEnd,
+    /// execution continues into a data cell (`RST` / `RPA`)
+    FallsIntoData,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeKind {
+    FallThrough,
+    Jump,
+    BranchTaken,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    pub target: BlockId,
+    pub kind: EdgeKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasicBlock {
+    pub id: BlockId,
+    /// indices into `ControlFlowGraph::statements`, in program order
+    pub statements: Vec<usize>,
+    /// labels naming the first statement
+    pub labels: Vec<String>,
+    pub terminator: Terminator,
+    /// for branches, `FallsOffEnd` / `FallsIntoData` when the not-taken path
+    /// leaves the code
+    pub branch_exit: Option<Terminator>,
+    pub successors: Vec<Edge>,
+    pub predecessors: Vec<BlockId>,
+}
+
+impl BasicBlock {
+    /// `FallsOffEnd` / `FallsIntoData` when some path out of the block runs
+    /// past the code
+    pub fn leaves_code(&self) -> Option<Terminator> {
+        match self.terminator {
+            Terminator::FallsOffEnd | Terminator::FallsIntoData => Some(self.terminator),
+            _ => self.branch_exit,
+        }
+    }
+}
+
+/// data cell declared with `RST` / `RPA`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataCell {
+    pub labels: Vec<String>,
+    pub directive: String,
+    /// initial value, as written after `RST`
+    pub value: Option<String>,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// basic blocks of a program in program order; execution starts at the first
+/// executable statement
+#[derive(Debug, Clone, Default)]
+pub struct ControlFlowGraph {
+    statements: Vec<Statement>,
+    blocks: Vec<BasicBlock>,
+    data_cells: Vec<DataCell>,
+    label_blocks: HashMap<String, BlockId>,
+    /// jump or branch whose label names no code, as (block, label)
+    unresolved_targets: Vec<(BlockId, String)>,
+}
+
+/// layout item, in program order
+enum Item {
+    Statement(Statement, Vec<String>),
+    Data(DataCell),
+}
+
+impl ControlFlowGraph {
+    /// opcodes are resolved against the instruction set, so its aliases and
+    /// case sensitivity decide what each statement does
+    pub fn build(program: &Program, instruction_db: &InstructionDatabase) -> Self {
+        let mut cfg = Self::default();
+        let items = collect_items(program, instruction_db);
+
+        // block boundaries
+        let mut current: Option<BlockId> = None;
+        let mut open_branch: Option<BlockId> = None;
+        for item in items {
+            match item {
+                Item::Data(cell) => {
+                    if let Some(block) = current.take() {
+                        cfg.blocks[block].terminator = Terminator::FallsIntoData;
+                    }
+                    if let Some(block) = open_branch.take() {
+                        cfg.blocks[block].branch_exit = Some(Terminator::FallsIntoData);
+                    }
+                    cfg.data_cells.push(cell);
+                }
+                Item::Statement(statement, labels) => {
+                    open_branch = None;
+                    let starts_block = current.is_none() || !labels.is_empty();
+                    if starts_block {
+                        if let Some(block) = current {
+                            cfg.blocks[block].terminator = Terminator::FallThrough;
+                        }
+                        current = Some(cfg.new_block(labels));
+                    }
+
+                    let block = current.unwrap();
+                    let terminator = statement_terminator(&statement);
+                    cfg.blocks[block].statements.push(cfg.statements.len());
+                    cfg.statements.push(statement);
+
+                    if terminator != Terminator::FallThrough {
+                        cfg.blocks[block].terminator = terminator;
+                        current = None;
+                        if terminator == Terminator::Branch {
+                            open_branch = Some(block);
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(block) = current {
+            cfg.blocks[block].terminator = Terminator::FallsOffEnd;
+        }
+        if let Some(block) = open_branch {
+            cfg.blocks[block].branch_exit = Some(Terminator::FallsOffEnd);
+        }
+
+        cfg.connect_blocks();
+        cfg
+    }
+
+    fn new_block(&mut self, labels: Vec<String>) -> BlockId {
+        let id = self.blocks.len();
+        for label in &labels {
+            self.label_blocks.entry(label.clone()).or_insert(id);
+        }
+        self.blocks.push(BasicBlock {
+            id,
+            statements: Vec::new(),
+            labels,
+            terminator: Terminator::FallsOffEnd,
+            branch_exit: None,
+            successors: Vec::new(),
+            predecessors: Vec::new(),
+        });
+        id
+    }
+
+    fn connect_blocks(&mut self) {
+        for id in 0..self.blocks.len() {
+            let terminator = self.blocks[id].terminator;
+            let mut successors = Vec::new();
+
+            if matches!(terminator, Terminator::Jump | Terminator::Branch) {
+                let last = *self.blocks[id].statements.last().unwrap();
+                let target = self.statements[last]
+                    .operand()
+                    .map(|operand| operand.value.clone())
+                    .unwrap_or_default();
+
+                let kind = if terminator == Terminator::Jump {
+                    EdgeKind::Jump
+                } else {
+                    EdgeKind::BranchTaken
+                };
+                match self.label_blocks.get(&target) {
+                    Some(&target) => successors.push(Edge { target, kind }),
+                    None => self.unresolved_targets.push((id, target)),
+                }
+            }
+
+            let falls_through = terminator == Terminator::FallThrough
+                || (terminator == Terminator::Branch && self.blocks[id].branch_exit.is_none());
+            if falls_through && id + 1 < self.blocks.len() {
+                successors.push(Edge {
+                    target: id + 1,
+                    kind: EdgeKind::FallThrough,
+                });
+            }
+
+            for edge in &successors {
+                self.blocks[edge.target].predecessors.push(id);
+            }
+            self.blocks[id].successors = successors;
+        }
+    }
+
+    pub fn statements(&self) -> &[Statement] {
+        &self.statements
+    }
+
+    pub fn blocks(&self) -> &[BasicBlock] {
+        &self.blocks
+    }
+
+    pub fn block(&self, id: BlockId) -> &BasicBlock {
+        &self.blocks[id]
+    }
+
+    pub fn data_cells(&self) -> &[DataCell] {
+        &self.data_cells
+    }
+
+    /// block where execution starts
+    pub fn entry(&self) -> Option<BlockId> {
+        if self.blocks.is_empty() {
+            None
+        } else {
+            Some(0)
+        }
+    }
+
+    /// block whose first statement carries label
+    pub fn block_of_label(&self, label: &str) -> Option<BlockId> {
+        self.label_blocks.get(label).copied()
+    }
+
+    /// jumps and branches whose target label names no code
+    pub fn unresolved_targets(&self) -> &[(BlockId, String)] {
+        &self.unresolved_targets
+    }
+
+    /// statements of block, in program order
+    pub fn block_statements(&self, id: BlockId) -> impl Iterator<Item = &Statement> {
+        self.blocks[id]
+            .statements
+            .iter()
+            .map(move |&idx| &self.statements[idx])
+    }
+
+    /// no data cell lies between the end of first and the start of second,
+    /// so code running off first continues in second
+    pub fn adjacent(&self, first: BlockId, second: BlockId) -> bool {
+        let after = self.block_statements(first).last().unwrap().line();
+        let before = self.block_statements(second).next().unwrap().line();
+        !self
+            .data_cells
+            .iter()
+            .any(|cell| cell.line > after && cell.line < before)
+    }
+
+    /// blocks reachable from the entry
+    pub fn reachable(&self) -> Vec<bool> {
+        self.reachable_from(self.entry())
+    }
+
+    /// blocks reachable from any of roots
+    pub fn reachable_from(&self, roots: impl IntoIterator<Item = BlockId>) -> Vec<bool> {
+        let mut reachable = vec![false; self.blocks.len()];
+        let mut queue: VecDeque<BlockId> = roots.into_iter().collect();
+
+        while let Some(id) = queue.pop_front() {
+            if reachable[id] {
+                continue;
+            }
+            reachable[id] = true;
+            queue.extend(self.blocks[id].successors.iter().map(|edge| edge.target));
+        }
+
+        reachable
+    }
+}
+
+/// labels attach to the statement or data cell that follows them
+fn collect_items(program: &Program, instruction_db: &InstructionDatabase) -> Vec<Item> {
+    let mut items = Vec::new();
+    let mut pending_labels = Vec::new();
+
+    for element in &program.elements {
+        match element {
+            ProgramElement::LabelDefinition(label) => pending_labels.push(label.name.clone()),
+            ProgramElement::Instruction(instruction) => items.push(Item::Statement(
+                Statement::Instruction(
+                    instruction.clone(),
+                    instruction_db
+                        .canonical_name(&instruction.opcode)
+                        .map(str::to_string),
+                ),
+                std::mem::take(&mut pending_labels),
+            )),
+            ProgramElement::MacroCall(call) => items.push(Item::Statement(
+                Statement::MacroCall(call.clone()),
+                std::mem::take(&mut pending_labels),
+            )),
+            ProgramElement::Directive(directive) if is_data_directive(&directive.name) => {
+                items.push(Item::Data(DataCell {
+                    labels: std::mem::take(&mut pending_labels),
+                    directive: directive.name.to_uppercase(),
+                    value: directive.arguments.first().cloned(),
+                    line: directive.line,
+                    column: directive.column,
+                }))
+            }
+            ProgramElement::Directive(_) | ProgramElement::MacroDefinition(_) => {}
+        }
+    }
+
+    items
+}
+
+fn statement_terminator(statement: &Statement) -> Terminator {
+    if statement.is_opcode("SOB") {
+        Terminator::Jump
+    } else if statement.is_opcode("SOM") || statement.is_opcode("SOZ") {
+        Terminator::Branch
+    } else if statement.is_opcode("STP") {
+        Terminator::Stop
+    } else if statement.is_opcode("PWR") {
+        Terminator::Return
+    } else {
+        Terminator::FallThrough
+    }
+}
+
+pub fn is_data_directive(name: &str) -> bool {
+    name.eq_ignore_ascii_case("RST") || name.eq_ignore_ascii_case("RPA")
+}
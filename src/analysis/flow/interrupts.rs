@@ -0,0 +1,207 @@
+use super::{BlockId, ControlFlowGraph, EdgeKind, Terminator};
+use crate::analysis::utils::comment_text;
+use serde::Deserialize;
+
+/// Machine W masks one bit per interrupt line, `MSK` takes an 8-bit mask
+pub const DEFAULT_INTERRUPT_LINES: u32 = 8;
+
+/// comment pragma declaring handler entry labels, `; asmod-handler name...`
+const HANDLER_PRAGMA: &str = "asmod-handler";
+
+/// interrupt lines of the machine and handler entry labels declared outside
+/// the document
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InterruptConfig {
+    pub lines: u32,
+    pub handlers: Vec<String>,
+}
+
+impl Default for InterruptConfig {
+    fn default() -> Self {
+        Self::new(DEFAULT_INTERRUPT_LINES, Vec::new())
+    }
+}
+
+impl InterruptConfig {
+    /// lines are clamped to 1..=32
+    pub fn new(lines: u32, handlers: Vec<String>) -> Self {
+        Self {
+            lines: lines.clamp(1, 32),
+            handlers,
+        }
+    }
+
+    /// mask sets no bit past the last line
+    pub fn fits_mask(&self, mask: i128) -> bool {
+        (0..(1i128 << self.lines)).contains(&mask)
+    }
+
+    /// lines whose bit is set in mask, 0-based
+    pub fn masked_lines(&self, mask: i128) -> Vec<u32> {
+        (0..self.lines).filter(|line| (mask >> line) & 1 == 1).collect()
+    }
+
+    /// configured handlers followed by the ones declared with pragmas in lines
+    pub fn handlers_in<'a>(&self, lines: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+        let mut handlers = self.handlers.clone();
+        for pragma in handler_pragmas(lines) {
+            if !handlers.contains(&pragma.name) {
+                handlers.push(pragma.name);
+            }
+        }
+        handlers
+    }
+}
+
+/// label named by an `; asmod-handler` comment; columns are char columns,
+/// line is 0-based
+#[derive(Debug, Clone, PartialEq)]
+pub struct HandlerPragma {
+    pub name: String,
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// every label named by a handler pragma, in document order
+pub fn handler_pragmas<'a>(lines: impl IntoIterator<Item = &'a str>) -> Vec<HandlerPragma> {
+    let mut pragmas = Vec::new();
+
+    for (line_num, line) in lines.into_iter().enumerate() {
+        let Some(comment) = comment_text(line) else {
+            continue;
+        };
+        let Some(names) = comment.trim_start().strip_prefix(HANDLER_PRAGMA) else {
+            continue;
+        };
+        if !names.is_empty() && !names.starts_with(char::is_whitespace) {
+            continue;
+        }
+
+        // byte offset of the names within line
+        let names_offset = line.len() - names.len();
+        let mut search_from = 0;
+        for name in names.split_whitespace() {
+            let byte_pos = names_offset + search_from + names[search_from..].find(name).unwrap();
+            search_from = byte_pos - names_offset + name.len();
+            let start = line[..byte_pos].chars().count();
+            pragmas.push(HandlerPragma {
+                name: name.to_string(),
+                line: line_num,
+                start,
+                end: start + name.chars().count(),
+            });
+        }
+    }
+
+    pragmas
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InterruptIssue {
+    /// `PWR` reachable from the program entry
+    ReturnOutsideHandler { statement: usize },
+    /// path out of a handler that does not end in `PWR`; `exit` is
+    /// `FallsOffEnd`, `FallsIntoData`, or `FallThrough` into the handler `into`
+    HandlerFallsThrough {
+        handler: String,
+        block: BlockId,
+        exit: Terminator,
+        into: Option<String>,
+    },
+}
+
+/// code run on behalf of interrupt handlers, and the ways it can go wrong
+#[derive(Debug, Clone, Default)]
+pub struct InterruptAnalysis {
+    /// handler label and its entry block, for handlers naming code
+    entries: Vec<(String, BlockId)>,
+    in_handler: Vec<bool>,
+    issues: Vec<InterruptIssue>,
+}
+
+impl InterruptAnalysis {
+    pub fn analyze(cfg: &ControlFlowGraph, handlers: &[String]) -> Self {
+        let mut entries: Vec<(String, BlockId)> = Vec::new();
+        for handler in handlers {
+            if let Some(block) = cfg.block_of_label(handler) {
+                if !entries.iter().any(|(_, entry)| *entry == block) {
+                    entries.push((handler.clone(), block));
+                }
+            }
+        }
+        let handler_of = |block: BlockId| {
+            entries
+                .iter()
+                .find(|(_, entry)| *entry == block)
+                .map(|(name, _)| name.clone())
+        };
+
+        let mut issues = Vec::new();
+
+        // the program starting in a handler has no main code to check
+        let main_entry = cfg.entry().filter(|entry| handler_of(*entry).is_none());
+        let from_main = cfg.reachable_from(main_entry);
+        for block in cfg.blocks().iter().filter(|block| from_main[block.id]) {
+            for &statement in &block.statements {
+                if cfg.statements()[statement].is_opcode("PWR") {
+                    issues.push(InterruptIssue::ReturnOutsideHandler { statement });
+                }
+            }
+        }
+
+        let mut in_handler = vec![false; cfg.blocks().len()];
+        for (handler, entry) in &entries {
+            let reachable = cfg.reachable_from([*entry]);
+            for block in cfg.blocks().iter().filter(|block| reachable[block.id]) {
+                in_handler[block.id] = true;
+
+                if let Some(exit) = block.leaves_code() {
+                    issues.push(InterruptIssue::HandlerFallsThrough {
+                        handler: handler.clone(),
+                        block: block.id,
+                        exit,
+                        into: None,
+                    });
+                }
+
+                // running into the next handler is only caught by its label
+                let into = block
+                    .successors
+                    .iter()
+                    .filter(|edge| edge.kind == EdgeKind::FallThrough)
+                    .filter(|edge| edge.target != *entry)
+                    .find_map(|edge| handler_of(edge.target));
+                if let Some(into) = into {
+                    issues.push(InterruptIssue::HandlerFallsThrough {
+                        handler: handler.clone(),
+                        block: block.id,
+                        exit: Terminator::FallThrough,
+                        into: Some(into),
+                    });
+                }
+            }
+        }
+
+        Self {
+            entries,
+            in_handler,
+            issues,
+        }
+    }
+
+    /// handler labels naming code, with their entry blocks
+    pub fn entries(&self) -> &[(String, BlockId)] {
+        &self.entries
+    }
+
+    /// block is reachable from some handler entry
+    pub fn in_handler(&self, block: BlockId) -> bool {
+        self.in_handler.get(block).copied().unwrap_or(false)
+    }
+
+    pub fn issues(&self) -> &[InterruptIssue] {
+        &self.issues
+    }
+}
@@ -0,0 +1,151 @@
+use super::{BlockId, ControlFlowGraph, EdgeKind, Statement, Terminator};
+use std::collections::VecDeque;
+
+/// statements that may end a loop; waiting for input lets the user stop it
+const LOOP_EXITS: [&str; 5] = ["SOM", "SOZ", "STP", "WEJSCIE", "PWR"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JumpIssue {
+    /// `SOB` back to `label` with nothing in the loop that can leave it
+    InfiniteLoop { statement: usize, label: String },
+    /// jump or branch to the statement right after it
+    JumpToNext { statement: usize, label: String },
+    /// jump to `via`, whose first statement is a `SOB`; `target` is where
+    /// the chain of jumps ends
+    JumpChain {
+        statement: usize,
+        via: String,
+        target: String,
+    },
+}
+
+impl JumpIssue {
+    pub fn statement(&self) -> usize {
+        match self {
+            Self::InfiniteLoop { statement, .. }
+            | Self::JumpToNext { statement, .. }
+            | Self::JumpChain { statement, .. } => *statement,
+        }
+    }
+}
+
+/// loops without exit and jumps that could be removed or shortened
+#[derive(Debug, Clone, Default)]
+pub struct JumpAnalysis {
+    issues: Vec<JumpIssue>,
+}
+
+impl JumpAnalysis {
+    pub fn analyze(cfg: &ControlFlowGraph) -> Self {
+        let mut issues = Vec::new();
+
+        for block in cfg.blocks() {
+            let Some(target) = jump_target(cfg, block.id) else {
+                continue;
+            };
+            let statement = *block.statements.last().unwrap();
+            let label = jump_label(&cfg.statements()[statement]);
+
+            if block.terminator == Terminator::Jump
+                && target <= block.id
+                && loop_never_exits(cfg, target, block.id)
+            {
+                issues.push(JumpIssue::InfiniteLoop { statement, label });
+            } else if target == block.id + 1 && cfg.adjacent(block.id, target) {
+                issues.push(JumpIssue::JumpToNext { statement, label });
+            } else if let Some(end) = chain_end(cfg, block.id, target) {
+                issues.push(JumpIssue::JumpChain {
+                    statement,
+                    via: label,
+                    target: end,
+                });
+            }
+        }
+
+        Self { issues }
+    }
+
+    pub fn issues(&self) -> &[JumpIssue] {
+        &self.issues
+    }
+}
+
+/// block a jump or taken branch at the end of block goes to
+fn jump_target(cfg: &ControlFlowGraph, block: BlockId) -> Option<BlockId> {
+    cfg.block(block)
+        .successors
+        .iter()
+        .find(|edge| edge.kind != EdgeKind::FallThrough)
+        .map(|edge| edge.target)
+}
+
+fn jump_label(statement: &Statement) -> String {
+    statement
+        .operand()
+        .map(|operand| operand.value.clone())
+        .unwrap_or_default()
+}
+
+/// the loop from target back to the jump ending block is every block on a
+/// path between the two; it never exits when no path leaves it and it holds
+/// no branch, stop, input or macro call
+fn loop_never_exits(cfg: &ControlFlowGraph, target: BlockId, block: BlockId) -> bool {
+    let forward = cfg.reachable_from([target]);
+    if !forward[block] {
+        return false;
+    }
+    let backward = reaching(cfg, block);
+    let body: Vec<BlockId> = (0..cfg.blocks().len())
+        .filter(|&id| forward[id] && backward[id])
+        .collect();
+
+    body.iter().all(|&id| {
+        let current = cfg.block(id);
+        matches!(current.terminator, Terminator::FallThrough | Terminator::Jump)
+            && current.successors.iter().all(|edge| body.contains(&edge.target))
+            && cfg.block_statements(id).all(|statement| {
+                !matches!(statement, Statement::MacroCall(_))
+                    && !LOOP_EXITS.iter().any(|opcode| statement.is_opcode(opcode))
+            })
+    })
+}
+
+/// blocks from which block can be reached
+fn reaching(cfg: &ControlFlowGraph, block: BlockId) -> Vec<bool> {
+    let mut reached = vec![false; cfg.blocks().len()];
+    let mut queue = VecDeque::from([block]);
+
+    while let Some(id) = queue.pop_front() {
+        if reached[id] {
+            continue;
+        }
+        reached[id] = true;
+        queue.extend(cfg.block(id).predecessors.iter().copied());
+    }
+
+    reached
+}
+
+/// label the chain of `SOB`s starting at target finally jumps to; `None`
+/// when target does not start with a `SOB` or the chain loops
+fn chain_end(cfg: &ControlFlowGraph, block: BlockId, target: BlockId) -> Option<String> {
+    let mut visited = vec![block];
+    let mut current = target;
+    let mut end = None;
+
+    loop {
+        let first = cfg.block_statements(current).next()?;
+        if !first.is_opcode("SOB") {
+            return end;
+        }
+        end = Some(jump_label(first));
+        visited.push(current);
+
+        match jump_target(cfg, current) {
+            Some(next) if visited.contains(&next) => return None,
+            Some(next) => current = next,
+            // numeric address or undefined label, nothing further to follow
+            None => return end,
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use super::{cell_of, BlockId, Cell, ControlFlowGraph, Statement, Terminator};
+use parseid::AddressingMode;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// instructions whose operand is the address execution continues at
+const JUMP_OPCODES: [&str; 3] = ["SOB", "SOM", "SOZ"];
+
+/// what a word of the program image holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Region {
+    Code,
+    Data,
+}
+
+impl Region {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Code => "code",
+            Self::Data => "data",
+        }
+    }
+}
+
+/// how a layout check is reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckLevel {
+    Error,
+    Warning,
+    Off,
+}
+
+/// levels of the layout checks; storing into code is sometimes done on
+/// purpose, so it only warns by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct LayoutConfig {
+    pub store_into_code: CheckLevel,
+    pub jump_into_data: CheckLevel,
+    pub fall_into_data: CheckLevel,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            store_into_code: CheckLevel::Warning,
+            jump_into_data: CheckLevel::Error,
+            fall_into_data: CheckLevel::Warning,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayoutIssue {
+    /// `ŁAD` to a label or address holding an instruction
+    StoreIntoCode {
+        statement: usize,
+        target: String,
+        address: Option<usize>,
+    },
+    /// jump or branch to a label or address holding data
+    JumpIntoData { statement: usize, target: String },
+    /// path out of block continuing into the data cell at `address`
+    FallsIntoData {
+        block: BlockId,
+        address: Option<usize>,
+    },
+}
+
+/// addresses of the program image, loaded from address 0 with one word per
+/// instruction and data cell; macro expansions have unknown size, so
+/// addresses past the first macro call are unknown
+#[derive(Debug, Clone, Default)]
+pub struct AddressLayout {
+    /// region of each word up to the first macro call
+    regions: Vec<Region>,
+    statement_addresses: Vec<Option<usize>>,
+    data_addresses: Vec<Option<usize>>,
+    labels: HashMap<String, (Region, Option<usize>)>,
+}
+
+impl AddressLayout {
+    pub fn build(cfg: &ControlFlowGraph) -> Self {
+        let mut layout = Self::default();
+        let mut statements = cfg.statements().iter().peekable();
+        let mut cells = cfg.data_cells().iter().peekable();
+        let mut known = true;
+
+        // statements and data cells interleave in source order
+        loop {
+            let statement_first = match (statements.peek(), cells.peek()) {
+                (Some(statement), Some(cell)) => {
+                    (statement.line(), statement.column()) < (cell.line, cell.column)
+                }
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => break,
+            };
+            let address = known.then_some(layout.regions.len());
+
+            if statement_first {
+                let statement = statements.next().unwrap();
+                layout.statement_addresses.push(address);
+                if let Statement::MacroCall(_) = statement {
+                    known = false;
+                } else if known {
+                    layout.regions.push(Region::Code);
+                }
+            } else {
+                let cell = cells.next().unwrap();
+                layout.data_addresses.push(address);
+                for label in &cell.labels {
+                    layout
+                        .labels
+                        .entry(label.clone())
+                        .or_insert((Region::Data, address));
+                }
+                if known {
+                    layout.regions.push(Region::Data);
+                }
+            }
+        }
+
+        for block in cfg.blocks() {
+            let address = layout.statement_addresses[block.statements[0]];
+            for label in &block.labels {
+                layout
+                    .labels
+                    .entry(label.clone())
+                    .or_insert((Region::Code, address));
+            }
+        }
+
+        layout
+    }
+
+    /// address of the statement with the given index
+    pub fn statement_address(&self, statement: usize) -> Option<usize> {
+        self.statement_addresses.get(statement).copied().flatten()
+    }
+
+    /// address of the data cell with the given index
+    pub fn data_address(&self, cell: usize) -> Option<usize> {
+        self.data_addresses.get(cell).copied().flatten()
+    }
+
+    /// region and, when known, address a label names
+    pub fn label(&self, label: &str) -> Option<(Region, Option<usize>)> {
+        self.labels.get(label).copied()
+    }
+
+    /// region holding address; `None` past the known part of the image
+    pub fn region_at(&self, address: i128) -> Option<Region> {
+        usize::try_from(address)
+            .ok()
+            .and_then(|address| self.regions.get(address).copied())
+    }
+
+    fn region_of(&self, cell: &Cell) -> Option<(Region, Option<usize>)> {
+        match cell {
+            Cell::Label(label) => self.label(label),
+            Cell::Address(address) => self
+                .region_at(*address)
+                .map(|region| (region, Some(*address as usize))),
+        }
+    }
+
+    /// stores into code, jumps into data and blocks running into data
+    pub fn issues(&self, cfg: &ControlFlowGraph) -> Vec<LayoutIssue> {
+        let mut issues = Vec::new();
+
+        for (index, statement) in cfg.statements().iter().enumerate() {
+            let Some(operand) = statement.operand() else {
+                continue;
+            };
+            // through a pointer the target is only known at run time
+            if operand.addressing_mode != AddressingMode::Direct {
+                continue;
+            }
+            let Some((region, address)) = cell_of(&operand.value).and_then(|cell| self.region_of(&cell))
+            else {
+                continue;
+            };
+
+            if region == Region::Code && statement.is_opcode("ŁAD") {
+                issues.push(LayoutIssue::StoreIntoCode {
+                    statement: index,
+                    target: operand.value.clone(),
+                    address,
+                });
+            } else if region == Region::Data
+                && JUMP_OPCODES.iter().any(|opcode| statement.is_opcode(opcode))
+            {
+                issues.push(LayoutIssue::JumpIntoData {
+                    statement: index,
+                    target: operand.value.clone(),
+                });
+            }
+        }
+
+        for block in cfg.blocks() {
+            if block.leaves_code() == Some(Terminator::FallsIntoData) {
+                let last = *block.statements.last().unwrap();
+                issues.push(LayoutIssue::FallsIntoData {
+                    block: block.id,
+                    address: self.statement_address(last).map(|address| address + 1),
+                });
+            }
+        }
+
+        issues
+    }
+}
@@ -0,0 +1,284 @@
+use super::{BlockId, ControlFlowGraph, Statement};
+use crate::analysis::utils::{is_valid_symbol_name, parse_number_literal};
+use parseid::AddressingMode;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt;
+
+/// instructions that read their memory operand
+const READ_OPCODES: [&str; 6] = ["POB", "DOD", "ODE", "MNO", "DZI", "MOD"];
+
+/// longest path kept for a report
+const MAX_PATH_BLOCKS: usize = 16;
+
+/// memory cell named by an operand
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Cell {
+    Label(String),
+    Address(i128),
+}
+
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Label(name) => write!(f, "{}", name),
+            Self::Address(address) => write!(f, "{}", address),
+        }
+    }
+}
+
+/// read of a cell that no store reached on some path from the entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct UninitializedRead {
+    pub statement: usize,
+    pub cell: Cell,
+    /// blocks from the entry to the read, none of which stores to the cell
+    pub path: Vec<BlockId>,
+}
+
+/// store to a cell that nothing reads
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnreadWrite {
+    pub statement: usize,
+    pub cell: Cell,
+}
+
+/// cells holding no defined value: `RPA` reservations and addresses past the
+/// loaded program
+#[derive(Debug, Clone, Default)]
+pub struct MemoryAnalysis {
+    uninitialized_reads: Vec<UninitializedRead>,
+    unread_writes: Vec<UnreadWrite>,
+}
+
+/// cells possibly unwritten, each with the predecessor it arrived from
+type UnwrittenCells = BTreeMap<Cell, Option<BlockId>>;
+
+impl MemoryAnalysis {
+    pub fn analyze(cfg: &ControlFlowGraph) -> Self {
+        let tracked = uninitialized_cells(cfg);
+        let entry = unwritten_on_entry(cfg, &tracked);
+
+        let mut uninitialized_reads = Vec::new();
+        for block in cfg.blocks() {
+            let Some(mut unwritten) = entry[block.id].clone() else {
+                continue;
+            };
+            for &statement in &block.statements {
+                let current = &cfg.statements()[statement];
+                if let Some(cell) = read_cell(current) {
+                    if unwritten.contains_key(&cell) {
+                        uninitialized_reads.push(UninitializedRead {
+                            statement,
+                            path: witness_path(&entry, block.id, &cell),
+                            cell,
+                        });
+                    }
+                }
+                apply_writes(current, &mut unwritten);
+            }
+        }
+
+        Self {
+            uninitialized_reads,
+            unread_writes: unread_writes(cfg),
+        }
+    }
+
+    pub fn uninitialized_reads(&self) -> &[UninitializedRead] {
+        &self.uninitialized_reads
+    }
+
+    pub fn unread_writes(&self) -> &[UnreadWrite] {
+        &self.unread_writes
+    }
+}
+
+/// cell named by a direct or indirect operand; indirect reads go through the
+/// named cell first
+pub fn operand_cell(statement: &Statement) -> Option<Cell> {
+    let operand = statement.operand()?;
+    match operand.addressing_mode {
+        AddressingMode::Direct | AddressingMode::Indirect | AddressingMode::MultipleIndirect => {
+            cell_of(&operand.value)
+        }
+        _ => None,
+    }
+}
+
+/// cell named by an operand value, label or numeric address
+pub fn cell_of(value: &str) -> Option<Cell> {
+    match parse_number_literal(value) {
+        Some(address) => Some(Cell::Address(address)),
+        None if is_valid_symbol_name(value) => Some(Cell::Label(value.to_string())),
+        None => None,
+    }
+}
+
+fn is_read(statement: &Statement) -> bool {
+    READ_OPCODES.iter().any(|opcode| statement.is_opcode(opcode))
+}
+
+fn is_store(statement: &Statement) -> bool {
+    statement.is_opcode("ŁAD")
+}
+
+fn read_cell(statement: &Statement) -> Option<Cell> {
+    if is_read(statement) {
+        operand_cell(statement)
+    } else {
+        None
+    }
+}
+
+/// `ŁAD` writes its cell, or any cell through a pointer; macro calls may
+/// write the cells they are given
+fn apply_writes(statement: &Statement, unwritten: &mut UnwrittenCells) {
+    if let Statement::MacroCall(call) = statement {
+        for cell in call.arguments.iter().filter_map(|argument| cell_of(argument)) {
+            unwritten.remove(&cell);
+        }
+        return;
+    }
+    if !is_store(statement) {
+        return;
+    }
+
+    match statement.operand().map(|operand| &operand.addressing_mode) {
+        Some(AddressingMode::Direct) => {
+            if let Some(cell) = operand_cell(statement) {
+                unwritten.remove(&cell);
+            }
+        }
+        Some(_) => unwritten.clear(),
+        None => {}
+    }
+}
+
+/// `RPA` cells, and addresses past the program image when its size is known
+pub fn uninitialized_cells(cfg: &ControlFlowGraph) -> BTreeSet<Cell> {
+    let mut cells: BTreeSet<Cell> = cfg
+        .data_cells()
+        .iter()
+        .filter(|cell| cell.directive == "RPA")
+        .flat_map(|cell| cell.labels.iter().map(|label| Cell::Label(label.clone())))
+        .collect();
+
+    // macro expansions have unknown size
+    let has_macro_calls = cfg
+        .statements()
+        .iter()
+        .any(|statement| matches!(statement, Statement::MacroCall(_)));
+    if !has_macro_calls {
+        let image_size = (cfg.statements().len() + cfg.data_cells().len()) as i128;
+        cells.extend(
+            cfg.statements()
+                .iter()
+                .filter(|statement| is_read(statement))
+                .filter_map(operand_cell)
+                .filter(|cell| matches!(cell, Cell::Address(address) if *address >= image_size)),
+        );
+    }
+
+    cells
+}
+
+/// forward may-analysis; a cell stays unwritten while any path into the block
+/// skips every store to it
+fn unwritten_on_entry(
+    cfg: &ControlFlowGraph,
+    tracked: &BTreeSet<Cell>,
+) -> Vec<Option<UnwrittenCells>> {
+    let mut entry: Vec<Option<UnwrittenCells>> = vec![None; cfg.blocks().len()];
+    let mut queue = VecDeque::new();
+
+    if let Some(start) = cfg.entry() {
+        entry[start] = Some(tracked.iter().map(|cell| (cell.clone(), None)).collect());
+        queue.push_back(start);
+    }
+
+    while let Some(id) = queue.pop_front() {
+        let Some(mut unwritten) = entry[id].clone() else {
+            continue;
+        };
+        for statement in cfg.block_statements(id) {
+            apply_writes(statement, &mut unwritten);
+        }
+
+        for edge in &cfg.block(id).successors {
+            let target = entry[edge.target].get_or_insert_with(BTreeMap::new);
+            let mut changed = false;
+            for cell in unwritten.keys() {
+                if !target.contains_key(cell) {
+                    target.insert(cell.clone(), Some(id));
+                    changed = true;
+                }
+            }
+            // blocks entered with every cell written have nothing to report
+            if changed {
+                queue.push_back(edge.target);
+            }
+        }
+    }
+
+    entry
+}
+
+/// follows the recorded predecessors back to the entry
+fn witness_path(entry: &[Option<UnwrittenCells>], block: BlockId, cell: &Cell) -> Vec<BlockId> {
+    let mut path = vec![block];
+    let mut current = block;
+
+    while let Some(Some(previous)) = entry[current].as_ref().and_then(|cells| cells.get(cell)) {
+        if path.contains(previous) || path.len() >= MAX_PATH_BLOCKS {
+            break;
+        }
+        path.push(*previous);
+        current = *previous;
+    }
+
+    path.reverse();
+    path
+}
+
+/// direct stores to cells no instruction reads; a read through a pointer may
+/// reach any cell, so nothing is reported then
+fn unread_writes(cfg: &ControlFlowGraph) -> Vec<UnreadWrite> {
+    let mut read = BTreeSet::new();
+    for statement in cfg.statements() {
+        match statement {
+            Statement::MacroCall(call) => {
+                read.extend(call.arguments.iter().filter_map(|argument| cell_of(argument)))
+            }
+            Statement::Instruction(..) if is_read(statement) => {
+                let Some(operand) = statement.operand() else {
+                    continue;
+                };
+                match &operand.addressing_mode {
+                    AddressingMode::Direct => read.extend(cell_of(&operand.value)),
+                    AddressingMode::Immediate => {}
+                    _ => return Vec::new(),
+                }
+            }
+            Statement::Instruction(..) => {}
+        }
+    }
+
+    cfg.statements()
+        .iter()
+        .enumerate()
+        .filter(|(_, statement)| is_store(statement))
+        .filter(|(_, statement)| {
+            matches!(
+                statement.operand().map(|operand| &operand.addressing_mode),
+                Some(AddressingMode::Direct)
+            )
+        })
+        .filter_map(|(index, statement)| {
+            let cell = operand_cell(statement)?;
+            (!read.contains(&cell)).then_some(UnreadWrite {
+                statement: index,
+                cell,
+            })
+        })
+        .collect()
+}
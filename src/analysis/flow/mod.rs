@@ -0,0 +1,24 @@
+pub mod cfg;
+pub mod interrupts;
+pub mod jumps;
+pub mod layout;
+pub mod memory;
+pub mod provider;
+pub mod reaching;
+pub mod stack;
+pub mod values;
+
+pub use cfg::*;
+pub use interrupts::{
+    handler_pragmas, HandlerPragma, InterruptAnalysis, InterruptConfig, InterruptIssue,
+    DEFAULT_INTERRUPT_LINES,
+};
+pub use jumps::{JumpAnalysis, JumpIssue};
+pub use layout::{AddressLayout, CheckLevel, LayoutConfig, LayoutIssue, Region};
+pub use memory::{
+    cell_of, operand_cell, uninitialized_cells, Cell, MemoryAnalysis, UninitializedRead, UnreadWrite,
+};
+pub use reaching::{Definition, ReachingStores};
+pub use provider::FlowAnalysisProvider;
+pub use stack::{StackAnalysis, StackDepth, StackIssue};
+pub use values::{AbstractState, BranchOutcome, Effect, Interval, ValueAnalysis};
@@ -0,0 +1,601 @@
+use super::{
+    handler_pragmas, operand_cell, uninitialized_cells, BlockId, BranchOutcome, Cell, CheckLevel,
+    ControlFlowGraph, Definition, InterruptAnalysis, InterruptConfig, InterruptIssue, JumpAnalysis,
+    JumpIssue, LayoutConfig, LayoutIssue, MemoryAnalysis, ReachingStores, StackIssue, Statement,
+    Terminator, ValueAnalysis,
+};
+use crate::analysis::core::{DocumentCache, ParsedDocument};
+use crate::analysis::utils::{
+    char_len, create_diagnostic, parse_number_literal, strip_comment, word_range, DiagnosticCode,
+    InstructionDatabase, MemoryModel,
+};
+use parseid::AddressingMode;
+use tower_lsp::lsp_types::*;
+
+#[derive(Debug)]
+pub struct FlowAnalysisProvider {
+    instruction_db: InstructionDatabase,
+    documents: DocumentCache,
+    interrupts: InterruptConfig,
+    layout: LayoutConfig,
+}
+
+impl Default for FlowAnalysisProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FlowAnalysisProvider {
+    pub fn new() -> Self {
+        Self::with_cache(DocumentCache::new())
+    }
+
+    pub fn with_cache(documents: DocumentCache) -> Self {
+        Self {
+            instruction_db: InstructionDatabase::new(),
+            documents,
+            interrupts: InterruptConfig::default(),
+            layout: LayoutConfig::default(),
+        }
+    }
+
+    pub fn with_instruction_db(mut self, instruction_db: InstructionDatabase) -> Self {
+        self.documents.set_instruction_db(instruction_db.clone());
+        self.instruction_db = instruction_db;
+        self
+    }
+
+    pub fn with_memory_model(self, memory_model: MemoryModel) -> Self {
+        self.documents.set_memory_model(memory_model);
+        self
+    }
+
+    pub fn with_interrupt_config(mut self, interrupts: InterruptConfig) -> Self {
+        self.interrupts = interrupts;
+        self
+    }
+
+    pub fn with_layout_config(mut self, layout: LayoutConfig) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// unreachable code, paths running past the last instruction or into
+    /// data, stores into code and jumps into data, loops without exit and
+    /// needless jumps, misplaced or missing PWR, unbalanced SDP/PZS, memory
+    /// read before it is written and branches or divisions decided by
+    /// constant values
+    pub fn validate_control_flow(&self, content: &str, uri: &Url) -> Vec<Diagnostic> {
+        let document = self.documents.get(content);
+        let interrupts = self.interrupt_analysis(&document);
+        let mut diagnostics = self.validate_reachability(&document, &interrupts);
+        diagnostics.extend(self.validate_program_end(&document, &interrupts));
+        diagnostics.extend(self.validate_layout(&document, &interrupts));
+        diagnostics.extend(self.validate_jumps(&document));
+        diagnostics.extend(self.validate_interrupts(&document, &interrupts));
+        diagnostics.extend(self.validate_stack_balance(&document));
+        diagnostics.extend(self.validate_memory_use(&document, uri));
+        diagnostics.extend(self.validate_constant_values(&document));
+        diagnostics
+    }
+
+    fn validate_layout(
+        &self,
+        document: &ParsedDocument,
+        interrupts: &InterruptAnalysis,
+    ) -> Vec<Diagnostic> {
+        let cfg = document.cfg();
+        let reachable = reachable_blocks(cfg, interrupts);
+        let mut diagnostics = Vec::new();
+
+        for issue in document.layout().issues(cfg) {
+            match issue {
+                LayoutIssue::StoreIntoCode {
+                    statement,
+                    target,
+                    address,
+                } => {
+                    let Some(severity) = check_severity(self.layout.store_into_code) else {
+                        continue;
+                    };
+                    let location = match address {
+                        Some(address) => format!("the instruction at address {}", address),
+                        None => "an instruction".to_string(),
+                    };
+                    diagnostics.push(Diagnostic {
+                        severity: Some(severity),
+                        ..create_diagnostic(
+                            operand_range(document, &cfg.statements()[statement]),
+                            DiagnosticCode::Sem034,
+                            format!("ŁAD to '{}' overwrites {}", target, location),
+                        )
+                    });
+                }
+                LayoutIssue::JumpIntoData { statement, target } => {
+                    let Some(severity) = check_severity(self.layout.jump_into_data) else {
+                        continue;
+                    };
+                    let statement = &cfg.statements()[statement];
+                    diagnostics.push(Diagnostic {
+                        severity: Some(severity),
+                        ..create_diagnostic(
+                            operand_range(document, statement),
+                            DiagnosticCode::Sem035,
+                            format!(
+                                "{} to '{}' jumps into data, not code",
+                                statement.name().to_uppercase(),
+                                target
+                            ),
+                        )
+                    });
+                }
+                LayoutIssue::FallsIntoData { block, address } => {
+                    // handlers running into data are reported as missing PWR
+                    if !reachable[block] || interrupts.in_handler(block) {
+                        continue;
+                    }
+                    let Some(severity) = check_severity(self.layout.fall_into_data) else {
+                        continue;
+                    };
+                    let last = last_statement(cfg, block);
+                    let mut message = match address {
+                        Some(address) => {
+                            format!("Execution runs from code into data at address {}", address)
+                        }
+                        None => "Execution runs from code into data".to_string(),
+                    };
+                    if cfg.block(block).terminator == Terminator::Branch {
+                        message.push_str(&format!(" when {} is not taken", last.name()));
+                    }
+                    diagnostics.push(Diagnostic {
+                        severity: Some(severity),
+                        ..create_diagnostic(
+                            statement_range(document, last),
+                            DiagnosticCode::Sem036,
+                            message,
+                        )
+                    });
+                }
+            }
+        }
+
+        diagnostics
+    }
+
+    fn validate_jumps(&self, document: &ParsedDocument) -> Vec<Diagnostic> {
+        let cfg = document.cfg();
+        let values = document.values();
+
+        JumpAnalysis::analyze(cfg)
+            .issues()
+            .iter()
+            // a branch with a known outcome is reported as such instead
+            .filter(|issue| {
+                !matches!(issue, JumpIssue::JumpToNext { statement, .. }
+                    if values.branch_outcome(cfg, *statement).is_some())
+            })
+            .map(|issue| {
+                let statement = &cfg.statements()[issue.statement()];
+                match issue {
+                    JumpIssue::InfiniteLoop { label, .. } => create_diagnostic(
+                        statement_range(document, statement),
+                        DiagnosticCode::Sem031,
+                        format!(
+                            "Infinite loop: nothing between '{}' and this SOB can leave the loop",
+                            label
+                        )),
+                    JumpIssue::JumpToNext { label, .. } => create_diagnostic(
+                        statement_range(document, statement),
+                        DiagnosticCode::Sem032,
+                        format!("Jump to '{}' goes to the next instruction anyway", label)),
+                    JumpIssue::JumpChain { via, target, .. } => create_diagnostic(
+                        operand_range(document, statement),
+                        DiagnosticCode::Sem033,
+                        format!(
+                            "Jump to '{}' only continues to '{}', jump there directly",
+                            via, target
+                        )),
+                }
+            })
+            .collect()
+    }
+
+    /// handlers declared by configuration or by pragmas in the document
+    pub fn interrupt_handlers(&self, content: &str) -> Vec<String> {
+        self.interrupts.handlers_in(self.documents.get(content).lines())
+    }
+
+    fn interrupt_analysis(&self, document: &ParsedDocument) -> InterruptAnalysis {
+        let handlers = self.interrupts.handlers_in(document.lines());
+        InterruptAnalysis::analyze(document.cfg(), &handlers)
+    }
+
+    fn validate_interrupts(
+        &self,
+        document: &ParsedDocument,
+        interrupts: &InterruptAnalysis,
+    ) -> Vec<Diagnostic> {
+        let cfg = document.cfg();
+        let mut diagnostics = Vec::new();
+
+        for issue in interrupts.issues() {
+            diagnostics.push(match issue {
+                InterruptIssue::ReturnOutsideHandler { statement } => create_diagnostic(
+                    statement_range(document, &cfg.statements()[*statement]),
+                    DiagnosticCode::Sem027,
+                    "PWR is reachable from the main program, outside any interrupt handler"
+                        .to_string()),
+                InterruptIssue::HandlerFallsThrough {
+                    handler,
+                    block,
+                    exit,
+                    into,
+                } => {
+                    let reason = match (exit, into) {
+                        (_, Some(into)) => format!("falls through into handler '{}'", into),
+                        (Terminator::FallsIntoData, _) => "runs into data".to_string(),
+                        _ => "runs past its last instruction".to_string(),
+                    };
+                    create_diagnostic(
+                        statement_range(document, last_statement(cfg, *block)),
+                        DiagnosticCode::Sem028,
+                        format!("Interrupt handler '{}' {} without PWR", handler, reason))
+                }
+            });
+        }
+
+        // pragmas naming no label are most likely typos
+        let symbols = document.symbols();
+        for pragma in handler_pragmas(document.lines()) {
+            if !symbols.is_defined(&pragma.name) {
+                diagnostics.push(create_diagnostic(
+                    word_range(pragma.line as u32, pragma.start, pragma.end),
+                    DiagnosticCode::Sem030,
+                    format!("Interrupt handler '{}' is not a label in this file", pragma.name)));
+            }
+        }
+
+        for statement in cfg.statements().iter().filter(|s| s.is_opcode("MSK")) {
+            let Some(operand) = statement.operand() else {
+                continue;
+            };
+            if operand.addressing_mode != AddressingMode::Immediate {
+                continue;
+            }
+            let Some(mask) = parse_number_literal(&operand.value) else {
+                continue;
+            };
+            if !self.interrupts.fits_mask(mask) {
+                diagnostics.push(create_diagnostic(
+                    operand_range(document, statement),
+                    DiagnosticCode::Sem029,
+                    format!(
+                        "Interrupt mask {} does not fit the {} interrupt lines (0..{})",
+                        operand.value,
+                        self.interrupts.lines,
+                        (1i128 << self.interrupts.lines) - 1
+                    )));
+            }
+        }
+
+        diagnostics
+    }
+
+    fn validate_constant_values(&self, document: &ParsedDocument) -> Vec<Diagnostic> {
+        let cfg = document.cfg();
+        let analysis = document.values();
+        let mut diagnostics = Vec::new();
+
+        for (index, statement) in cfg.statements().iter().enumerate() {
+            let Some(ak) = analysis.ak_before(index) else {
+                continue;
+            };
+
+            if let Some(outcome) = analysis.branch_outcome(cfg, index) {
+                let (code, verdict) = match outcome {
+                    BranchOutcome::AlwaysTaken => (DiagnosticCode::Sem023, "always"),
+                    BranchOutcome::NeverTaken => (DiagnosticCode::Sem024, "never"),
+                };
+                diagnostics.push(create_diagnostic(
+                    statement_range(document, statement),
+                    code,
+                    format!(
+                        "{} {} jumps, AK is {} here",
+                        statement.name().to_uppercase(),
+                        verdict,
+                        ak
+                    )));
+            }
+        }
+
+        diagnostics.extend(self.validate_division(document, analysis));
+        diagnostics
+    }
+
+    /// `DZI` / `MOD` by a literal zero, or by a cell that only ever holds
+    /// zero or is never written
+    fn validate_division(&self, document: &ParsedDocument, values: &ValueAnalysis) -> Vec<Diagnostic> {
+        let cfg = document.cfg();
+        let reaching = ReachingStores::analyze(cfg);
+        let unwritten = uninitialized_cells(cfg);
+        let mut diagnostics = Vec::new();
+
+        for (index, statement) in cfg.statements().iter().enumerate() {
+            if !statement.is_opcode("DZI") && !statement.is_opcode("MOD") {
+                continue;
+            }
+            let Some(operand) = statement.operand() else {
+                continue;
+            };
+            let opcode = statement.name().to_uppercase();
+
+            if operand.addressing_mode == AddressingMode::Immediate {
+                if parse_number_literal(&operand.value) == Some(0) {
+                    diagnostics.push(create_diagnostic(
+                        operand_range(document, statement),
+                        DiagnosticCode::Sem025,
+                        format!("{} divides by zero", opcode)));
+                }
+                continue;
+            }
+
+            let Some(cell) = operand_cell(statement) else {
+                continue;
+            };
+            let definitions = reaching.reaching(index, &cell);
+            if definitions.is_empty() {
+                continue;
+            }
+
+            let holds_zero = |definition: &Definition| match definition {
+                Definition::Initial => {
+                    unwritten.contains(&cell) || initial_value(cfg, &cell) == Some(0)
+                }
+                Definition::Store(store) => {
+                    values.ak_before(*store).and_then(|ak| ak.as_exact()) == Some(0)
+                }
+                Definition::Unknown => false,
+            };
+            let never_written = unwritten.contains(&cell)
+                && definitions.iter().all(|definition| *definition == Definition::Initial);
+
+            let reason = if never_written {
+                "which is never written"
+            } else if definitions.iter().all(holds_zero) {
+                "whose only reaching stores are 0"
+            } else {
+                continue;
+            };
+            diagnostics.push(create_diagnostic(
+                operand_range(document, statement),
+                DiagnosticCode::Sem026,
+                format!("{} divides by '{}', {}", opcode, cell, reason)));
+        }
+
+        diagnostics
+    }
+
+    fn validate_memory_use(&self, document: &ParsedDocument, uri: &Url) -> Vec<Diagnostic> {
+        let cfg = document.cfg();
+        let analysis = MemoryAnalysis::analyze(cfg);
+        let mut diagnostics = Vec::new();
+
+        for read in analysis.uninitialized_reads() {
+            let statement = &cfg.statements()[read.statement];
+            let mut diagnostic = create_diagnostic(
+                operand_range(document, statement),
+                DiagnosticCode::Sem021,
+                format!(
+                    "'{}' is read before anything is stored to it on some path",
+                    read.cell
+                ));
+
+            // the blocks leading to the read, none when it is in the entry block
+            let earlier = read.path.split_last().map_or(&[][..], |(_, earlier)| earlier);
+            let related: Vec<DiagnosticRelatedInformation> = earlier
+                .iter()
+                .enumerate()
+                .map(|(step, &block)| DiagnosticRelatedInformation {
+                    location: Location {
+                        uri: uri.clone(),
+                        range: statement_range(document, first_statement(cfg, block)),
+                    },
+                    message: if step == 0 {
+                        format!("Path starts here, '{}' not yet written", read.cell)
+                    } else {
+                        format!("Path continues here, '{}' still not written", read.cell)
+                    },
+                })
+                .collect();
+            diagnostic.related_information = (!related.is_empty()).then_some(related);
+            diagnostics.push(diagnostic);
+        }
+
+        for write in analysis.unread_writes() {
+            let statement = &cfg.statements()[write.statement];
+            diagnostics.push(create_diagnostic(
+                operand_range(document, statement),
+                DiagnosticCode::Sem022,
+                format!("'{}' is written but never read", write.cell)));
+        }
+
+        diagnostics
+    }
+
+    fn validate_stack_balance(&self, document: &ParsedDocument) -> Vec<Diagnostic> {
+        let cfg = document.cfg();
+        let analysis = document.stack();
+
+        analysis
+            .issues()
+            .iter()
+            .map(|issue| match issue {
+                StackIssue::PopFromEmpty { statement, always } => {
+                    let message = if *always {
+                        "PZS pops from an empty stack".to_string()
+                    } else {
+                        "PZS may pop from an empty stack on some paths".to_string()
+                    };
+                    create_diagnostic(
+                        statement_range(document, &cfg.statements()[*statement]),
+                        DiagnosticCode::Sem018,
+                        message)
+                }
+                StackIssue::MergeMismatch { block, depths } => {
+                    let depths: Vec<String> = depths.iter().map(|depth| depth.to_string()).collect();
+                    create_diagnostic(
+                        statement_range(document, first_statement(cfg, *block)),
+                        DiagnosticCode::Sem019,
+                        format!(
+                            "Paths reach this point with different stack depths ({})",
+                            depths.join(", ")
+                        ))
+                }
+                StackIssue::ItemsAtStop { statement, depth } => create_diagnostic(
+                    statement_range(document, &cfg.statements()[*statement]),
+                    DiagnosticCode::Sem020,
+                    format!("Program stops with {} item(s) left on the stack", depth)),
+            })
+            .collect()
+    }
+
+    fn validate_reachability(
+        &self,
+        document: &ParsedDocument,
+        interrupts: &InterruptAnalysis,
+    ) -> Vec<Diagnostic> {
+        let cfg = document.cfg();
+        let reachable = reachable_blocks(cfg, interrupts);
+        let mut diagnostics = Vec::new();
+
+        // consecutive unreachable blocks are reported as one range
+        let mut runs: Vec<(BlockId, BlockId)> = Vec::new();
+        for block in cfg.blocks().iter().filter(|block| !reachable[block.id]) {
+            match runs.last_mut() {
+                Some((_, last)) if *last + 1 == block.id && cfg.adjacent(*last, block.id) => {
+                    *last = block.id
+                }
+                _ => runs.push((block.id, block.id)),
+            }
+        }
+
+        for (first, last) in runs {
+            let start = first_statement(cfg, first);
+            let end = last_statement(cfg, last);
+            diagnostics.push(create_diagnostic(
+                Range {
+                    start: statement_start(start),
+                    end: statement_end(document, end),
+                },
+                DiagnosticCode::Sem016,
+                "Unreachable code".to_string()));
+        }
+
+        diagnostics
+    }
+
+    fn validate_program_end(
+        &self,
+        document: &ParsedDocument,
+        interrupts: &InterruptAnalysis,
+    ) -> Vec<Diagnostic> {
+        let cfg = document.cfg();
+        let reachable = reachable_blocks(cfg, interrupts);
+
+        // handlers end in PWR, not STP, and are checked on their own
+        cfg.blocks()
+            .iter()
+            .filter(|block| reachable[block.id] && !interrupts.in_handler(block.id))
+            .filter(|block| block.leaves_code() == Some(Terminator::FallsOffEnd))
+            .map(|block| {
+                let last = last_statement(cfg, block.id);
+                let message = if block.terminator == Terminator::Branch {
+                    format!(
+                        "Program runs past its last instruction without reaching STP when {} is not taken",
+                        last.name()
+                    )
+                } else {
+                    "Program runs past its last instruction without reaching STP".to_string()
+                };
+                create_diagnostic(
+                    statement_range(document, last),
+                    DiagnosticCode::Sem017,
+                    message)
+            })
+            .collect()
+    }
+}
+
+/// blocks reachable from the entry or an interrupt handler; a jump to a
+/// numeric address or an undefined label may land on any labelled block
+fn reachable_blocks(cfg: &ControlFlowGraph, interrupts: &InterruptAnalysis) -> Vec<bool> {
+    let handlers = interrupts.entries().iter().map(|(_, block)| *block);
+    if cfg.unresolved_targets().is_empty() {
+        return cfg.reachable_from(cfg.entry().into_iter().chain(handlers));
+    }
+
+    let labelled = cfg
+        .blocks()
+        .iter()
+        .filter(|block| !block.labels.is_empty())
+        .map(|block| block.id);
+    cfg.reachable_from(cfg.entry().into_iter().chain(handlers).chain(labelled))
+}
+
+fn check_severity(level: CheckLevel) -> Option<DiagnosticSeverity> {
+    match level {
+        CheckLevel::Error => Some(DiagnosticSeverity::ERROR),
+        CheckLevel::Warning => Some(DiagnosticSeverity::WARNING),
+        CheckLevel::Off => None,
+    }
+}
+
+/// value an `RST` cell is declared with
+fn initial_value(cfg: &ControlFlowGraph, cell: &Cell) -> Option<i128> {
+    let Cell::Label(label) = cell else {
+        return None;
+    };
+    cfg.data_cells()
+        .iter()
+        .find(|data| data.directive == "RST" && data.labels.contains(label))
+        .and_then(|data| data.value.as_deref())
+        .and_then(parse_number_literal)
+}
+
+fn first_statement(cfg: &ControlFlowGraph, block: BlockId) -> &Statement {
+    cfg.block_statements(block).next().unwrap()
+}
+
+fn last_statement(cfg: &ControlFlowGraph, block: BlockId) -> &Statement {
+    cfg.block_statements(block).last().unwrap()
+}
+
+fn statement_start(statement: &Statement) -> Position {
+    Position {
+        line: statement.line().saturating_sub(1) as u32,
+        character: statement.column().saturating_sub(1) as u32,
+    }
+}
+
+fn statement_range(document: &ParsedDocument, statement: &Statement) -> Range {
+    Range {
+        start: statement_start(statement),
+        end: statement_end(document, statement),
+    }
+}
+
+/// operand of the statement, or the whole statement when it has none
+fn operand_range(document: &ParsedDocument, statement: &Statement) -> Range {
+    match document.operand_span(statement.line(), statement.column()) {
+        Some((line, start, end)) => word_range(line as u32, start, end),
+        None => statement_range(document, statement),
+    }
+}
+
+/// end of the code on the statement's line, comments excluded
+fn statement_end(document: &ParsedDocument, statement: &Statement) -> Position {
+    let line = statement.line().saturating_sub(1);
+    let code = document.line(line).map(strip_comment).unwrap_or("");
+    word_range(line as u32, 0, char_len(code.trim_end())).end
+}
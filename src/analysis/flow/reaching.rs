@@ -0,0 +1,134 @@
+use super::{cell_of, operand_cell, BlockId, Cell, ControlFlowGraph, Statement};
+use parseid::AddressingMode;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+/// where the value in a cell may have come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Definition {
+    /// value the cell holds when the program is loaded
+    Initial,
+    /// `ŁAD` at index into `ControlFlowGraph::statements`
+    Store(usize),
+    /// write through a pointer or inside a macro
+    Unknown,
+}
+
+type Definitions = BTreeMap<Cell, BTreeSet<Definition>>;
+
+/// stores that may reach each statement, per cell
+#[derive(Debug, Clone, Default)]
+pub struct ReachingStores {
+    before: Vec<Option<Definitions>>,
+}
+
+impl ReachingStores {
+    pub fn analyze(cfg: &ControlFlowGraph) -> Self {
+        let entry = definitions_on_entry(cfg);
+
+        let mut before = vec![None; cfg.statements().len()];
+        for block in cfg.blocks() {
+            let Some(mut definitions) = entry[block.id].clone() else {
+                continue;
+            };
+            for &statement in &block.statements {
+                before[statement] = Some(definitions.clone());
+                apply(&cfg.statements()[statement], statement, &mut definitions);
+            }
+        }
+
+        Self { before }
+    }
+
+    /// definitions of cell reaching the statement, empty when it is unreachable
+    pub fn reaching(&self, statement: usize, cell: &Cell) -> BTreeSet<Definition> {
+        self.before
+            .get(statement)
+            .and_then(Option::as_ref)
+            .and_then(|definitions| definitions.get(cell))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// every cell the program names starts with its initial value
+fn definitions_on_entry(cfg: &ControlFlowGraph) -> Vec<Option<Definitions>> {
+    let mut entry: Vec<Option<Definitions>> = vec![None; cfg.blocks().len()];
+    let mut queue: VecDeque<BlockId> = VecDeque::new();
+
+    if let Some(start) = cfg.entry() {
+        let mut initial = Definitions::new();
+        for statement in cfg.statements() {
+            let cells: Vec<Cell> = match statement {
+                Statement::MacroCall(call) => {
+                    call.arguments.iter().filter_map(|argument| cell_of(argument)).collect()
+                }
+                Statement::Instruction(..) => operand_cell(statement).into_iter().collect(),
+            };
+            for cell in cells {
+                initial.insert(cell, BTreeSet::from([Definition::Initial]));
+            }
+        }
+        entry[start] = Some(initial);
+        queue.push_back(start);
+    }
+
+    while let Some(id) = queue.pop_front() {
+        let Some(mut definitions) = entry[id].clone() else {
+            continue;
+        };
+        for &statement in &cfg.block(id).statements {
+            apply(&cfg.statements()[statement], statement, &mut definitions);
+        }
+
+        for edge in &cfg.block(id).successors {
+            let changed = match entry[edge.target].as_mut() {
+                Some(target) => {
+                    let mut changed = false;
+                    for (cell, incoming) in &definitions {
+                        let current = target.entry(cell.clone()).or_default();
+                        for definition in incoming {
+                            changed |= current.insert(*definition);
+                        }
+                    }
+                    changed
+                }
+                None => {
+                    entry[edge.target] = Some(definitions.clone());
+                    true
+                }
+            };
+
+            if changed {
+                queue.push_back(edge.target);
+            }
+        }
+    }
+
+    entry
+}
+
+fn apply(statement: &Statement, index: usize, definitions: &mut Definitions) {
+    if let Statement::MacroCall(call) = statement {
+        for cell in call.arguments.iter().filter_map(|argument| cell_of(argument)) {
+            definitions.insert(cell, BTreeSet::from([Definition::Unknown]));
+        }
+        return;
+    }
+    if !statement.is_opcode("ŁAD") {
+        return;
+    }
+
+    match statement.operand().map(|operand| &operand.addressing_mode) {
+        Some(AddressingMode::Direct) => {
+            if let Some(cell) = operand_cell(statement) {
+                definitions.insert(cell, BTreeSet::from([Definition::Store(index)]));
+            }
+        }
+        Some(_) => {
+            for current in definitions.values_mut() {
+                current.insert(Definition::Unknown);
+            }
+        }
+        None => {}
+    }
+}
@@ -0,0 +1,220 @@
+use super::{BlockId, ControlFlowGraph, Statement};
+use std::collections::VecDeque;
+use std::fmt;
+
+/// updates to a block's entry depth before its upper bound is given up,
+/// so loops that keep pushing still terminate
+const WIDEN_AFTER: usize = 4;
+
+/// possible stack depths at a program point
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StackDepth {
+    pub min: usize,
+    /// `None` when the stack can grow without bound
+    pub max: Option<usize>,
+}
+
+impl StackDepth {
+    pub fn exact(depth: usize) -> Self {
+        Self {
+            min: depth,
+            max: Some(depth),
+        }
+    }
+
+    pub fn is_exact(&self) -> bool {
+        self.max == Some(self.min)
+    }
+
+    /// stack may be empty here
+    pub fn may_be_empty(&self) -> bool {
+        self.min == 0
+    }
+
+    /// stack may hold items here
+    pub fn may_be_nonempty(&self) -> bool {
+        self.max != Some(0)
+    }
+
+    fn join(self, other: Self) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: match (self.max, other.max) {
+                (Some(a), Some(b)) => Some(a.max(b)),
+                _ => None,
+            },
+        }
+    }
+
+    fn push(self) -> Self {
+        Self {
+            min: self.min + 1,
+            max: self.max.map(|max| max + 1),
+        }
+    }
+
+    fn pop(self) -> Self {
+        Self {
+            min: self.min.saturating_sub(1),
+            max: self.max.map(|max| max.saturating_sub(1)),
+        }
+    }
+}
+
+impl fmt::Display for StackDepth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.max {
+            Some(max) if max == self.min => write!(f, "{}", max),
+            Some(max) => write!(f, "{}..{}", self.min, max),
+            None => write!(f, "{}+", self.min),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StackIssue {
+    /// `PZS` on a stack that may be empty; `always` when it is empty on every path
+    PopFromEmpty { statement: usize, always: bool },
+    /// predecessors leave the stack at different depths
+    MergeMismatch { block: BlockId, depths: Vec<StackDepth> },
+    /// `STP` reached with items possibly left on the stack
+    ItemsAtStop { statement: usize, depth: StackDepth },
+}
+
+/// stack depth before every statement, over all paths from the entry
+#[derive(Debug, Clone)]
+pub struct StackAnalysis {
+    block_entry: Vec<Option<StackDepth>>,
+    before: Vec<Option<StackDepth>>,
+    issues: Vec<StackIssue>,
+}
+
+impl StackAnalysis {
+    pub fn analyze(cfg: &ControlFlowGraph) -> Self {
+        let block_entry = entry_depths(cfg);
+        let block_exit: Vec<Option<StackDepth>> = cfg
+            .blocks()
+            .iter()
+            .map(|block| block_entry[block.id].map(|depth| transfer_block(cfg, block.id, depth)))
+            .collect();
+
+        let mut before = vec![None; cfg.statements().len()];
+        let mut issues = Vec::new();
+
+        for block in cfg.blocks() {
+            let Some(mut depth) = block_entry[block.id] else {
+                continue;
+            };
+
+            let mut incoming = Vec::new();
+            if cfg.entry() == Some(block.id) {
+                incoming.push(StackDepth::exact(0));
+            }
+            for depth in block.predecessors.iter().filter_map(|&pred| block_exit[pred]) {
+                if !incoming.contains(&depth) {
+                    incoming.push(depth);
+                }
+            }
+            if incoming.len() > 1 {
+                issues.push(StackIssue::MergeMismatch {
+                    block: block.id,
+                    depths: incoming,
+                });
+            }
+
+            for &statement in &block.statements {
+                before[statement] = Some(depth);
+                let current = &cfg.statements()[statement];
+                if current.is_opcode("PZS") && depth.may_be_empty() {
+                    issues.push(StackIssue::PopFromEmpty {
+                        statement,
+                        always: depth.max == Some(0),
+                    });
+                }
+                if current.is_opcode("STP") && depth.may_be_nonempty() {
+                    issues.push(StackIssue::ItemsAtStop { statement, depth });
+                }
+                depth = transfer(current, depth);
+            }
+        }
+
+        Self {
+            block_entry,
+            before,
+            issues,
+        }
+    }
+
+    /// depth on entry to block, `None` when it is unreachable
+    pub fn block_entry(&self, block: BlockId) -> Option<StackDepth> {
+        self.block_entry.get(block).copied().flatten()
+    }
+
+    /// depth before the statement at index into `ControlFlowGraph::statements`
+    pub fn depth_before(&self, statement: usize) -> Option<StackDepth> {
+        self.before.get(statement).copied().flatten()
+    }
+
+    pub fn depth_after(&self, cfg: &ControlFlowGraph, statement: usize) -> Option<StackDepth> {
+        self.depth_before(statement)
+            .map(|depth| transfer(&cfg.statements()[statement], depth))
+    }
+
+    pub fn issues(&self) -> &[StackIssue] {
+        &self.issues
+    }
+}
+
+/// worklist over blocks, starting with an empty stack at the entry
+fn entry_depths(cfg: &ControlFlowGraph) -> Vec<Option<StackDepth>> {
+    let mut entry: Vec<Option<StackDepth>> = vec![None; cfg.blocks().len()];
+    let mut updates = vec![0; cfg.blocks().len()];
+    let mut queue = VecDeque::new();
+
+    if let Some(start) = cfg.entry() {
+        entry[start] = Some(StackDepth::exact(0));
+        queue.push_back(start);
+    }
+
+    while let Some(id) = queue.pop_front() {
+        let Some(depth) = entry[id] else {
+            continue;
+        };
+        let exit = transfer_block(cfg, id, depth);
+
+        for edge in &cfg.block(id).successors {
+            let target = edge.target;
+            let mut joined = match entry[target] {
+                Some(current) => current.join(exit),
+                None => exit,
+            };
+            if entry[target] == Some(joined) {
+                continue;
+            }
+
+            updates[target] += 1;
+            if updates[target] > WIDEN_AFTER {
+                joined.max = None;
+            }
+            entry[target] = Some(joined);
+            queue.push_back(target);
+        }
+    }
+
+    entry
+}
+
+fn transfer_block(cfg: &ControlFlowGraph, block: BlockId, depth: StackDepth) -> StackDepth {
+    cfg.block_statements(block)
+        .fold(depth, |depth, statement| transfer(statement, depth))
+}
+
+fn transfer(statement: &Statement, depth: StackDepth) -> StackDepth {
+    if statement.is_opcode("SDP") {
+        depth.push()
+    } else if statement.is_opcode("PZS") {
+        depth.pop()
+    } else {
+        depth
+    }
+}
@@ -0,0 +1,451 @@
+use super::{operand_cell, BlockId, Cell, ControlFlowGraph, EdgeKind, Statement};
+use crate::analysis::utils::{parse_number_literal, InstructionDatabase, MemoryModel};
+use parseid::AddressingMode;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+
+/// updates to a block's entry state before changing values are given up
+const WIDEN_AFTER: usize = 4;
+
+/// range of values a word may hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interval {
+    pub min: i64,
+    pub max: i64,
+}
+
+impl Interval {
+    pub fn exact(value: i64) -> Self {
+        Self {
+            min: value,
+            max: value,
+        }
+    }
+
+    pub fn as_exact(&self) -> Option<i64> {
+        (self.min == self.max).then_some(self.min)
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        self.min <= value && value <= self.max
+    }
+
+    fn hull(self, other: Self) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.as_exact() {
+            Some(value) => write!(f, "{}", value),
+            None => write!(f, "{}..{}", self.min, self.max),
+        }
+    }
+}
+
+/// what an instruction does to AK, read from its `operation` in the
+/// instruction database
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    /// `(operand) → AK`
+    Load,
+    /// `(AK) → (address)`
+    Store,
+    /// `(AK) <op> (operand) → AK`
+    Arithmetic(char),
+    /// AK receives a value that cannot be known, like input or a stack pop
+    Clobber,
+    /// AK and memory are left alone
+    None,
+}
+
+impl Effect {
+    pub fn of_operation(operation: &str) -> Self {
+        let Some((source, target)) = operation.split_once('→') else {
+            return Self::None;
+        };
+        let (source, target) = (source.trim(), target.trim());
+
+        if target != "AK" {
+            return if source == "(AK)" && target.starts_with('(') {
+                Self::Store
+            } else {
+                Self::None
+            };
+        }
+        if source == "(operand)" {
+            return Self::Load;
+        }
+
+        let operator = source
+            .strip_prefix("(AK)")
+            .and_then(|rest| rest.strip_suffix("(operand)"))
+            .map(str::trim)
+            .and_then(|operator| {
+                let mut chars = operator.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(operator), None) => Some(operator),
+                    _ => None,
+                }
+            });
+        match operator {
+            Some(operator) => Self::Arithmetic(operator),
+            None => Self::Clobber,
+        }
+    }
+}
+
+/// known values of AK and of memory cells; anything missing is unknown
+#[derive(Debug, Clone, PartialEq)]
+pub struct AbstractState {
+    pub ak: Option<Interval>,
+    pub cells: BTreeMap<Cell, Interval>,
+}
+
+impl AbstractState {
+    fn join(&self, other: &Self) -> Self {
+        Self {
+            ak: match (self.ak, other.ak) {
+                (Some(a), Some(b)) => Some(a.hull(b)),
+                _ => None,
+            },
+            cells: self
+                .cells
+                .iter()
+                .filter_map(|(cell, value)| {
+                    other.cells.get(cell).map(|other| (cell.clone(), value.hull(*other)))
+                })
+                .collect(),
+        }
+    }
+
+    /// drops every value that differs from previous
+    fn widen(&self, previous: &Self) -> Self {
+        Self {
+            ak: self.ak.filter(|_| self.ak == previous.ak),
+            cells: self
+                .cells
+                .iter()
+                .filter(|(cell, value)| previous.cells.get(*cell) == Some(value))
+                .map(|(cell, value)| (cell.clone(), *value))
+                .collect(),
+        }
+    }
+}
+
+/// outcome of a conditional branch over every path reaching it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BranchOutcome {
+    AlwaysTaken,
+    NeverTaken,
+}
+
+/// abstract interpretation of AK and memory over the control-flow graph
+#[derive(Debug, Clone)]
+pub struct ValueAnalysis {
+    /// state before each statement, `None` when it is unreachable
+    before: Vec<Option<AbstractState>>,
+    /// state after each statement
+    after: Vec<Option<AbstractState>>,
+}
+
+impl ValueAnalysis {
+    pub fn analyze(
+        cfg: &ControlFlowGraph,
+        instruction_db: &InstructionDatabase,
+        memory_model: &MemoryModel,
+    ) -> Self {
+        let interpreter = Interpreter {
+            cfg,
+            instruction_db,
+            memory_model,
+        };
+        let entry = interpreter.entry_states();
+
+        let mut before = vec![None; cfg.statements().len()];
+        let mut after = vec![None; cfg.statements().len()];
+        for block in cfg.blocks() {
+            let Some(mut state) = entry[block.id].clone() else {
+                continue;
+            };
+            for &statement in &block.statements {
+                before[statement] = Some(state.clone());
+                state = interpreter.transfer(&cfg.statements()[statement], state);
+                after[statement] = Some(state.clone());
+            }
+        }
+
+        Self { before, after }
+    }
+
+    pub fn state_before(&self, statement: usize) -> Option<&AbstractState> {
+        self.before.get(statement).and_then(Option::as_ref)
+    }
+
+    pub fn state_after(&self, statement: usize) -> Option<&AbstractState> {
+        self.after.get(statement).and_then(Option::as_ref)
+    }
+
+    /// AK before the statement, when it is known
+    pub fn ak_before(&self, statement: usize) -> Option<Interval> {
+        self.state_before(statement).and_then(|state| state.ak)
+    }
+
+    pub fn ak_after(&self, statement: usize) -> Option<Interval> {
+        self.state_after(statement).and_then(|state| state.ak)
+    }
+
+    /// outcome of `SOZ` / `SOM` when AK decides it on every path
+    pub fn branch_outcome(&self, cfg: &ControlFlowGraph, statement: usize) -> Option<BranchOutcome> {
+        let ak = self.ak_before(statement)?;
+        let current = &cfg.statements()[statement];
+
+        let (always, never) = if current.is_opcode("SOZ") {
+            (ak.as_exact() == Some(0), !ak.contains(0))
+        } else if current.is_opcode("SOM") {
+            (ak.max < 0, ak.min >= 0)
+        } else {
+            return None;
+        };
+
+        if always {
+            Some(BranchOutcome::AlwaysTaken)
+        } else if never {
+            Some(BranchOutcome::NeverTaken)
+        } else {
+            None
+        }
+    }
+
+    /// operand value of the statement in the state before it
+    pub fn operand_value(&self, statement: &Statement, index: usize) -> Option<Interval> {
+        operand_value(statement, self.state_before(index)?)
+    }
+}
+
+struct Interpreter<'a> {
+    cfg: &'a ControlFlowGraph,
+    instruction_db: &'a InstructionDatabase,
+    memory_model: &'a MemoryModel,
+}
+
+impl Interpreter<'_> {
+    /// worklist over blocks; execution starts with AK unknown and data cells
+    /// holding their `RST` values
+    fn entry_states(&self) -> Vec<Option<AbstractState>> {
+        let cfg = self.cfg;
+        let mut entry: Vec<Option<AbstractState>> = vec![None; cfg.blocks().len()];
+        let mut updates = vec![0; cfg.blocks().len()];
+        let mut queue = VecDeque::new();
+
+        if let Some(start) = cfg.entry() {
+            entry[start] = Some(AbstractState {
+                ak: None,
+                cells: initial_cells(cfg),
+            });
+            queue.push_back(start);
+        }
+
+        while let Some(id) = queue.pop_front() {
+            let Some(mut state) = entry[id].clone() else {
+                continue;
+            };
+            for statement in cfg.block_statements(id) {
+                state = self.transfer(statement, state);
+            }
+
+            for edge in &cfg.block(id).successors {
+                let Some(incoming) = self.refine(id, edge.kind, &state) else {
+                    continue;
+                };
+                let joined = match &entry[edge.target] {
+                    Some(current) => {
+                        let joined = current.join(&incoming);
+                        if updates[edge.target] >= WIDEN_AFTER {
+                            joined.widen(current)
+                        } else {
+                            joined
+                        }
+                    }
+                    None => incoming,
+                };
+                if entry[edge.target].as_ref() == Some(&joined) {
+                    continue;
+                }
+
+                updates[edge.target] += 1;
+                entry[edge.target] = Some(joined);
+                queue.push_back(edge.target);
+            }
+        }
+
+        entry
+    }
+
+    /// narrows AK along a branch edge; `None` when the edge cannot be taken
+    fn refine(&self, block: BlockId, kind: EdgeKind, state: &AbstractState) -> Option<AbstractState> {
+        let Some(&last) = self.cfg.block(block).statements.last() else {
+            return Some(state.clone());
+        };
+        let branch = &self.cfg.statements()[last];
+        let taken = kind == EdgeKind::BranchTaken;
+        let Some(ak) = state.ak else {
+            // a taken SOZ tells AK even when nothing else does
+            let ak = (taken && branch.is_opcode("SOZ")).then_some(Interval::exact(0));
+            return Some(AbstractState {
+                ak,
+                cells: state.cells.clone(),
+            });
+        };
+
+        let refined = if branch.is_opcode("SOZ") {
+            if taken {
+                ak.contains(0).then_some(Interval::exact(0))
+            } else if ak.as_exact() == Some(0) {
+                None
+            } else if ak.min == 0 {
+                Some(Interval { min: 1, ..ak })
+            } else if ak.max == 0 {
+                Some(Interval { max: -1, ..ak })
+            } else {
+                Some(ak)
+            }
+        } else if branch.is_opcode("SOM") {
+            if taken {
+                (ak.min < 0).then_some(Interval {
+                    max: ak.max.min(-1),
+                    ..ak
+                })
+            } else {
+                (ak.max >= 0).then_some(Interval {
+                    min: ak.min.max(0),
+                    ..ak
+                })
+            }
+        } else {
+            Some(ak)
+        };
+
+        refined.map(|ak| AbstractState {
+            ak: Some(ak),
+            cells: state.cells.clone(),
+        })
+    }
+
+    fn transfer(&self, statement: &Statement, mut state: AbstractState) -> AbstractState {
+        let call = match statement {
+            Statement::MacroCall(call) => call,
+            Statement::Instruction(..) => {
+                let effect = statement
+                    .mnemonic()
+                    .and_then(|mnemonic| self.instruction_db.get_instruction(mnemonic))
+                    .map(|info| Effect::of_operation(&info.operation))
+                    .unwrap_or(Effect::Clobber);
+                return self.apply(effect, statement, state);
+            }
+        };
+
+        // a macro body may do anything with AK and the cells it is given
+        state.ak = None;
+        for argument in &call.arguments {
+            state.cells.retain(|cell, _| cell.to_string() != *argument);
+        }
+        state
+    }
+
+    fn apply(&self, effect: Effect, statement: &Statement, mut state: AbstractState) -> AbstractState {
+        match effect {
+            Effect::Load => state.ak = operand_value(statement, &state),
+            Effect::Store => match statement.operand().map(|operand| &operand.addressing_mode) {
+                Some(AddressingMode::Direct) => {
+                    if let Some(cell) = operand_cell(statement) {
+                        match state.ak {
+                            Some(ak) => state.cells.insert(cell, ak),
+                            None => state.cells.remove(&cell),
+                        };
+                    }
+                }
+                // through a pointer any cell may change
+                _ => state.cells.clear(),
+            },
+            Effect::Arithmetic(operator) => {
+                state.ak = match (state.ak, operand_value(statement, &state)) {
+                    (Some(ak), Some(operand)) => self.arithmetic(operator, ak, operand),
+                    _ => None,
+                };
+            }
+            Effect::Clobber => state.ak = None,
+            Effect::None => {}
+        }
+        state
+    }
+
+    /// result range, or unknown when it may leave the word or divide by zero
+    fn arithmetic(&self, operator: char, ak: Interval, operand: Interval) -> Option<Interval> {
+        let candidates: Vec<Option<i64>> = match operator {
+            '+' => vec![ak.min.checked_add(operand.min), ak.max.checked_add(operand.max)],
+            '-' => vec![ak.min.checked_sub(operand.max), ak.max.checked_sub(operand.min)],
+            '*' => corners(ak, operand, i64::checked_mul),
+            '/' if !operand.contains(0) => corners(ak, operand, i64::checked_div),
+            '%' => match (ak.as_exact(), operand.as_exact()) {
+                (Some(a), Some(b)) if b != 0 => vec![a.checked_rem(b)],
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        let values: Option<Vec<i64>> = candidates.into_iter().collect();
+        let values = values?;
+        let result = Interval {
+            min: *values.iter().min()?,
+            max: *values.iter().max()?,
+        };
+
+        let fits = self.memory_model.fits_word(result.min as i128)
+            && self.memory_model.fits_word(result.max as i128);
+        fits.then_some(result)
+    }
+}
+
+fn corners(a: Interval, b: Interval, op: fn(i64, i64) -> Option<i64>) -> Vec<Option<i64>> {
+    vec![
+        op(a.min, b.min),
+        op(a.min, b.max),
+        op(a.max, b.min),
+        op(a.max, b.max),
+    ]
+}
+
+/// immediate value, or the known content of the cell named by the operand
+fn operand_value(statement: &Statement, state: &AbstractState) -> Option<Interval> {
+    let operand = statement.operand()?;
+    match operand.addressing_mode {
+        AddressingMode::Immediate => parse_number_literal(&operand.value)
+            .and_then(|value| i64::try_from(value).ok())
+            .map(Interval::exact),
+        AddressingMode::Direct => state.cells.get(&operand_cell(statement)?).copied(),
+        _ => None,
+    }
+}
+
+/// `RST` cells start with their declared value
+fn initial_cells(cfg: &ControlFlowGraph) -> BTreeMap<Cell, Interval> {
+    let mut cells = BTreeMap::new();
+    for data in cfg.data_cells().iter().filter(|cell| cell.directive == "RST") {
+        let value = data
+            .value
+            .as_deref()
+            .and_then(parse_number_literal)
+            .and_then(|value| i64::try_from(value).ok());
+        if let Some(value) = value {
+            for label in &data.labels {
+                cells.insert(Cell::Label(label.clone()), Interval::exact(value));
+            }
+        }
+    }
+    cells
+}
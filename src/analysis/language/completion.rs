@@ -1,5 +1,7 @@
+use crate::analysis::core::DocumentCache;
 use crate::analysis::utils::{
-    get_line_at_position, is_valid_position, InstructionCategory, InstructionDatabase,
+    get_line_at_position, is_valid_position, text_before_column, InstructionCategory,
+    InstructionDatabase, IsaProfile,
 };
 use tower_lsp::lsp_types::*;
 
@@ -14,15 +16,41 @@ pub enum CompletionContext {
 #[derive(Debug)]
 pub struct CompletionProvider {
     instruction_db: InstructionDatabase,
+    documents: DocumentCache,
+    isa: IsaProfile,
+}
+
+impl Default for CompletionProvider {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl CompletionProvider {
     pub fn new() -> Self {
+        Self::with_cache(DocumentCache::new())
+    }
+
+    pub fn with_cache(documents: DocumentCache) -> Self {
         Self {
             instruction_db: InstructionDatabase::new(),
+            documents,
+            isa: IsaProfile::default(),
         }
     }
 
+    pub fn with_instruction_db(mut self, instruction_db: InstructionDatabase) -> Self {
+        self.documents.set_instruction_db(instruction_db.clone());
+        self.instruction_db = instruction_db;
+        self
+    }
+
+    /// instructions outside the profile are not offered
+    pub fn with_isa_profile(mut self, isa: IsaProfile) -> Self {
+        self.isa = isa;
+        self
+    }
+
     pub fn get_completions(&self, content: &str, position: Position) -> Vec<CompletionItem> {
         let mut completions = Vec::new();
 
@@ -47,11 +75,7 @@ impl CompletionProvider {
         let cursor_pos = position.character as usize;
 
         // get text before cursor for context analysis
-        let text_before_cursor = if cursor_pos <= current_line.len() {
-            &current_line[..cursor_pos]
-        } else {
-            current_line
-        };
+        let text_before_cursor = text_before_column(current_line, cursor_pos);
 
         let completion_context = self.analyze_completion_context(text_before_cursor);
 
@@ -84,7 +108,7 @@ impl CompletionProvider {
 
         // analyze words
         let words: Vec<&str> = trimmed.split_whitespace().collect();
-        if words.len() >= 1 {
+        if !words.is_empty() {
             let first_word = words[0];
             if self.instruction_db.is_valid_instruction(first_word) {
                 // if expects a label operand
@@ -141,35 +165,27 @@ impl CompletionProvider {
             let category_instructions = self.instruction_db.get_instructions_by_category(category.clone());
 
             for instruction in category_instructions {
-                let (snippet, kind_text) = match instruction.category {
-                    InstructionCategory::Arithmetic => {
-                        (format!("{} ${{1:operand}}", instruction.name), "Arithmetic")
-                    }
-                    InstructionCategory::Memory => {
-                        (format!("{} ${{1:operand}}", instruction.name), "Memory")
-                    }
-                    InstructionCategory::ControlFlow => {
-                        if instruction.name == "STP" {
-                            (instruction.name.to_string(), "Control Flow")
-                        } else {
-                            (format!("{} ${{1:label}}", instruction.name), "Control Flow")
-                        }
-                    }
-                    InstructionCategory::Stack => (instruction.name.to_string(), "Stack"),
-                    InstructionCategory::Interrupt => {
-                        if instruction.name == "MSK" {
-                            (format!("{} ${{1:mask}}", instruction.name), "Interrupt")
-                        } else {
-                            (instruction.name.to_string(), "Interrupt")
-                        }
-                    }
-                    InstructionCategory::InputOutput => (instruction.name.to_string(), "I/O"),
+                if !self.isa.allows(instruction) {
+                    continue;
+                }
+
+                let snippet = match instruction.parameter() {
+                    Some(parameter) => format!("{} ${{1:{}}}", instruction.name, parameter.name),
+                    None => instruction.name.clone(),
+                };
+                let kind_text = match instruction.category {
+                    InstructionCategory::Arithmetic => "Arithmetic",
+                    InstructionCategory::Memory => "Memory",
+                    InstructionCategory::ControlFlow => "Control Flow",
+                    InstructionCategory::Stack => "Stack",
+                    InstructionCategory::Interrupt => "Interrupt",
+                    InstructionCategory::InputOutput => "I/O",
                 };
 
                 let label = if instruction.is_extended {
                     format!("{} (Extended)", instruction.name)
                 } else {
-                    instruction.name.to_string()
+                    instruction.name.clone()
                 };
 
                 let extended_priority = if instruction.is_extended { "2" } else { "1" };
@@ -178,7 +194,7 @@ impl CompletionProvider {
                     label,
                     kind: Some(CompletionItemKind::KEYWORD),
                     detail: Some(kind_text.to_string()),
-                    documentation: Some(Documentation::String(instruction.description.to_string())),
+                    documentation: Some(Documentation::String(instruction.description.clone())),
                     insert_text: Some(snippet),
                     insert_text_format: Some(InsertTextFormat::SNIPPET),
                     sort_text: Some(format!(
@@ -217,34 +233,26 @@ impl CompletionProvider {
     }
 
     fn get_label_completions(&self, content: &str) -> Vec<CompletionItem> {
-        let mut completions = Vec::new();
-        let lines: Vec<&str> = content.lines().collect();
+        let document = self.documents.get(content);
 
         // all label definitions in document
-        for (line_num, line) in lines.iter().enumerate() {
-            let trimmed = line.trim();
-            if let Some(colon_pos) = trimmed.find(':') {
-                let label_name = &trimmed[..colon_pos];
-
-                // validation if the name is a valid label
-                if crate::analysis::utils::is_valid_symbol_name(label_name) {
-                    completions.push(CompletionItem {
-                        label: label_name.to_string(),
-                        kind: Some(CompletionItemKind::FUNCTION),
-                        detail: Some("Label".to_string()),
-                        documentation: Some(Documentation::String(format!(
-                            "Label defined at line {}",
-                            line_num + 1
-                        ))),
-                        insert_text: Some(label_name.to_string()),
-                        insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
-                        sort_text: Some(format!("1_{}", label_name)), // high priority
-                        ..Default::default()
-                    });
-                }
-            }
-        }
-
-        completions
+        document
+            .symbols()
+            .labels()
+            .iter()
+            .map(|label| CompletionItem {
+                label: label.name.clone(),
+                kind: Some(CompletionItemKind::FUNCTION),
+                detail: Some("Label".to_string()),
+                documentation: Some(Documentation::String(format!(
+                    "Label defined at line {}",
+                    label.line + 1
+                ))),
+                insert_text: Some(label.name.clone()),
+                insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
+                sort_text: Some(format!("1_{}", label.name)), // high priority
+                ..Default::default()
+            })
+            .collect()
     }
 }
@@ -1,21 +1,57 @@
+use crate::analysis::core::DocumentCache;
+use crate::analysis::flow::InterruptConfig;
 use crate::analysis::utils::{
-    get_label_info, get_line_at_position, get_word_at_position, is_valid_position, word_range,
-    InstructionDatabase,
+    get_line_at_position, get_word_at_position, is_valid_position, parse_number_literal,
+    word_range, InstructionDatabase, MemoryModel,
 };
+use parseid::AddressingMode;
 use tower_lsp::lsp_types::*;
 
 #[derive(Debug)]
 pub struct HoverProvider {
     instruction_db: InstructionDatabase,
+    documents: DocumentCache,
+    memory_model: MemoryModel,
+    interrupts: InterruptConfig,
+}
+
+impl Default for HoverProvider {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl HoverProvider {
     pub fn new() -> Self {
+        Self::with_cache(DocumentCache::new())
+    }
+
+    pub fn with_cache(documents: DocumentCache) -> Self {
         Self {
             instruction_db: InstructionDatabase::new(),
+            documents,
+            memory_model: MemoryModel::default(),
+            interrupts: InterruptConfig::default(),
         }
     }
 
+    pub fn with_instruction_db(mut self, instruction_db: InstructionDatabase) -> Self {
+        self.documents.set_instruction_db(instruction_db.clone());
+        self.instruction_db = instruction_db;
+        self
+    }
+
+    pub fn with_memory_model(mut self, memory_model: MemoryModel) -> Self {
+        self.documents.set_memory_model(memory_model);
+        self.memory_model = memory_model;
+        self
+    }
+
+    pub fn with_interrupt_config(mut self, interrupts: InterruptConfig) -> Self {
+        self.interrupts = interrupts;
+        self
+    }
+
     pub fn get_hover_info(&self, content: &str, position: Position) -> Option<Hover> {
         if !is_valid_position(content, position) {
             return None;
@@ -31,13 +67,31 @@ impl HoverProvider {
             return None;
         }
 
+        // numbers take a directly preceding minus sign
+        let negative = start_pos > 0 && current_line.chars().nth(start_pos - 1) == Some('-');
+        let literal = if negative { format!("-{}", word) } else { word.clone() };
+        if let Some(value) = parse_number_literal(&literal) {
+            let start_pos = if negative { start_pos - 1 } else { start_pos };
+            return Some(Hover {
+                contents: HoverContents::Markup(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: self.get_literal_info(&literal, value)
+                        + &self.get_mask_info(content, position.line),
+                }),
+                range: Some(word_range(position.line, start_pos, end_pos)),
+            });
+        }
+
         // first check instruction info
-        let hover_content = if let Some(instruction_info) = self.get_instruction_info(&word) {
-            instruction_info
-        } else if let Some(label_info) = get_label_info(&word, content) {
-            label_info
-        } else {
-            return None;
+        let hover_content = match self.get_instruction_info(&word) {
+            Some(mut instruction_info) => {
+                instruction_info.push_str(&self.get_value_info(content, position.line, start_pos));
+                if word.eq_ignore_ascii_case("MSK") {
+                    instruction_info.push_str(&self.get_mask_info(content, position.line));
+                }
+                instruction_info
+            }
+            None => self.get_label_info(&word, content)?,
         };
 
         Some(Hover {
@@ -49,6 +103,119 @@ impl HoverProvider {
         })
     }
 
+    fn get_literal_info(&self, literal: &str, value: i128) -> String {
+        let word_bits = self.memory_model.word_bits as usize;
+        let pattern = self.memory_model.word_pattern(value);
+        let hex_digits = word_bits.div_ceil(4);
+
+        let mut info = format!(
+            "**Number:** `{}`\n\n**Decimal:** {}\n\n**Hexadecimal:** `0x{:0hex$X}`\n\n**Binary:** `0b{:0bin$b}`\n\n**Two's complement ({}-bit):** {}",
+            literal,
+            value,
+            pattern,
+            pattern,
+            word_bits,
+            self.memory_model.signed_value(pattern),
+            hex = hex_digits,
+            bin = word_bits,
+        );
+
+        if !self.memory_model.fits_word(value) {
+            info.push_str(&format!(
+                "\n\n**Warning:** does not fit in a {}-bit word, only the low {} bits are kept",
+                word_bits, word_bits
+            ));
+        }
+
+        info
+    }
+
+    /// known AK around the instruction whose opcode starts at (line, column)
+    fn get_value_info(&self, content: &str, line: u32, column: usize) -> String {
+        let document = self.documents.get(content);
+        let cfg = document.cfg();
+        let Some(index) = cfg.statements().iter().position(|statement| {
+            statement.line() == line as usize + 1 && statement.column() == column + 1
+        }) else {
+            return String::new();
+        };
+
+        let values = document.values();
+        let mut info = String::new();
+        if let Some(ak) = values.ak_before(index) {
+            info.push_str(&format!("\n\n**AK before:** {}", ak));
+        }
+        if let Some(ak) = values.ak_after(index) {
+            info.push_str(&format!("\n\n**AK after:** {}", ak));
+        }
+        info
+    }
+
+    /// bits of the immediate mask given to `MSK` on line
+    fn get_mask_info(&self, content: &str, line: u32) -> String {
+        let document = self.documents.get(content);
+        let Some(mask) = document
+            .cfg()
+            .statements()
+            .iter()
+            .filter(|statement| statement.line() == line as usize + 1 && statement.is_opcode("MSK"))
+            .filter_map(|statement| statement.operand())
+            .find(|operand| operand.addressing_mode == AddressingMode::Immediate)
+            .and_then(|operand| parse_number_literal(&operand.value))
+        else {
+            return String::new();
+        };
+
+        let lines = self.interrupts.lines;
+        if !self.interrupts.fits_mask(mask) {
+            return format!(
+                "\n\n**Interrupt mask:** does not fit the {} interrupt lines",
+                lines
+            );
+        }
+
+        let masked = self.interrupts.masked_lines(mask);
+        let enabled: Vec<u32> = (0..lines).filter(|line| !masked.contains(line)).collect();
+        format!(
+            "\n\n**Interrupt mask:** `0b{:0width$b}`, a set bit masks its line\n\n**Masked lines:** {}\n\n**Enabled lines:** {}",
+            mask,
+            list_lines(&masked),
+            list_lines(&enabled),
+            width = lines as usize,
+        )
+    }
+
+    fn get_label_info(&self, word: &str, content: &str) -> Option<String> {
+        let document = self.documents.get(content);
+        let label = document.symbols().find_label(word)?;
+        let definition = document.line(label.line)?.trim();
+
+        let mut info = format!(
+            "**Label:** `{}`\n\n**Defined at:** Line {}\n\n**Definition:** `{}`",
+            word,
+            label.line + 1,
+            definition
+        );
+
+        let cfg = document.cfg();
+        if let Some((region, Some(address))) = document.layout().label(word) {
+            info.push_str(&format!("\n\n**Address:** {} ({})", address, region.name()));
+        }
+
+        if let Some(depth) = cfg
+            .block_of_label(word)
+            .and_then(|block| document.stack().block_entry(block))
+        {
+            info.push_str(&format!("\n\n**Stack depth on entry:** {}", depth));
+        }
+
+        if self.interrupts.handlers_in(document.lines()).iter().any(|handler| handler == word) {
+            info.push_str("\n\n**Interrupt handler:** entered on an interrupt, returns with `PWR`");
+        }
+
+        Some(info)
+    }
+
     fn get_instruction_info(&self, word: &str) -> Option<String> {
         let instruction = self.instruction_db.get_instruction(word)?;
 
@@ -91,17 +258,40 @@ impl HoverProvider {
             ""
         };
 
+        let examples = if instruction.examples.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "\n\n**Examples:**\n```asmodeus\n{}\n```",
+                instruction.examples.join("\n")
+            )
+        };
+
         let result = format!(
-            "**{}**{}\n\n**Operation:** `{}`\n\n{}\n\n**Description:** {}{}{}",
+            "**{}**{}\n\n**Operation:** `{}`\n\n{}\n\n**Cycles:** {}\n\n**Description:** {}{}{}{}",
             instruction.name,
             extended_note,
             instruction.operation,
             category_info,
+            instruction.cycles,
             instruction.description,
             operand_info,
-            extended_info
+            extended_info,
+            examples
         );
 
         Some(result)
     }
 }
+
+/// comma separated line numbers, or `none`
+fn list_lines(lines: &[u32]) -> String {
+    if lines.is_empty() {
+        return "none".to_string();
+    }
+    lines
+        .iter()
+        .map(|line| line.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
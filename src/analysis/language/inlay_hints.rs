@@ -0,0 +1,108 @@
+use crate::analysis::core::{DocumentCache, ParsedDocument};
+use crate::analysis::flow::{Effect, Statement};
+use crate::analysis::utils::{char_len, strip_comment, InstructionDatabase, MemoryModel};
+use tower_lsp::lsp_types::*;
+
+#[derive(Debug)]
+pub struct InlayHintProvider {
+    instruction_db: InstructionDatabase,
+    documents: DocumentCache,
+}
+
+impl Default for InlayHintProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InlayHintProvider {
+    pub fn new() -> Self {
+        Self::with_cache(DocumentCache::new())
+    }
+
+    pub fn with_cache(documents: DocumentCache) -> Self {
+        Self {
+            instruction_db: InstructionDatabase::new(),
+            documents,
+        }
+    }
+
+    pub fn with_instruction_db(mut self, instruction_db: InstructionDatabase) -> Self {
+        self.documents.set_instruction_db(instruction_db.clone());
+        self.instruction_db = instruction_db;
+        self
+    }
+
+    pub fn with_memory_model(self, memory_model: MemoryModel) -> Self {
+        self.documents.set_memory_model(memory_model);
+        self
+    }
+
+    /// hints for lines within range
+    pub fn get_inlay_hints(&self, content: &str, range: Range) -> Vec<InlayHint> {
+        let document = self.documents.get(content);
+        let cfg = document.cfg();
+        let stack = document.stack();
+        let values = document.values();
+        let mut hints = Vec::new();
+
+        for (index, statement) in cfg.statements().iter().enumerate() {
+            let line = statement.line().saturating_sub(1) as u32;
+            if line < range.start.line || line > range.end.line {
+                continue;
+            }
+
+            // AK after instructions that change it, when its value is known
+            if self.writes_ak(statement) {
+                if let Some(ak) = values.ak_after(index) {
+                    hints.push(end_of_line_hint(
+                        &document,
+                        line,
+                        format!("AK = {}", ak),
+                        "Accumulator after this instruction",
+                    ));
+                }
+            }
+
+            // stack depth after every push and pop
+            if statement.is_opcode("SDP") || statement.is_opcode("PZS") {
+                if let Some(depth) = stack.depth_after(cfg, index) {
+                    hints.push(end_of_line_hint(
+                        &document,
+                        line,
+                        format!("stack: {}", depth),
+                        "Stack depth after this instruction",
+                    ));
+                }
+            }
+        }
+
+        hints
+    }
+
+    fn writes_ak(&self, statement: &Statement) -> bool {
+        statement
+            .mnemonic()
+            .and_then(|mnemonic| self.instruction_db.get_instruction(mnemonic))
+            .map(|info| Effect::of_operation(&info.operation))
+            .is_some_and(|effect| matches!(effect, Effect::Load | Effect::Arithmetic(_)))
+    }
+}
+
+/// hint placed after the code on line, before any comment
+fn end_of_line_hint(document: &ParsedDocument, line: u32, label: String, tooltip: &str) -> InlayHint {
+    let code = document.line(line as usize).map(strip_comment).unwrap_or("");
+    InlayHint {
+        position: Position {
+            line,
+            character: char_len(code.trim_end()) as u32,
+        },
+        label: InlayHintLabel::String(label),
+        kind: None,
+        text_edits: None,
+        tooltip: Some(InlayHintTooltip::String(tooltip.to_string())),
+        padding_left: Some(true),
+        padding_right: None,
+        data: None,
+    }
+}
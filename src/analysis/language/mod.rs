@@ -1,5 +1,6 @@
 pub mod completion;
 pub mod hover;
+pub mod inlay_hints;
 pub mod navigation;
 pub mod semantic_tokens;
 pub mod signature_help;
@@ -8,6 +9,7 @@ pub mod validation;
 
 pub use completion::CompletionProvider;
 pub use hover::HoverProvider;
+pub use inlay_hints::InlayHintProvider;
 pub use navigation::NavigationProvider;
 pub use semantic_tokens::SemanticTokensProvider;
 pub use signature_help::SignatureHelpProvider;
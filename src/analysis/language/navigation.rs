@@ -1,16 +1,29 @@
+use crate::analysis::core::DocumentCache;
+use crate::LspError;
 use crate::analysis::utils::{
-    coordinates_to_location, create_word_location, find_label_definition, find_word_occurrences,
-    get_line_at_position, get_word_at_position, is_label_declaration, is_valid_position,
-    is_whole_word_match,
+    char_len, create_word_location, get_line_at_position, get_word_at_position,
+    is_valid_position,
 };
 use tower_lsp::lsp_types::*;
 
 #[derive(Debug)]
-pub struct NavigationProvider {}
+pub struct NavigationProvider {
+    documents: DocumentCache,
+}
+
+impl Default for NavigationProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl NavigationProvider {
     pub fn new() -> Self {
-        Self {}
+        Self::with_cache(DocumentCache::new())
+    }
+
+    pub fn with_cache(documents: DocumentCache) -> Self {
+        Self { documents }
     }
 
     /// go-to-definition functionality
@@ -30,20 +43,46 @@ impl NavigationProvider {
         let word_info = get_word_at_position(current_line, cursor_pos)?;
         let (word, _, _) = word_info;
 
+        if self.check_unambiguous(content, position).is_err() {
+            return None;
+        }
+
         self.find_label_definition(&word, content, uri)
     }
 
+    /// label under cursor defined more than once has no single target
+    pub fn check_unambiguous(&self, content: &str, position: Position) -> Result<(), LspError> {
+        let word = get_line_at_position(content, position)
+            .and_then(|line| get_word_at_position(line, position.character as usize));
+        let (word, _, _) = match word {
+            Some(word) => word,
+            None => return Ok(()),
+        };
+
+        let document = self.documents.get(content);
+        let lines: Vec<usize> = document
+            .symbols()
+            .definitions(&word)
+            .map(|label| label.line + 1)
+            .collect();
+
+        if lines.len() > 1 {
+            return Err(LspError::AmbiguousLabel { name: word, lines });
+        }
+
+        Ok(())
+    }
+
     fn find_label_definition(
         &self,
         label: &str,
         content: &str,
         uri: &Url,
     ) -> Option<GotoDefinitionResponse> {
-        if let Some((line_num, start_pos, _end_pos)) = find_label_definition(label, content) {
-            let location = create_word_location(uri, line_num, start_pos, label.len());
-            return Some(GotoDefinitionResponse::Scalar(location));
-        }
-        None
+        let document = self.documents.get(content);
+        let definition = document.symbols().find_label(label)?;
+        let location = create_word_location(uri, definition.line, definition.start, char_len(label));
+        Some(GotoDefinitionResponse::Scalar(location))
     }
 
     pub fn find_references(
@@ -72,32 +111,16 @@ impl NavigationProvider {
         };
 
         let (word, _, _) = word_info;
+        let document = self.documents.get(content);
 
-        // find ALL occurrences
-        let occurrences = find_word_occurrences(content, &word);
-        let lines: Vec<&str> = content.lines().collect();
-
-        for (line_num, start_pos, end_pos) in occurrences {
-            if line_num >= lines.len() {
-                continue;
-            }
-
-            let line = lines[line_num];
-
-            if !is_whole_word_match(line, start_pos, &word) {
-                continue;
-            }
-
+        // find ALL occurrences outside comments
+        for (line_num, start_pos, _end_pos) in document.word_occurrences(&word) {
             // skip definition if not needed
-            if !include_declaration && is_label_declaration(line, start_pos, &word) {
+            if !include_declaration && document.symbols().is_definition_at(line_num, start_pos) {
                 continue;
             }
 
-            if end_pos == start_pos + word.len() {
-                locations.push(create_word_location(uri, line_num, start_pos, word.len()));
-            } else {
-                locations.push(coordinates_to_location(uri, line_num, start_pos, end_pos));
-            }
+            locations.push(create_word_location(uri, line_num, start_pos, char_len(&word)));
         }
 
         locations
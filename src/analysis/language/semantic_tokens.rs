@@ -1,5 +1,53 @@
+use crate::analysis::core::{DocumentCache, ParsedDocument};
+use crate::analysis::flow::{AddressLayout, Region, Statement};
+use crate::analysis::utils::{is_word_char, strip_comment, InstructionDatabase, OperandType};
+use lexariel::{Token, TokenKind};
+use parseid::AddressingMode;
+use std::collections::HashSet;
 use tower_lsp::lsp_types::*;
-use crate::analysis::utils::{InstructionDatabase, is_word_char, could_be_label};
+
+/// token types in legend order; the position is the type index sent to clients
+pub const TOKEN_TYPES: &[SemanticTokenType] = &[
+    SemanticTokenType::KEYWORD,  // instructions and directives
+    SemanticTokenType::FUNCTION, // code labels
+    SemanticTokenType::NUMBER,   // immediates and data values
+    SemanticTokenType::OPERATOR, // #, [ and ]
+    SemanticTokenType::COMMENT,
+    SemanticTokenType::MACRO,
+    SemanticTokenType::PARAMETER,      // macro parameters
+    SemanticTokenType::VARIABLE,       // data labels
+    SemanticTokenType::new("address"), // numeric addresses
+];
+
+/// token modifiers in legend order; the position is the bit sent to clients
+pub const TOKEN_MODIFIERS: &[SemanticTokenModifier] = &[
+    SemanticTokenModifier::DECLARATION,
+    SemanticTokenModifier::READONLY, // constants no instruction stores to
+    SemanticTokenModifier::DEFAULT_LIBRARY, // extended instructions
+    SemanticTokenModifier::new("unused"), // labels nothing refers to
+];
+
+const KEYWORD: u32 = 0;
+const FUNCTION: u32 = 1;
+const NUMBER: u32 = 2;
+const OPERATOR: u32 = 3;
+const COMMENT: u32 = 4;
+const MACRO: u32 = 5;
+const PARAMETER: u32 = 6;
+const VARIABLE: u32 = 7;
+const ADDRESS: u32 = 8;
+
+const DECLARATION: u32 = 1 << 0;
+const READONLY: u32 = 1 << 1;
+const DEFAULT_LIBRARY: u32 = 1 << 2;
+const UNUSED: u32 = 1 << 3;
+
+pub fn semantic_tokens_legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_TYPES.to_vec(),
+        token_modifiers: TOKEN_MODIFIERS.to_vec(),
+    }
+}
 
 #[derive(Debug, Clone)]
 struct RawSemanticToken {
@@ -10,131 +58,296 @@ struct RawSemanticToken {
     token_modifiers: u32,
 }
 
+/// what a document says about its labels, computed once per request
+struct LabelFacts<'a> {
+    layout: &'a AddressLayout,
+    unused: HashSet<&'a str>,
+    /// data labels defined with `RST` that no instruction stores to
+    constants: HashSet<&'a str>,
+}
+
+impl<'a> LabelFacts<'a> {
+    fn build(document: &'a ParsedDocument, exported: &[String]) -> Self {
+        let cfg = document.cfg();
+        let unused = document
+            .unused_labels(exported)
+            .into_iter()
+            .map(|label| label.name.as_str())
+            .collect();
+
+        // a store through a pointer may write any cell
+        let mut written = HashSet::new();
+        let mut pointer_store = false;
+        for statement in cfg.statements() {
+            match statement {
+                Statement::MacroCall(call) => {
+                    written.extend(call.arguments.iter().map(String::as_str))
+                }
+                Statement::Instruction(..) if statement.is_opcode("ŁAD") => {
+                    match statement.operand() {
+                        Some(operand) if operand.addressing_mode == AddressingMode::Direct => {
+                            written.insert(operand.value.as_str());
+                        }
+                        _ => pointer_store = true,
+                    }
+                }
+                Statement::Instruction(..) => {}
+            }
+        }
+        let constants = if pointer_store {
+            HashSet::new()
+        } else {
+            cfg.data_cells()
+                .iter()
+                .filter(|cell| cell.directive == "RST")
+                .flat_map(|cell| cell.labels.iter().map(String::as_str))
+                .filter(|label| !written.contains(label))
+                .collect()
+        };
+
+        Self {
+            layout: document.layout(),
+            unused,
+            constants,
+        }
+    }
+
+    /// type and modifiers of a label; labels outside the layout are code
+    /// labels when used as a jump target
+    fn classify(&self, name: &str, jump_target: bool) -> (u32, u32) {
+        let token_type = match self.layout.label(name) {
+            Some((Region::Data, _)) => VARIABLE,
+            Some((Region::Code, _)) => FUNCTION,
+            None if jump_target => FUNCTION,
+            None => VARIABLE,
+        };
+        let mut modifiers = 0;
+        if token_type == VARIABLE && self.constants.contains(name) {
+            modifiers |= READONLY;
+        }
+        if self.unused.contains(name) {
+            modifiers |= UNUSED;
+        }
+        (token_type, modifiers)
+    }
+}
+
 #[derive(Debug)]
 pub struct SemanticTokensProvider {
     instruction_db: InstructionDatabase,
+    documents: DocumentCache,
+    exported_labels: Vec<String>,
+}
+
+impl Default for SemanticTokensProvider {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SemanticTokensProvider {
     pub fn new() -> Self {
+        Self::with_cache(DocumentCache::new())
+    }
+
+    pub fn with_cache(documents: DocumentCache) -> Self {
         Self {
             instruction_db: InstructionDatabase::new(),
+            documents,
+            exported_labels: Vec::new(),
         }
     }
 
+    pub fn with_instruction_db(mut self, instruction_db: InstructionDatabase) -> Self {
+        self.documents.set_instruction_db(instruction_db.clone());
+        self.instruction_db = instruction_db;
+        self
+    }
+
+    /// labels used from outside the document, never marked unused
+    pub fn with_exported_labels(mut self, exported_labels: Vec<String>) -> Self {
+        self.exported_labels = exported_labels;
+        self
+    }
+
     pub fn get_semantic_tokens(&self, content: &str) -> Vec<SemanticToken> {
-        let mut tokens = Vec::new();
-        let lines: Vec<&str> = content.lines().collect();
-        
-        for (line_num, line) in lines.iter().enumerate() {
-            let line_tokens = self.tokenize_line(line, line_num as u32);
-            tokens.extend(line_tokens);
-        }
-        
+        let document = self.documents.get(content);
+        let labels = LabelFacts::build(&document, &self.exported_labels);
+
+        let mut tokens = self.classify_tokens(document.tokens(), &labels);
+        tokens.extend(self.classify_rejected_lines(&document, &labels));
+        tokens.extend(comment_tokens(&document));
+        tokens.sort_by_key(|token| (token.line, token.start_char));
+
         // convert to delta encoding
         self.encode_semantic_tokens(tokens)
     }
 
-    fn tokenize_line(&self, line: &str, line_num: u32) -> Vec<RawSemanticToken> {
-        let mut tokens = Vec::new();
-        let chars: Vec<char> = line.chars().collect();
-        let mut i = 0;
-        
-        while i < chars.len() {
-            if chars[i].is_whitespace() {
-                i += 1;
-                continue;
-            }
-            
-            // comment 
-            if chars[i] == ';' {
-                tokens.push(RawSemanticToken {
-                    line: line_num,
-                    start_char: i as u32,
-                    length: (chars.len() - i) as u32,
-                    token_type: 4, // COMMENT
-                    token_modifiers: 0,
-                });
-                break;
-            }
-            
-            // operands
-            if chars[i] == '#' {
-                tokens.push(RawSemanticToken {
-                    line: line_num,
-                    start_char: i as u32,
-                    length: 1,
-                    token_type: 3, // OPERATOR
-                    token_modifiers: 0,
-                });
-                i += 1;
-                continue;
+    /// walks the lexer tokens, which come in line order; lines the lexer
+    /// rejected have none
+    fn classify_tokens(&self, tokens: &[Token], labels: &LabelFacts) -> Vec<RawSemanticToken> {
+        let mut raw = Vec::new();
+        // parameters of the macro being defined, until `KONM`
+        let mut parameters: Vec<&str> = Vec::new();
+
+        for line in tokens.chunk_by(|a, b| a.line == b.line) {
+            // operand kind of the instruction on the line, once seen
+            let mut operand_type = None;
+            let mut macro_header = false;
+            let mut macro_name_seen = false;
+            let mut at_statement_start = true;
+
+            for (index, token) in line.iter().enumerate() {
+                let previous = index.checked_sub(1).map(|previous| &line[previous]);
+                let classified = match token.kind {
+                    TokenKind::Keyword => {
+                        let info = self.instruction_db.get_instruction(&token.value);
+                        operand_type = Some(
+                            info.map_or(OperandType::Flexible, |info| info.operand_type.clone()),
+                        );
+                        let modifiers = if info.is_some_and(|info| info.is_extended) {
+                            DEFAULT_LIBRARY
+                        } else {
+                            0
+                        };
+                        Some((KEYWORD, modifiers))
+                    }
+                    TokenKind::Directive => {
+                        match token.value.to_uppercase().as_str() {
+                            "MAKRO" => {
+                                macro_header = true;
+                                parameters.clear();
+                            }
+                            "KONM" => parameters.clear(),
+                            _ => {}
+                        }
+                        Some((KEYWORD, 0))
+                    }
+                    TokenKind::LabelDef => {
+                        let (token_type, modifiers) = labels.classify(&token.value, true);
+                        Some((token_type, modifiers | DECLARATION))
+                    }
+                    TokenKind::Identifier if macro_header => {
+                        if macro_name_seen {
+                            parameters.push(&token.value);
+                            Some((PARAMETER, DECLARATION))
+                        } else {
+                            macro_name_seen = true;
+                            Some((MACRO, DECLARATION))
+                        }
+                    }
+                    TokenKind::Identifier if parameters.contains(&token.value.as_str()) => {
+                        Some((PARAMETER, 0))
+                    }
+                    TokenKind::Identifier if at_statement_start => {
+                        // the parser reads unknown mnemonics as macro calls
+                        match self.instruction_db.get_instruction(&token.value) {
+                            Some(info) => {
+                                operand_type = Some(info.operand_type.clone());
+                                let modifiers = if info.is_extended { DEFAULT_LIBRARY } else { 0 };
+                                Some((KEYWORD, modifiers))
+                            }
+                            None => Some((MACRO, 0)),
+                        }
+                    }
+                    TokenKind::Identifier => Some(
+                        labels.classify(&token.value, operand_type == Some(OperandType::LabelOnly)),
+                    ),
+                    TokenKind::Number => {
+                        let immediate = previous.is_some_and(|previous| previous.value == "#");
+                        if operand_type.is_some() && !immediate {
+                            Some((ADDRESS, 0))
+                        } else {
+                            Some((NUMBER, 0))
+                        }
+                    }
+                    TokenKind::Punctuation if matches!(token.value.as_str(), "#" | "[" | "]") => {
+                        Some((OPERATOR, 0))
+                    }
+                    TokenKind::Punctuation | TokenKind::Eof => None,
+                };
+
+                at_statement_start = token.kind == TokenKind::LabelDef;
+
+                if let Some((token_type, token_modifiers)) = classified {
+                    raw.push(RawSemanticToken {
+                        line: token.line.saturating_sub(1) as u32,
+                        start_char: token.column.saturating_sub(1) as u32,
+                        length: token.value.chars().count() as u32,
+                        token_type,
+                        token_modifiers,
+                    });
+                }
             }
-            
-            if chars[i] == '[' || chars[i] == ']' {
-                tokens.push(RawSemanticToken {
-                    line: line_num,
-                    start_char: i as u32,
-                    length: 1,
-                    token_type: 3, // OPERATOR
-                    token_modifiers: 0,
-                });
-                i += 1;
+        }
+
+        raw
+    }
+
+    /// lines the lexer rejected, often ones being typed, are split into words
+    /// and classified without the lexer's help
+    fn classify_rejected_lines(
+        &self,
+        document: &ParsedDocument,
+        labels: &LabelFacts,
+    ) -> Vec<RawSemanticToken> {
+        let lexed: HashSet<usize> = document
+            .tokens()
+            .iter()
+            .filter(|token| token.kind != TokenKind::Eof)
+            .map(|token| token.line.saturating_sub(1))
+            .collect();
+        let mut raw = Vec::new();
+
+        for (line_num, line) in document.lines().enumerate() {
+            if lexed.contains(&line_num) {
                 continue;
             }
-            
-            // word/number
-            let start = i;
-            while i < chars.len() && is_word_char(chars[i]) {
-                i += 1;
-            }
-            
-            if start < i {
-                let word: String = chars[start..i].iter().collect();
-                let token_type = self.classify_token(&word, line, start);
-                
-                tokens.push(RawSemanticToken {
-                    line: line_num,
-                    start_char: start as u32,
-                    length: (i - start) as u32,
-                    token_type,
-                    token_modifiers: 0,
-                });
-            } else {
-                i += 1; // unknown symbol
+            let chars: Vec<char> = strip_comment(line).chars().collect();
+            let mut i = 0;
+            while i < chars.len() {
+                let start = i;
+                let classified = if matches!(chars[i], '#' | '[' | ']') {
+                    i += 1;
+                    Some((OPERATOR, 0))
+                } else if is_word_char(chars[i]) {
+                    while i < chars.len() && is_word_char(chars[i]) {
+                        i += 1;
+                    }
+                    let word: String = chars[start..i].iter().collect();
+                    Some(self.classify_word(&word, chars.get(i) == Some(&':'), labels))
+                } else {
+                    i += 1;
+                    None
+                };
+
+                if let Some((token_type, token_modifiers)) = classified {
+                    raw.push(RawSemanticToken {
+                        line: line_num as u32,
+                        start_char: start as u32,
+                        length: (i - start) as u32,
+                        token_type,
+                        token_modifiers,
+                    });
+                }
             }
         }
-        
-        tokens
+
+        raw
     }
 
-    /// based on content and context
-    fn classify_token(&self, word: &str, line: &str, position: usize) -> u32 {
-        // number 
-        if word.chars().all(|c| c.is_ascii_digit()) || 
-           word.starts_with("0x") || word.starts_with("0b") {
-            return 2; // NUMBER
-        }
-        
-        // instruction
-        if self.instruction_db.is_valid_instruction(word) {
-            return 0; // KEYWORD
-        }
-        
-        // label (after which is : )
-        let after_word = position + word.len();
-        if let Some(next_char) = line.chars().nth(after_word) {
-            if next_char == ':' {
-                return 1; // FUNCTION (label definition)
-            }
+    /// type and modifiers of a word on a line the lexer rejected
+    fn classify_word(&self, word: &str, definition: bool, labels: &LabelFacts) -> (u32, u32) {
+        if word.starts_with(|c: char| c.is_ascii_digit()) {
+            (NUMBER, 0)
+        } else if definition {
+            let (token_type, modifiers) = labels.classify(word, true);
+            (token_type, modifiers | DECLARATION)
+        } else if let Some(info) = self.instruction_db.get_instruction(word) {
+            (KEYWORD, if info.is_extended { DEFAULT_LIBRARY } else { 0 })
+        } else {
+            labels.classify(word, false)
         }
-        
-        // label reference
-        if could_be_label(word) {
-            return 1; // FUNCTION (label reference)
-        }
-        
-        2 // defaults to NUMBER/IDENTIFIER
     }
 
     /// raw tokens to delta encoding format (LSP requirement)
@@ -142,7 +355,7 @@ impl SemanticTokensProvider {
         let mut encoded = Vec::new();
         let mut prev_line = 0;
         let mut prev_char = 0;
-        
+
         for token in tokens {
             let delta_line = token.line - prev_line;
             let delta_char = if delta_line == 0 {
@@ -150,7 +363,7 @@ impl SemanticTokensProvider {
             } else {
                 token.start_char
             };
-            
+
             encoded.push(SemanticToken {
                 delta_line,
                 delta_start: delta_char,
@@ -158,11 +371,30 @@ impl SemanticTokensProvider {
                 token_type: token.token_type,
                 token_modifiers_bitset: token.token_modifiers,
             });
-            
+
             prev_line = token.line;
             prev_char = token.start_char;
         }
-        
+
         encoded
     }
 }
+
+/// comments are not lexer tokens, they run from `;` or `//` to the line end
+fn comment_tokens(document: &ParsedDocument) -> Vec<RawSemanticToken> {
+    document
+        .lines()
+        .enumerate()
+        .filter_map(|(line_num, line)| {
+            let start = strip_comment(line).chars().count();
+            let length = line.chars().count() - start;
+            (length > 0).then_some(RawSemanticToken {
+                line: line_num as u32,
+                start_char: start as u32,
+                length: length as u32,
+                token_type: COMMENT,
+                token_modifiers: 0,
+            })
+        })
+        .collect()
+}
@@ -1,44 +1,48 @@
-use crate::analysis::utils::{create_location, is_valid_symbol_name, word_range};
+use crate::analysis::core::DocumentCache;
+use crate::analysis::utils::{create_location, word_range};
 use tower_lsp::lsp_types::*;
 
 #[derive(Debug)]
-pub struct SymbolProvider {}
+pub struct SymbolProvider {
+    documents: DocumentCache,
+}
+
+impl Default for SymbolProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl SymbolProvider {
     pub fn new() -> Self {
-        Self {}
+        Self::with_cache(DocumentCache::new())
+    }
+
+    pub fn with_cache(documents: DocumentCache) -> Self {
+        Self { documents }
     }
 
     #[allow(deprecated)]
     pub fn get_document_symbols(&self, content: &str) -> Vec<SymbolInformation> {
-        let mut symbols = Vec::new();
-        let lines: Vec<&str> = content.lines().collect();
-
-        for (line_num, line) in lines.iter().enumerate() {
-            println!("Processing line {}: '{}'", line_num, line);
-            let trimmed = line.trim();
-
-            // search for labels
-            if let Some(colon_pos) = trimmed.find(':') {
-                let label_name = &trimmed[..colon_pos];
-
-                if !label_name.is_empty() && is_valid_symbol_name(label_name) {
-                    let label_start = line.len() - trimmed.len(); // leading whitespace
-                    let range = word_range(line_num as u32, label_start, label_start + colon_pos);
-
-                    symbols.push(SymbolInformation {
-                        name: label_name.to_string(),
-                        kind: SymbolKind::FUNCTION,
-                        tags: None,
-                        deprecated: None,
-                        location: create_location(&Url::parse("file:///dummy").unwrap(), range),
-                        container_name: None,
-                    });
-                }
-            }
-        }
+        let document = self.documents.get(content);
 
-        symbols
+        // search for labels
+        document
+            .symbols()
+            .labels()
+            .iter()
+            .map(|label| SymbolInformation {
+                name: label.name.clone(),
+                kind: SymbolKind::FUNCTION,
+                tags: None,
+                deprecated: None,
+                location: create_location(
+                    &Url::parse("file:///dummy").unwrap(),
+                    word_range(label.line as u32, label.start, label.end),
+                ),
+                container_name: None,
+            })
+            .collect()
     }
 
     /// based on query
@@ -1,58 +1,269 @@
 use tower_lsp::lsp_types::*;
-use crate::analysis::utils::{InstructionDatabase, create_semantic_diagnostic};
+use crate::analysis::core::{
+    DocumentCache, LabelNaming, LabelReference, LabelSymbol, ParsedDocument, SymbolTable,
+};
+use crate::analysis::utils::{
+    DiagnosticCode, InstructionDatabase, InstructionInfo, IsaProfile, MemoryModel, OperandType, char_len,
+    create_diagnostic,
+    find_number_literals, literal_radix, parse_number_literal,
+    create_semantic_diagnostic, find_similar_names, is_valid_symbol_name, slice_columns,
+    strip_comment, word_range,
+};
+use parseid::{AddressingMode, Operand};
 
 #[derive(Debug)]
 pub struct ValidationProvider {
     instruction_db: InstructionDatabase,
+    documents: DocumentCache,
+    memory_model: MemoryModel,
+    exported_labels: Vec<String>,
+    isa: IsaProfile,
+    label_naming: LabelNaming,
+    normalize_mnemonics: bool,
+}
+
+impl Default for ValidationProvider {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ValidationProvider {
     pub fn new() -> Self {
+        Self::with_cache(DocumentCache::new())
+    }
+
+    pub fn with_cache(documents: DocumentCache) -> Self {
         Self {
             instruction_db: InstructionDatabase::new(),
+            documents,
+            memory_model: MemoryModel::default(),
+            exported_labels: Vec::new(),
+            isa: IsaProfile::default(),
+            label_naming: LabelNaming::default(),
+            normalize_mnemonics: false,
         }
     }
 
-    /// AST semantic
-    pub fn validate_semantics(&self, ast: &parseid::Program) -> Vec<Diagnostic> {
+    pub fn with_instruction_db(mut self, instruction_db: InstructionDatabase) -> Self {
+        self.documents.set_instruction_db(instruction_db.clone());
+        self.instruction_db = instruction_db;
+        self
+    }
+
+    pub fn with_memory_model(mut self, memory_model: MemoryModel) -> Self {
+        self.memory_model = memory_model;
+        self
+    }
+
+    /// labels used from outside the document, never reported as unused
+    pub fn with_exported_labels(mut self, exported_labels: Vec<String>) -> Self {
+        self.exported_labels = exported_labels;
+        self
+    }
+
+    /// instruction set the document may use
+    pub fn with_isa_profile(mut self, isa: IsaProfile) -> Self {
+        self.isa = isa;
+        self
+    }
+
+    pub fn with_mnemonic_normalization(mut self, normalize: bool) -> Self {
+        self.normalize_mnemonics = normalize;
+        self
+    }
+
+    /// case style label definitions are checked against
+    pub fn with_label_naming(mut self, label_naming: LabelNaming) -> Self {
+        self.label_naming = label_naming;
+        self
+    }
+
+    /// malformed literals, immediates wider than a word and addresses past
+    /// the end of memory
+    pub fn validate_numeric_literals(&self, content: &str) -> Vec<Diagnostic> {
+        let document = self.documents.get(content);
         let mut diagnostics = Vec::new();
 
-        for element in &ast.elements {
+        for (line_num, line) in document.lines().enumerate() {
+            for (literal, start, end) in find_number_literals(strip_comment(line)) {
+                if parse_number_literal(&literal).is_none() {
+                    diagnostics.push(create_diagnostic(
+                        word_range(line_num as u32, start, end),
+                        DiagnosticCode::Sem015,
+                        format!(
+                            "Malformed {} literal '{}'",
+                            literal_radix(&literal).name(),
+                            literal
+                        )));
+                }
+            }
+        }
+
+        for element in &document.ast().elements {
             match element {
                 parseid::ProgramElement::Instruction(instruction) => {
-                    if let Err(diagnostic) = self.validate_instruction(instruction) {
+                    if let Some(diagnostic) = self.check_operand_range(&document, instruction) {
                         diagnostics.push(diagnostic);
                     }
                 }
+                parseid::ProgramElement::Directive(directive)
+                    if directive.name.eq_ignore_ascii_case("RST") =>
+                {
+                    for argument in &directive.arguments {
+                        if let Some(diagnostic) =
+                            self.check_word_literal(&document, directive.line, directive.column, argument)
+                        {
+                            diagnostics.push(diagnostic);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        diagnostics.sort_by_key(|diagnostic| {
+            (diagnostic.range.start.line, diagnostic.range.start.character)
+        });
+        diagnostics
+    }
+
+    fn check_operand_range(
+        &self,
+        document: &ParsedDocument,
+        instruction: &parseid::Instruction,
+    ) -> Option<Diagnostic> {
+        let operand = instruction.operand.as_ref()?;
+
+        match operand.addressing_mode {
+            AddressingMode::Immediate => {
+                self.check_word_literal(document, instruction.line, instruction.column, &operand.value)
+            }
+            AddressingMode::Direct | AddressingMode::Indirect | AddressingMode::MultipleIndirect => {
+                let address = parse_number_literal(&operand.value)?;
+                if self.memory_model.fits_address(address) {
+                    return None;
+                }
+
+                let range = literal_range(document, instruction.line, instruction.column, &operand.value)?;
+                Some(create_diagnostic(
+                    range,
+                    DiagnosticCode::Sem014,
+                    format!(
+                        "Address {} is past the end of memory ({} words, {}-bit addresses)",
+                        address,
+                        self.memory_model.memory_size(),
+                        self.memory_model.address_bits
+                    )))
+            }
+            _ => None,
+        }
+    }
+
+    fn check_word_literal(
+        &self,
+        document: &ParsedDocument,
+        line: usize,
+        column: usize,
+        literal: &str,
+    ) -> Option<Diagnostic> {
+        let value = parse_number_literal(literal)?;
+        if self.memory_model.fits_word(value) {
+            return None;
+        }
+
+        let range = literal_range(document, line, column, literal)?;
+        Some(create_diagnostic(
+            range,
+            DiagnosticCode::Sem013,
+            format!(
+                "Value {} does not fit in a {}-bit word",
+                value, self.memory_model.word_bits
+            )))
+    }
+
+    /// AST semantic
+    pub fn validate_semantics(&self, content: &str) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let document = self.documents.get(content);
+
+        for element in &document.ast().elements {
+            match element {
+                parseid::ProgramElement::Instruction(instruction) => {
+                    if let Err(diagnostic) = self.validate_instruction(&document, instruction) {
+                        diagnostics.push(*diagnostic);
+                    }
+                }
                 parseid::ProgramElement::MacroCall(macro_call) => {
                     if let Err(diagnostic) = self.validate_macro_call(macro_call) {
-                        diagnostics.push(diagnostic);
+                        diagnostics.push(*diagnostic);
                     }
                 }
                 _ => {}
             }
         }
 
+        if self.normalize_mnemonics {
+            diagnostics.extend(self.validate_mnemonic_spelling(&document));
+        }
+
         diagnostics
     }
 
+    /// mnemonics written in lowercase or as an alias
+    fn validate_mnemonic_spelling(&self, document: &ParsedDocument) -> Vec<Diagnostic> {
+        document
+            .mnemonics()
+            .into_iter()
+            .filter_map(|(spelling, line, start, end)| {
+                let canonical = self.instruction_db.canonical_name(spelling)?;
+                (canonical != spelling).then(|| {
+                    create_diagnostic(
+                        word_range(line as u32, start, end),
+                        DiagnosticCode::Sem041,
+                        format!("'{}' is not the canonical spelling of '{}'", spelling, canonical))
+                })
+            })
+            .collect()
+    }
+
     /// single instruction 
-    fn validate_instruction(&self, instruction: &parseid::Instruction) -> Result<(), Diagnostic> {
+    fn validate_instruction(
+        &self,
+        document: &ParsedDocument,
+        instruction: &parseid::Instruction,
+    ) -> Result<(), Box<Diagnostic>> {
         let opcode_str = &instruction.opcode;
         
         if !self.instruction_db.is_valid_instruction(opcode_str) {
-            return Err(create_semantic_diagnostic(
+            return Err(Box::new(create_semantic_diagnostic(
                 instruction.line,
                 instruction.column,
-                opcode_str.len(),
-                "SEM001",
-                format!("Unknown instruction: '{}'", opcode_str),
-            ));
+                char_len(opcode_str),
+                DiagnosticCode::Sem001,
+                format!("Unknown instruction: '{}'", opcode_str))));
         }
 
         // external validation - whether instruction has proper operands 
         if let Some(instruction_info) = self.instruction_db.get_instruction(opcode_str) {
+            if !self.isa.allows(instruction_info) {
+                return Err(Box::new(create_semantic_diagnostic(
+                    instruction.line,
+                    instruction.column,
+                    char_len(opcode_str),
+                    DiagnosticCode::Sem039,
+                    format!(
+                        "'{}' is an extended instruction, not part of the {} instruction set",
+                        opcode_str,
+                        self.isa.name()
+                    ))));
+            }
+
             self.validate_instruction_operands(instruction, instruction_info)?;
+
+            if let Some(operand) = &instruction.operand {
+                self.validate_operand_kind(document, instruction, operand, instruction_info)?;
+            }
         }
 
         Ok(())
@@ -61,31 +272,29 @@ impl ValidationProvider {
     fn validate_instruction_operands(
         &self, 
         instruction: &parseid::Instruction, 
-        instruction_info: &crate::analysis::utils::InstructionInfo
-    ) -> Result<(), Diagnostic> {
+        instruction_info: &InstructionInfo
+    ) -> Result<(), Box<Diagnostic>> {
         match instruction_info.operand_type {
-            crate::analysis::utils::OperandType::None => {
+            OperandType::None => {
                 // should not have operands
                 if instruction.operand.is_some() {
-                    return Err(create_semantic_diagnostic(
+                    return Err(Box::new(create_semantic_diagnostic(
                         instruction.line,
-                        instruction.column + instruction.opcode.len(),
+                        instruction.column + char_len(&instruction.opcode),
                         1,
-                        "SEM003",
-                        format!("Instruction '{}' does not take operands", instruction.opcode),
-                    ));
+                        DiagnosticCode::Sem003,
+                        format!("Instruction '{}' does not take operands", instruction.opcode))));
                 }
             },
             _ => {
                 // should have operands 
                 if instruction.operand.is_none() {
-                    return Err(create_semantic_diagnostic(
+                    return Err(Box::new(create_semantic_diagnostic(
                         instruction.line,
-                        instruction.column + instruction.opcode.len(),
+                        instruction.column + char_len(&instruction.opcode),
                         1,
-                        "SEM004",
-                        format!("Instruction '{}' requires an operand", instruction.opcode),
-                    ));
+                        DiagnosticCode::Sem004,
+                        format!("Instruction '{}' requires an operand", instruction.opcode))));
                 }
             }
         }
@@ -93,99 +302,268 @@ impl ValidationProvider {
         Ok(())
     }
 
-    fn validate_macro_call(&self, macro_call: &parseid::MacroCall) -> Result<(), Diagnostic> {
+    /// operand addressing mode must fit the instruction's `OperandType`
+    fn validate_operand_kind(
+        &self,
+        document: &ParsedDocument,
+        instruction: &parseid::Instruction,
+        operand: &Operand,
+        instruction_info: &InstructionInfo,
+    ) -> Result<(), Box<Diagnostic>> {
+        let is_immediate = operand.addressing_mode == AddressingMode::Immediate;
+        let is_label = operand.addressing_mode == AddressingMode::Direct
+            && is_valid_symbol_name(&operand.value);
+
+        let (code, expected) = match instruction_info.operand_type {
+            OperandType::LabelOnly if !is_label => {
+                (DiagnosticCode::Sem010, "expects a label operand")
+            }
+            OperandType::ImmediateOnly if !is_immediate => {
+                (DiagnosticCode::Sem011, "expects an immediate operand")
+            }
+            OperandType::AddressOrLabelOnly if is_immediate => {
+                (DiagnosticCode::Sem012, "cannot store to an immediate value")
+            }
+            _ => return Ok(()),
+        };
+
+        let (line, start, end) = document
+            .operand_span(instruction.line, instruction.column)
+            .unwrap_or((
+                instruction.line.saturating_sub(1),
+                instruction.column.saturating_sub(1),
+                instruction.column.saturating_sub(1) + char_len(&instruction.opcode),
+            ));
+        let operand_text = document
+            .line(line)
+            .map(|text| slice_columns(text, start, end))
+            .unwrap_or_default();
+
+        Err(Box::new(create_diagnostic(
+            word_range(line as u32, start, end),
+            code,
+            format!(
+                "Instruction '{}' {}, found '{}'",
+                instruction.opcode, expected, operand_text
+            ))))
+    }
+
+    fn validate_macro_call(&self, macro_call: &parseid::MacroCall) -> Result<(), Box<Diagnostic>> {
         let name = &macro_call.name;
-        
+
+        // the lexer only knows Machine W mnemonics, instructions of a custom
+        // instruction set arrive as macro calls
+        if self.instruction_db.is_valid_instruction(name) {
+            return Ok(());
+        }
+
         // whether is not an instruction used as macro
         if name.chars().all(|c| c.is_uppercase() || c == '_') {
-            return Err(create_semantic_diagnostic(
+            return Err(Box::new(create_semantic_diagnostic(
                 macro_call.line,
                 macro_call.column,
-                name.len(),
-                "SEM002",
-                format!("Unknown instruction or undefined macro: '{}'", name),
-            ));
+                char_len(name),
+                DiagnosticCode::Sem002,
+                format!("Unknown instruction or undefined macro: '{}'", name))));
         }
 
         Ok(())
     }
 
+    /// operands naming labels that are never defined
+    pub fn validate_label_references(&self, content: &str, uri: &Url) -> Vec<Diagnostic> {
+        let document = self.documents.get(content);
+        let symbols = document.symbols();
+
+        symbols
+            .references()
+            .iter()
+            .filter(|reference| !symbols.is_defined(&reference.name))
+            .map(|reference| self.undefined_label_diagnostic(reference, symbols, uri))
+            .collect()
+    }
+
+    /// labels defined more than once, reported at every definition
+    pub fn validate_label_definitions(&self, content: &str, uri: &Url) -> Vec<Diagnostic> {
+        let document = self.documents.get(content);
+        let symbols = document.symbols();
+        let mut diagnostics = Vec::new();
+
+        for label in symbols.labels() {
+            let others: Vec<&LabelSymbol> = symbols
+                .definitions(&label.name)
+                .filter(|other| *other != label)
+                .collect();
+            if others.is_empty() {
+                continue;
+            }
+
+            let mut diagnostic = create_diagnostic(
+                word_range(label.line as u32, label.start, label.end),
+                DiagnosticCode::Sem009,
+                format!("Duplicate label definition: '{}'", label.name));
+            diagnostic.related_information = Some(
+                others
+                    .into_iter()
+                    .map(|other| DiagnosticRelatedInformation {
+                        location: Location {
+                            uri: uri.clone(),
+                            range: word_range(other.line as u32, other.start, other.end),
+                        },
+                        message: format!("'{}' also defined here", other.name),
+                    })
+                    .collect(),
+            );
+            diagnostics.push(diagnostic);
+        }
+
+        diagnostics
+    }
+
+    /// labels defined but never jumped to or read
+    pub fn validate_unused_labels(&self, content: &str) -> Vec<Diagnostic> {
+        let document = self.documents.get(content);
+
+        document
+            .unused_labels(&self.exported_labels)
+            .into_iter()
+            .map(|label| {
+                create_diagnostic(
+                    word_range(label.line as u32, label.start, label.end),
+                    DiagnosticCode::Sem037,
+                    format!("Label '{}' is never used", label.name))
+            })
+            .collect()
+    }
+
+    fn undefined_label_diagnostic(
+        &self,
+        reference: &LabelReference,
+        symbols: &SymbolTable,
+        uri: &Url,
+    ) -> Diagnostic {
+        let mut diagnostic = create_diagnostic(
+            word_range(reference.line as u32, reference.start, reference.end),
+            DiagnosticCode::Sem008,
+            format!("Undefined label: '{}'", reference.name));
+        // quick fixes read the name from here rather than the message
+        if let Some(data) = diagnostic.data.as_mut() {
+            data["label"] = reference.name.clone().into();
+        }
+
+        // point at labels the user may have meant
+        let similar = find_similar_names(
+            &reference.name,
+            symbols.labels().iter().map(|label| label.name.as_str()),
+        );
+        let related: Vec<DiagnosticRelatedInformation> = similar
+            .into_iter()
+            .filter_map(|name| symbols.find_label(name))
+            .map(|label| DiagnosticRelatedInformation {
+                location: Location {
+                    uri: uri.clone(),
+                    range: word_range(label.line as u32, label.start, label.end),
+                },
+                message: format!("Similar label '{}' defined here", label.name),
+            })
+            .collect();
+        if !related.is_empty() {
+            diagnostic.related_information = Some(related);
+        }
+
+        diagnostic
+    }
+
     pub fn validate_symbol_usage(&self, content: &str) -> Vec<Diagnostic> {
         let mut diagnostics = Vec::new();
-        let lines: Vec<&str> = content.lines().collect();
+        let document = self.documents.get(content);
 
-        for (line_num, line) in lines.iter().enumerate() {
+        for (line_num, line) in document.lines().enumerate() {
             // forbidden symbols check
             if let Some(diagnostic) = self.check_invalid_characters(line, line_num) {
                 diagnostics.push(diagnostic);
             }
+        }
 
-            // valid label naming check
-            if let Some(diagnostic) = self.check_label_naming(line, line_num) {
+        // valid label naming check
+        let symbols = document.symbols();
+        for label in symbols.invalid_labels() {
+            diagnostics.push(self.invalid_label_name_diagnostic(label));
+        }
+        for label in symbols.labels() {
+            if let Some(diagnostic) = self.check_label_naming(label) {
                 diagnostics.push(diagnostic);
             }
         }
 
+        diagnostics.sort_by_key(|diagnostic| {
+            (diagnostic.range.start.line, diagnostic.range.start.character)
+        });
         diagnostics
     }
 
     fn check_invalid_characters(&self, line: &str, line_num: usize) -> Option<Diagnostic> {
         // outside comments
-        if let Some(comment_pos) = line.find(';') {
-            let code_part = &line[..comment_pos];
-            self.find_invalid_chars_in_code(code_part, line_num)
-        } else {
-            self.find_invalid_chars_in_code(line, line_num)
-        }
+        self.find_invalid_chars_in_code(strip_comment(line), line_num)
     }
 
     fn find_invalid_chars_in_code(&self, code: &str, line_num: usize) -> Option<Diagnostic> {
-        for (char_pos, ch) in code.char_indices() {
+        for (char_pos, ch) in code.chars().enumerate() {
             if !ch.is_alphanumeric() && ch != '_' && ch != 'Ł' && ch != ' ' && ch != '\t' 
                 && ch != '#' && ch != '[' && ch != ']' && ch != ':' {
                 return Some(create_semantic_diagnostic(
                     line_num + 1,
                     char_pos + 1,
                     1,
-                    "SEM005",
-                    format!("Invalid character '{}' in code", ch),
-                ));
+                    DiagnosticCode::Sem005,
+                    format!("Invalid character '{}' in code", ch)));
             }
         }
         None
     }
 
-    fn check_label_naming(&self, line: &str, line_num: usize) -> Option<Diagnostic> {
-        let trimmed = line.trim();
-        
-        if let Some(colon_pos) = trimmed.find(':') {
-            let label_name = &trimmed[..colon_pos];
-            
-            // valid label naming check
-            if !crate::analysis::utils::is_valid_symbol_name(label_name) {
-                let label_start = line.len() - trimmed.len();
-                return Some(create_semantic_diagnostic(
-                    line_num + 1,
-                    label_start + 1,
-                    label_name.len(),
-                    "SEM006",
-                    format!("Invalid label name '{}'. Labels must start with a letter or underscore and contain only alphanumeric characters and underscores", label_name),
-                ));
-            }
+    fn invalid_label_name_diagnostic(&self, label: &LabelSymbol) -> Diagnostic {
+        create_semantic_diagnostic(
+            label.line + 1,
+            label.start + 1,
+            char_len(&label.name),
+            DiagnosticCode::Sem006,
+            format!("Invalid label name '{}'. Labels must start with a letter or underscore and contain only alphanumeric characters and underscores", label.name))
+    }
 
-            // whether label name is instruction name as well
-            if self.instruction_db.is_valid_instruction(label_name) {
-                let label_start = line.len() - trimmed.len();
-                return Some(create_semantic_diagnostic(
-                    line_num + 1,
-                    label_start + 1,
-                    label_name.len(),
-                    "SEM007",
-                    format!("Label name '{}' conflicts with instruction name", label_name),
-                ));
-            }
+    fn check_label_naming(&self, label: &LabelSymbol) -> Option<Diagnostic> {
+        // whether label name is instruction name as well
+        if self.instruction_db.is_valid_instruction(&label.name) {
+            return Some(create_semantic_diagnostic(
+                label.line + 1,
+                label.start + 1,
+                char_len(&label.name),
+                DiagnosticCode::Sem007,
+                format!("Label name '{}' conflicts with instruction name", label.name)));
+        }
+
+        if !self.label_naming.accepts(&label.name) {
+            return Some(create_diagnostic(
+                word_range(label.line as u32, label.start, label.end),
+                DiagnosticCode::Sem040,
+                format!(
+                    "Label '{}' is not {}",
+                    label.name,
+                    self.label_naming.name()
+                )));
         }
 
         None
     }
 }
+
+/// range of literal after the element starting at 1-based (line, column)
+fn literal_range(document: &ParsedDocument, line: usize, column: usize, literal: &str) -> Option<Range> {
+    let line_num = line.checked_sub(1)?;
+    let code = strip_comment(document.line(line_num)?);
+
+    find_number_literals(code)
+        .into_iter()
+        .find(|(text, start, _)| text == literal && *start >= column.saturating_sub(1))
+        .map(|(_, start, end)| word_range(line_num as u32, start, end))
+}
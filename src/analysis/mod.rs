@@ -1,4 +1,5 @@
 pub mod core;
+pub mod flow;
 pub mod language;
 pub mod refactoring;
 pub mod utils;
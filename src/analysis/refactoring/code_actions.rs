@@ -1,5 +1,7 @@
 use tower_lsp::lsp_types::*;
+use crate::analysis::core::DocumentCache;
 use crate::analysis::refactoring::{QuickFixProvider, RefactoringActionsProvider};
+use crate::analysis::utils::InstructionDatabase;
 
 #[derive(Debug)]
 pub struct CodeActionsProvider {
@@ -7,22 +9,44 @@ pub struct CodeActionsProvider {
     refactoring_provider: RefactoringActionsProvider,
 }
 
+impl Default for CodeActionsProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CodeActionsProvider {
     pub fn new() -> Self {
+        Self::with_cache(DocumentCache::new())
+    }
+
+    pub fn with_cache(documents: DocumentCache) -> Self {
         Self {
-            quick_fix_provider: QuickFixProvider::new(),
-            refactoring_provider: RefactoringActionsProvider::new(),
+            quick_fix_provider: QuickFixProvider::with_cache(documents.clone()),
+            refactoring_provider: RefactoringActionsProvider::with_cache(documents),
         }
     }
 
+    pub fn with_instruction_db(mut self, instruction_db: InstructionDatabase) -> Self {
+        self.quick_fix_provider = self
+            .quick_fix_provider
+            .with_instruction_db(instruction_db.clone());
+        self.refactoring_provider = self.refactoring_provider.with_instruction_db(instruction_db);
+        self
+    }
+
+    pub fn with_exported_labels(mut self, exported_labels: Vec<String>) -> Self {
+        self.refactoring_provider = self.refactoring_provider.with_exported_labels(exported_labels);
+        self
+    }
+
     pub fn get_code_actions(&self, content: &str, range: Range, uri: &Url, context: &CodeActionContext) -> Vec<CodeActionOrCommand> {
         let mut actions = Vec::new();
         
         // quick fixes for diagnostics
         for diagnostic in &context.diagnostics {
-            if let Some(action) = self.quick_fix_provider.create_quick_fix(diagnostic, content, uri) {
-                actions.push(action);
-            }
+            actions.extend(self.quick_fix_provider.create_quick_fixes(diagnostic, content, uri));
+            actions.extend(self.quick_fix_provider.suggest_suppression(diagnostic, content, uri));
         }
         
         // refactoring actions 
@@ -1,54 +1,591 @@
+use crate::analysis::core::{
+    suppression_pragmas, DocumentCache, LabelSymbol, ParsedDocument, SuppressionKind,
+};
+use crate::analysis::flow::{JumpAnalysis, JumpIssue};
 use crate::analysis::refactoring::SuggestionProvider;
+use crate::analysis::utils::{
+    char_len, expand_snippet, find_similar_names, get_word_at_position, indentation_width,
+    parse_number_literal, slice_columns, strip_comment, text_before_column, word_range,
+    DiagnosticCode, InstructionCategory, InstructionDatabase,
+};
 use std::collections::HashMap;
 use tower_lsp::lsp_types::*;
 
+/// saves AK, skips the division when the divisor is zero, leaving AK at 0;
+/// tabstops are the temporary cell, the divisor, the skip label and the opcode
+const DIVISION_GUARD_SNIPPET: &str =
+    "ŁAD ${1:div_temp}\nPOB ${2:divisor}\nSOZ ${3:div_skip}\nPOB $1\n${4:DZI} $2\n$3:";
+
+/// `MNO x` as AK added up x times; tabstops are the multiplicand, counter
+/// and product cells, the loop and done labels, and x
+const MULTIPLY_LOOP_SNIPPET: &str = "ŁAD ${1:mul_a}\nPOB ${6:x}\nŁAD ${2:mul_n}\nPOB #0\nŁAD ${3:mul_r}\n\
+    ${4:mul_loop}:\nPOB $2\nSOZ ${5:mul_done}\nODE #1\nŁAD $2\nPOB $3\nDOD $1\nŁAD $3\nSOB $4\n$5:\nPOB $3";
+
+/// `DZI x` / `MOD x` as x subtracted from AK while it stays non-negative;
+/// tabstops are the remainder and quotient cells, the loop and done labels,
+/// x, and the cell holding the result
+const DIVIDE_LOOP_SNIPPET: &str = "ŁAD ${1:div_r}\nPOB #0\nŁAD ${2:div_q}\n${3:div_loop}:\nPOB $1\n\
+    ODE ${5:x}\nSOM ${4:div_done}\nŁAD $1\nPOB $2\nDOD #1\nŁAD $2\nSOB $3\n$4:\nPOB ${6:div_q}";
+
 #[derive(Debug)]
 pub struct QuickFixProvider {
     suggestion_provider: SuggestionProvider,
+    instruction_db: InstructionDatabase,
+    documents: DocumentCache,
+}
+
+impl Default for QuickFixProvider {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl QuickFixProvider {
     pub fn new() -> Self {
+        Self::with_cache(DocumentCache::new())
+    }
+
+    pub fn with_cache(documents: DocumentCache) -> Self {
         Self {
             suggestion_provider: SuggestionProvider::new(),
+            instruction_db: InstructionDatabase::new(),
+            documents,
         }
     }
 
+    pub fn with_instruction_db(mut self, instruction_db: InstructionDatabase) -> Self {
+        self.documents.set_instruction_db(instruction_db.clone());
+        self.suggestion_provider = self
+            .suggestion_provider
+            .with_instruction_db(instruction_db.clone());
+        self.instruction_db = instruction_db;
+        self
+    }
+
+    /// preferred fix for diagnostic
     pub fn create_quick_fix(
         &self,
         diagnostic: &Diagnostic,
         content: &str,
         uri: &Url,
     ) -> Option<CodeActionOrCommand> {
-        if diagnostic.message.contains("Unknown instruction") {
-            return self.suggest_instruction_correction(diagnostic, content, uri);
+        self.create_quick_fixes(diagnostic, content, uri)
+            .into_iter()
+            .next()
+    }
+
+    /// every fix for diagnostic, preferred first
+    pub fn create_quick_fixes(
+        &self,
+        diagnostic: &Diagnostic,
+        content: &str,
+        uri: &Url,
+    ) -> Vec<CodeActionOrCommand> {
+        let Some(code) = DiagnosticCode::of(diagnostic) else {
+            return Vec::new();
+        };
+
+        match code {
+            DiagnosticCode::Sem001 | DiagnosticCode::Sem002 => self
+                .suggest_instruction_correction(diagnostic, content, uri)
+                .into_iter()
+                .collect(),
+            DiagnosticCode::Sem008 => self.suggest_undefined_label_fixes(diagnostic, content, uri),
+            DiagnosticCode::Sem010 | DiagnosticCode::Sem011 | DiagnosticCode::Sem012 => self
+                .suggest_operand_rewrite(code, diagnostic, content, uri)
+                .into_iter()
+                .collect(),
+            DiagnosticCode::Sem026 => self
+                .suggest_division_guard(diagnostic, content, uri)
+                .into_iter()
+                .collect(),
+            DiagnosticCode::Sem032 => self
+                .suggest_jump_removal(diagnostic, content, uri)
+                .into_iter()
+                .collect(),
+            DiagnosticCode::Sem033 => self
+                .suggest_jump_retarget(diagnostic, content, uri)
+                .into_iter()
+                .collect(),
+            DiagnosticCode::Sem037 => self
+                .suggest_label_removal(diagnostic, content, uri)
+                .into_iter()
+                .collect(),
+            DiagnosticCode::Sem039 => self
+                .suggest_base_isa_rewrite(diagnostic, content, uri)
+                .into_iter()
+                .collect(),
+            DiagnosticCode::Sem041 => self.suggest_canonical_spelling(diagnostic, content, uri),
+            _ => Vec::new(),
         }
+    }
 
-        if diagnostic.message.contains("undefined macro") {
-            return self.suggest_instruction_correction(diagnostic, content, uri);
+    /// silences the diagnostic with an `; asmod-disable-next-line` comment,
+    /// extending the one right above its line when there is one
+    pub fn suggest_suppression(
+        &self,
+        diagnostic: &Diagnostic,
+        content: &str,
+        uri: &Url,
+    ) -> Option<CodeActionOrCommand> {
+        let code = DiagnosticCode::of(diagnostic)?;
+        if code == DiagnosticCode::Sem038 {
+            return None;
         }
 
-        None
+        let document = self.documents.get(content);
+        let line_num = diagnostic.range.start.line as usize;
+        let line = document.line(line_num).unwrap_or_default();
+
+        let previous = line_num
+            .checked_sub(1)
+            .and_then(|previous| document.line(previous))
+            .map(|previous| suppression_pragmas([previous]))
+            .unwrap_or_default();
+        let edit = match previous.last() {
+            Some(pragma) if pragma.kind == SuppressionKind::NextLine => {
+                // already suppressed, the diagnostic is stale
+                let covered = previous.iter().any(|pragma| match &pragma.code {
+                    Some(named) => named.eq_ignore_ascii_case(code.as_str()),
+                    None => true,
+                });
+                if covered {
+                    return None;
+                }
+                TextEdit {
+                    range: word_range(line_num as u32 - 1, pragma.end, pragma.end),
+                    new_text: format!(" {}", code),
+                }
+            }
+            _ => {
+                let indent = slice_columns(line, 0, indentation_width(line));
+                TextEdit {
+                    range: word_range(line_num as u32, 0, 0),
+                    new_text: format!(
+                        "{}; {} {}\n",
+                        indent,
+                        SuppressionKind::NextLine.pragma(),
+                        code
+                    ),
+                }
+            }
+        };
+
+        Some(quick_fix_action(
+            format!("Suppress {} for this line", code),
+            diagnostic,
+            uri,
+            vec![edit],
+            false,
+        ))
     }
 
-    fn suggest_instruction_correction(
+    /// deletes a jump to the next instruction, with its line when nothing
+    /// but the jump is on it
+    fn suggest_jump_removal(
+        &self,
+        diagnostic: &Diagnostic,
+        content: &str,
+        uri: &Url,
+    ) -> Option<CodeActionOrCommand> {
+        let document = self.documents.get(content);
+        let line_num = diagnostic.range.start.line as usize;
+        let line = document.line(line_num)?;
+        let start = diagnostic.range.start.character as usize;
+        let before = text_before_column(line, start);
+
+        let range = if !before.trim().is_empty() {
+            // keep the label the jump shares its line with
+            word_range(
+                line_num as u32,
+                char_len(before.trim_end()),
+                char_len(strip_comment(line).trim_end()),
+            )
+        } else if line_num + 1 < document.line_count() {
+            Range {
+                start: Position {
+                    line: line_num as u32,
+                    character: 0,
+                },
+                end: Position {
+                    line: line_num as u32 + 1,
+                    character: 0,
+                },
+            }
+        } else if line_num > 0 {
+            let previous_len = document.line(line_num - 1).map(char_len).unwrap_or(0);
+            Range {
+                start: Position {
+                    line: line_num as u32 - 1,
+                    character: previous_len as u32,
+                },
+                end: Position {
+                    line: line_num as u32,
+                    character: char_len(line) as u32,
+                },
+            }
+        } else {
+            word_range(0, 0, char_len(line))
+        };
+
+        let edit = TextEdit {
+            range,
+            new_text: String::new(),
+        };
+        Some(quick_fix_action(
+            "Remove redundant jump".to_string(),
+            diagnostic,
+            uri,
+            vec![edit],
+            true,
+        ))
+    }
+
+    fn suggest_label_removal(
+        &self,
+        diagnostic: &Diagnostic,
+        content: &str,
+        uri: &Url,
+    ) -> Option<CodeActionOrCommand> {
+        let document = self.documents.get(content);
+        let label = document.symbols().labels().iter().find(|label| {
+            label.line == diagnostic.range.start.line as usize
+                && label.start == diagnostic.range.start.character as usize
+        })?;
+
+        Some(quick_fix_action(
+            "Remove unused label".to_string(),
+            diagnostic,
+            uri,
+            vec![label_removal_edit(&document, label)?],
+            true,
+        ))
+    }
+
+    /// rewrites the mnemonic, and every other one in the file that is not in
+    /// its canonical spelling
+    fn suggest_canonical_spelling(
+        &self,
+        diagnostic: &Diagnostic,
+        content: &str,
+        uri: &Url,
+    ) -> Vec<CodeActionOrCommand> {
+        let document = self.documents.get(content);
+        let edits: Vec<(Range, &str)> = document
+            .mnemonics()
+            .into_iter()
+            .filter_map(|(spelling, line, start, end)| {
+                let canonical = self.instruction_db.canonical_name(spelling)?;
+                (canonical != spelling).then(|| (word_range(line as u32, start, end), canonical))
+            })
+            .collect();
+
+        let mut actions = Vec::new();
+        if let Some((range, canonical)) = edits
+            .iter()
+            .find(|(range, _)| range.start == diagnostic.range.start)
+        {
+            actions.push(quick_fix_action(
+                format!("Replace with '{}'", canonical),
+                diagnostic,
+                uri,
+                vec![TextEdit {
+                    range: *range,
+                    new_text: canonical.to_string(),
+                }],
+                true,
+            ));
+        }
+        if edits.len() > 1 {
+            actions.push(quick_fix_action(
+                "Normalize all mnemonics in file".to_string(),
+                diagnostic,
+                uri,
+                edits
+                    .into_iter()
+                    .map(|(range, canonical)| TextEdit {
+                        range,
+                        new_text: canonical.to_string(),
+                    })
+                    .collect(),
+                false,
+            ));
+        }
+        actions
+    }
+
+    /// points a jump straight at the end of the `SOB` chain it starts
+    fn suggest_jump_retarget(
+        &self,
+        diagnostic: &Diagnostic,
+        content: &str,
+        uri: &Url,
+    ) -> Option<CodeActionOrCommand> {
+        let document = self.documents.get(content);
+        let cfg = document.cfg();
+        let line = diagnostic.range.start.line as usize + 1;
+
+        let target = JumpAnalysis::analyze(cfg)
+            .issues()
+            .iter()
+            .find_map(|issue| match issue {
+                JumpIssue::JumpChain {
+                    statement, target, ..
+                } if cfg.statements()[*statement].line() == line => Some(target.clone()),
+                _ => None,
+            })?;
+
+        let edit = TextEdit {
+            range: diagnostic.range,
+            new_text: target.clone(),
+        };
+        Some(quick_fix_action(
+            format!("Jump directly to '{}'", target),
+            diagnostic,
+            uri,
+            vec![edit],
+            true,
+        ))
+    }
+
+    /// wraps `DZI x` / `MOD x` in a zero check on x, declaring a cell that
+    /// keeps AK meanwhile
+    fn suggest_division_guard(
+        &self,
+        diagnostic: &Diagnostic,
+        content: &str,
+        uri: &Url,
+    ) -> Option<CodeActionOrCommand> {
+        let document = self.documents.get(content);
+        let line_num = diagnostic.range.start.line as usize;
+        let line = document.line(line_num)?;
+        let statement = document.cfg().statements().iter().find(|statement| {
+            statement.line() == line_num + 1
+                && (statement.is_opcode("DZI") || statement.is_opcode("MOD"))
+        })?;
+
+        let divisor = slice_columns(
+            line,
+            diagnostic.range.start.character as usize,
+            diagnostic.range.end.character as usize,
+        );
+        let temp = unused_label(&document, "div_temp");
+        let skip = unused_label(&document, "div_skip");
+        let opcode = statement.name().to_uppercase();
+
+        let guard = expand_snippet(DIVISION_GUARD_SNIPPET, &[&temp, &divisor, &skip, &opcode]);
+        let start = statement.column().saturating_sub(1);
+        let end = char_len(strip_comment(line).trim_end());
+
+        Some(quick_fix_action(
+            format!("Skip {} when '{}' is zero", opcode, divisor),
+            diagnostic,
+            uri,
+            vec![
+                TextEdit {
+                    range: word_range(line_num as u32, start, end),
+                    new_text: indent_snippet(&guard, line),
+                },
+                cell_declarations(&document, &[&temp]),
+            ],
+            true,
+        ))
+    }
+
+    /// replaces `MNO x`, `DZI x` or `MOD x` with a loop of base instructions
+    /// over scratch cells declared at the end; the loop expects AK and x not
+    /// to be negative
+    fn suggest_base_isa_rewrite(
         &self,
         diagnostic: &Diagnostic,
         content: &str,
         uri: &Url,
     ) -> Option<CodeActionOrCommand> {
-        let message = &diagnostic.message;
-        let start_quote = message.find('\'')?;
-        let end_quote = message.rfind('\'')?;
-        if start_quote >= end_quote {
+        let document = self.documents.get(content);
+        let line_num = diagnostic.range.start.line as usize;
+        let line = document.line(line_num)?;
+        let statement = document
+            .cfg()
+            .statements()
+            .iter()
+            .find(|statement| statement.line() == line_num + 1)?;
+        let (_, operand_start, operand_end) =
+            document.operand_span(statement.line(), statement.column())?;
+        let operand = slice_columns(line, operand_start, operand_end);
+        let opcode = statement.name().to_uppercase();
+
+        let (rewrite, cells) = if statement.is_opcode("MNO") {
+            let cells = ["mul_a", "mul_n", "mul_r"].map(|base| unused_label(&document, base));
+            let looped = unused_label(&document, "mul_loop");
+            let done = unused_label(&document, "mul_done");
+            let values = [&cells[0], &cells[1], &cells[2], &looped, &done, &operand];
+            (expand_snippet(MULTIPLY_LOOP_SNIPPET, &values.map(String::as_str)), cells.to_vec())
+        } else if statement.is_opcode("DZI") || statement.is_opcode("MOD") {
+            let cells = ["div_r", "div_q"].map(|base| unused_label(&document, base));
+            let looped = unused_label(&document, "div_loop");
+            let done = unused_label(&document, "div_done");
+            // the quotient for DZI, what is left of AK for MOD
+            let result = if statement.is_opcode("DZI") { &cells[1] } else { &cells[0] };
+            let values = [&cells[0], &cells[1], &looped, &done, &operand, result];
+            (expand_snippet(DIVIDE_LOOP_SNIPPET, &values.map(String::as_str)), cells.to_vec())
+        } else {
             return None;
+        };
+
+        let start = statement.column() - 1;
+        let end = char_len(strip_comment(line).trim_end());
+        let cells: Vec<&str> = cells.iter().map(String::as_str).collect();
+
+        Some(quick_fix_action(
+            format!("Rewrite {} with base instructions", opcode),
+            diagnostic,
+            uri,
+            vec![
+                TextEdit {
+                    range: word_range(line_num as u32, start, end),
+                    new_text: indent_snippet(&rewrite, line),
+                },
+                cell_declarations(&document, &cells),
+            ],
+            true,
+        ))
+    }
+
+    /// rewrites operand when the intended addressing mode is obvious
+    fn suggest_operand_rewrite(
+        &self,
+        code: DiagnosticCode,
+        diagnostic: &Diagnostic,
+        content: &str,
+        uri: &Url,
+    ) -> Option<CodeActionOrCommand> {
+        let document = self.documents.get(content);
+        let range = diagnostic.range;
+        let operand = slice_columns(
+            document.line(range.start.line as usize)?,
+            range.start.character as usize,
+            range.end.character as usize,
+        );
+
+        let (title, new_text) = if code == DiagnosticCode::Sem010 {
+            // `SOB #loop` meant the label itself
+            let label = operand.strip_prefix('#')?;
+            if !document.symbols().is_defined(label) {
+                return None;
+            }
+            (format!("Jump to label '{}'", label), label.to_string())
+        } else if code == DiagnosticCode::Sem011 {
+            // `MSK 5` meant the value 5
+            parse_number_literal(&operand)?;
+            (format!("Use immediate '#{}'", operand), format!("#{}", operand))
+        } else {
+            // `ŁAD #100` meant address 100
+            let address = operand.strip_prefix('#')?;
+            (format!("Store to address '{}'", address), address.to_string())
+        };
+
+        let edit = TextEdit { range, new_text };
+        Some(quick_fix_action(title, diagnostic, uri, vec![edit], true))
+    }
+
+    fn suggest_undefined_label_fixes(
+        &self,
+        diagnostic: &Diagnostic,
+        content: &str,
+        uri: &Url,
+    ) -> Vec<CodeActionOrCommand> {
+        let label = diagnostic
+            .data
+            .as_ref()
+            .and_then(|data| data.get("label"))
+            .and_then(|label| label.as_str());
+        let Some(label) = label else {
+            return Vec::new();
+        };
+        let document = self.documents.get(content);
+        let mut fixes = Vec::new();
+
+        // rename reference to closest existing label
+        let labels = document.symbols().labels().iter().map(|l| l.name.as_str());
+        if let Some(closest) = find_similar_names(label, labels).first() {
+            let edit = TextEdit {
+                range: diagnostic.range,
+                new_text: closest.to_string(),
+            };
+            fixes.push(quick_fix_action(
+                format!("Change to '{}'", closest),
+                diagnostic,
+                uri,
+                vec![edit],
+                true,
+            ));
         }
 
-        let unknown_instruction = &message[start_quote + 1..end_quote];
+        // jump targets get a bare label, memory operands a data cell
+        let opcode = document
+            .symbols()
+            .references()
+            .iter()
+            .find(|reference| {
+                reference.line == diagnostic.range.start.line as usize
+                    && reference.start == diagnostic.range.start.character as usize
+            })
+            .map(|reference| reference.opcode.as_str());
+        let is_jump = opcode
+            .and_then(|opcode| self.instruction_db.get_instruction(opcode))
+            .map(|info| info.category == InstructionCategory::ControlFlow)
+            .unwrap_or(false);
+        let definition = if is_jump {
+            format!("{}:", label)
+        } else {
+            format!("{}: RST 0", label)
+        };
+
+        // append after the last line, reusing a trailing empty line
+        let last_line = document.line_count() - 1;
+        let last_text = document.line(last_line).unwrap_or("");
+        let (character, new_text) = if last_text.trim().is_empty() && last_line > 0 {
+            (0, format!("{}\n", definition))
+        } else {
+            (char_len(last_text), format!("\n{}", definition))
+        };
+        let position = Position {
+            line: last_line as u32,
+            character: character as u32,
+        };
+        let edit = TextEdit {
+            range: Range {
+                start: position,
+                end: position,
+            },
+            new_text,
+        };
+        fixes.push(quick_fix_action(
+            format!("Create label '{}' at end of file", label),
+            diagnostic,
+            uri,
+            vec![edit],
+            fixes.is_empty(),
+        ));
+
+        fixes
+    }
+
+    fn suggest_instruction_correction(
+        &self,
+        diagnostic: &Diagnostic,
+        content: &str,
+        uri: &Url,
+    ) -> Option<CodeActionOrCommand> {
+        // the mnemonic as written where the diagnostic starts
+        let document = self.documents.get(content);
+        let line = document.line(diagnostic.range.start.line as usize)?;
+        let (unknown_instruction, _, _) =
+            get_word_at_position(line, diagnostic.range.start.character as usize)?;
 
         let mut suggestions = self
             .suggestion_provider
-            .suggest_common_fixes(unknown_instruction);
+            .suggest_common_fixes(&unknown_instruction);
 
         if suggestions.is_empty() {
             if let Some(context) = self.extract_context_from_content(content, diagnostic) {
@@ -185,7 +722,7 @@ impl QuickFixProvider {
 
     fn get_surrounding_context<'a>(
         &self,
-        lines: &'a Vec<&'a str>,
+        lines: &[&'a str],
         line_index: usize,
     ) -> Vec<&'a str> {
         let mut context = Vec::new();
@@ -201,3 +738,117 @@ impl QuickFixProvider {
         context
     }
 }
+
+/// deletes a label definition, with its line when nothing else is on it;
+/// code after the label stays on its line, indented
+pub(crate) fn label_removal_edit(document: &ParsedDocument, label: &LabelSymbol) -> Option<TextEdit> {
+    let line = document.line(label.line)?;
+    let code = strip_comment(line);
+    let after_colon = label.end + 1;
+    let rest = slice_columns(code, after_colon, char_len(code));
+    let line_num = label.line as u32;
+
+    if rest.trim().is_empty() && code.len() == line.len() {
+        let range = if label.line + 1 < document.line_count() {
+            Range {
+                start: Position {
+                    line: line_num,
+                    character: 0,
+                },
+                end: Position {
+                    line: line_num + 1,
+                    character: 0,
+                },
+            }
+        } else {
+            word_range(line_num, 0, char_len(line))
+        };
+        return Some(TextEdit {
+            range,
+            new_text: String::new(),
+        });
+    }
+
+    let new_text = if rest.trim().is_empty() || label.start > 0 {
+        String::new()
+    } else {
+        "    ".to_string()
+    };
+    Some(TextEdit {
+        range: word_range(line_num, label.start, after_colon + indentation_width(&rest)),
+        new_text,
+    })
+}
+
+/// base, or base with the first free numeric suffix
+fn unused_label(document: &ParsedDocument, base: &str) -> String {
+    let symbols = document.symbols();
+    if !symbols.is_defined(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{}_{}", base, n))
+        .find(|name| !symbols.is_defined(name))
+        .unwrap()
+}
+
+/// snippet lines after the first lined up with the instruction on line,
+/// labels at the start of their line
+fn indent_snippet(snippet: &str, line: &str) -> String {
+    let indent = slice_columns(line, 0, indentation_width(line));
+    let indent = if indent.is_empty() { "    ".to_string() } else { indent };
+
+    let mut lines = snippet.lines();
+    let mut text = lines.next().unwrap_or_default().to_string();
+    for snippet_line in lines {
+        if snippet_line.ends_with(':') {
+            text.push_str(&format!("\n{}", snippet_line));
+        } else {
+            text.push_str(&format!("\n{}{}", indent, snippet_line));
+        }
+    }
+    text
+}
+
+/// `RPA` cells for names, after everything else in the document
+fn cell_declarations(document: &ParsedDocument, names: &[&str]) -> TextEdit {
+    let last_line = document.line_count().saturating_sub(1);
+    let last_len = document.line(last_line).map(char_len).unwrap_or(0);
+    let declarations: Vec<String> = names.iter().map(|name| format!("{}: RPA", name)).collect();
+    let declarations = declarations.join("\n");
+
+    TextEdit {
+        range: word_range(last_line as u32, last_len, last_len),
+        new_text: if last_len == 0 {
+            declarations
+        } else {
+            format!("\n{}", declarations)
+        },
+    }
+}
+
+fn quick_fix_action(
+    title: String,
+    diagnostic: &Diagnostic,
+    uri: &Url,
+    edits: Vec<TextEdit>,
+    is_preferred: bool,
+) -> CodeActionOrCommand {
+    let mut changes = HashMap::new();
+    changes.insert(uri.clone(), edits);
+
+    CodeActionOrCommand::CodeAction(CodeAction {
+        title,
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(WorkspaceEdit {
+            changes: Some(changes),
+            document_changes: None,
+            change_annotations: None,
+        }),
+        command: None,
+        is_preferred: Some(is_preferred),
+        disabled: None,
+        data: None,
+    })
+}
@@ -1,19 +1,47 @@
-use crate::analysis::utils::{position_to_range, InstructionDatabase};
+use crate::analysis::core::DocumentCache;
+use crate::analysis::refactoring::quick_fixes::label_removal_edit;
+use crate::analysis::utils::{char_len, position_to_range, slice_columns, InstructionDatabase};
 use std::collections::HashMap;
 use tower_lsp::lsp_types::*;
 
 #[derive(Debug)]
 pub struct RefactoringActionsProvider {
     instruction_db: InstructionDatabase,
+    documents: DocumentCache,
+    exported_labels: Vec<String>,
+}
+
+impl Default for RefactoringActionsProvider {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl RefactoringActionsProvider {
     pub fn new() -> Self {
+        Self::with_cache(DocumentCache::new())
+    }
+
+    pub fn with_cache(documents: DocumentCache) -> Self {
         Self {
             instruction_db: InstructionDatabase::new(),
+            documents,
+            exported_labels: Vec::new(),
         }
     }
 
+    pub fn with_instruction_db(mut self, instruction_db: InstructionDatabase) -> Self {
+        self.documents.set_instruction_db(instruction_db.clone());
+        self.instruction_db = instruction_db;
+        self
+    }
+
+    /// labels used from outside the document, kept by "Remove all unused labels"
+    pub fn with_exported_labels(mut self, exported_labels: Vec<String>) -> Self {
+        self.exported_labels = exported_labels;
+        self
+    }
+
     pub fn get_refactoring_actions(
         &self,
         content: &str,
@@ -42,9 +70,49 @@ impl RefactoringActionsProvider {
             actions.push(action);
         }
 
+        // "Remove all unused labels"
+        if let Some(action) = self.create_remove_unused_labels_action(content, uri) {
+            actions.push(action);
+        }
+
         actions
     }
 
+    fn create_remove_unused_labels_action(
+        &self,
+        content: &str,
+        uri: &Url,
+    ) -> Option<CodeActionOrCommand> {
+        let document = self.documents.get(content);
+        let edits: Vec<TextEdit> = document
+            .unused_labels(&self.exported_labels)
+            .into_iter()
+            .filter_map(|label| label_removal_edit(&document, label))
+            .collect();
+
+        if edits.is_empty() {
+            return None;
+        }
+
+        let mut changes = HashMap::new();
+        changes.insert(uri.clone(), edits);
+
+        Some(CodeActionOrCommand::CodeAction(CodeAction {
+            title: "Remove all unused labels".to_string(),
+            kind: Some(CodeActionKind::new("source.removeUnusedLabels")),
+            diagnostics: None,
+            edit: Some(WorkspaceEdit {
+                changes: Some(changes),
+                document_changes: None,
+                change_annotations: None,
+            }),
+            command: None,
+            is_preferred: Some(false),
+            disabled: None,
+            data: None,
+        }))
+    }
+
     fn create_uppercase_action(
         &self,
         content: &str,
@@ -62,11 +130,12 @@ impl RefactoringActionsProvider {
         let start_char = range.start.character as usize;
         let end_char = range.end.character as usize;
 
-        if start_char >= line.len() || end_char > line.len() {
+        let line_len = char_len(line);
+        if start_char >= line_len || end_char > line_len {
             return None;
         }
 
-        let selected_text = &line[start_char..end_char];
+        let selected_text = slice_columns(line, start_char, end_char);
 
         if self
             .instruction_db
@@ -118,7 +187,11 @@ impl RefactoringActionsProvider {
         // add comment if line does not have it
         if !line.contains(';') && !line.trim().is_empty() {
             let edit = TextEdit {
-                range: position_to_range(range.start.line, line.len() as u32, line.len() as u32),
+                range: position_to_range(
+                    range.start.line,
+                    char_len(line) as u32,
+                    char_len(line) as u32,
+                ),
                 new_text: "    ; TODO: Add comment".to_string(),
             };
 
@@ -165,7 +238,7 @@ impl RefactoringActionsProvider {
             let formatted_line = format!("    {}", trimmed); // 4 spaces indentation
 
             let edit = TextEdit {
-                range: position_to_range(range.start.line, 0, line.len() as u32),
+                range: position_to_range(range.start.line, 0, char_len(line) as u32),
                 new_text: formatted_line,
             };
 
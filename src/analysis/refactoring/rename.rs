@@ -1,7 +1,9 @@
+use crate::analysis::core::DocumentCache;
 use crate::analysis::language::NavigationProvider;
+use crate::LspError;
 use crate::analysis::utils::{
-    get_line_at_position, get_word_at_position, is_label_definition_location, is_valid_position,
-    is_valid_symbol_name, word_range, InstructionDatabase,
+    get_line_at_position, get_word_at_position, is_valid_position, is_valid_symbol_name,
+    word_range, InstructionDatabase,
 };
 use std::collections::HashMap;
 use tower_lsp::lsp_types::*;
@@ -10,16 +12,34 @@ use tower_lsp::lsp_types::*;
 pub struct RenameProvider {
     instruction_db: InstructionDatabase,
     navigation_provider: NavigationProvider,
+    documents: DocumentCache,
+}
+
+impl Default for RenameProvider {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl RenameProvider {
     pub fn new() -> Self {
+        Self::with_cache(DocumentCache::new())
+    }
+
+    pub fn with_cache(documents: DocumentCache) -> Self {
         Self {
             instruction_db: InstructionDatabase::new(),
-            navigation_provider: NavigationProvider::new(),
+            navigation_provider: NavigationProvider::with_cache(documents.clone()),
+            documents,
         }
     }
 
+    pub fn with_instruction_db(mut self, instruction_db: InstructionDatabase) -> Self {
+        self.documents.set_instruction_db(instruction_db.clone());
+        self.instruction_db = instruction_db;
+        self
+    }
+
     /// store symbol in code
     pub fn rename_symbol(
         &self,
@@ -38,7 +58,7 @@ impl RenameProvider {
         let word_info = get_word_at_position(current_line, cursor_pos)?;
         let (old_name, _, _) = word_info;
 
-        if let Err(_) = self.validate_new_name(new_name) {
+        if self.validate_new_name(new_name).is_err() {
             return None;
         }
 
@@ -46,6 +66,10 @@ impl RenameProvider {
             return None;
         }
 
+        if self.check_unambiguous(content, position).is_err() {
+            return None;
+        }
+
         let conflicts = self.check_name_conflicts(content, new_name, Some(position));
         if !conflicts.is_empty() {
             return None;
@@ -60,10 +84,15 @@ impl RenameProvider {
         }
 
         // edit text for all references
+        let document = self.documents.get(content);
         let mut edits = Vec::new();
         for reference in references {
             // whether is label definition (need : after)
-            let edit_text = if is_label_definition_location(content, &reference) {
+            let start = reference.range.start;
+            let edit_text = if document
+                .symbols()
+                .is_definition_at(start.line as usize, start.character as usize)
+            {
                 format!("{}:", new_name)
             } else {
                 new_name.to_string()
@@ -100,6 +129,10 @@ impl RenameProvider {
             return None;
         }
 
+        if self.check_unambiguous(content, position).is_err() {
+            return None;
+        }
+
         if is_valid_symbol_name(&word) {
             Some(word_range(position.line, start_pos, end_pos))
         } else {
@@ -107,6 +140,11 @@ impl RenameProvider {
         }
     }
 
+    /// renaming one of several definitions would leave the others dangling
+    pub fn check_unambiguous(&self, content: &str, position: Position) -> Result<(), LspError> {
+        self.navigation_provider.check_unambiguous(content, position)
+    }
+
     pub fn validate_new_name(&self, new_name: &str) -> Result<(), String> {
         if !is_valid_symbol_name(new_name) {
             return Err("Invalid symbol name. Must start with letter or underscore and contain only alphanumeric characters and underscores.".to_string());
@@ -146,35 +184,17 @@ impl RenameProvider {
         new_name: &str,
         exclude_position: Option<Position>,
     ) -> Vec<Position> {
-        let mut conflicts = Vec::new();
-        let lines: Vec<&str> = content.lines().collect();
-
-        for (line_num, line) in lines.iter().enumerate() {
-            let trimmed = line.trim();
-
-            // label definitions
-            if let Some(colon_pos) = trimmed.find(':') {
-                let label_name = &trimmed[..colon_pos];
-                if label_name == new_name {
-                    let position = Position {
-                        line: line_num as u32,
-                        character: (line.len() - trimmed.len()) as u32,
-                    };
-
-                    // skip position that is being changed
-                    if let Some(exclude_pos) = exclude_position {
-                        if position.line != exclude_pos.line
-                            || position.character != exclude_pos.character
-                        {
-                            conflicts.push(position);
-                        }
-                    } else {
-                        conflicts.push(position);
-                    }
-                }
-            }
-        }
-
-        conflicts
+        let document = self.documents.get(content);
+
+        document
+            .symbols()
+            .definitions(new_name)
+            .map(|label| Position {
+                line: label.line as u32,
+                character: label.start as u32,
+            })
+            // skip position that is being changed
+            .filter(|position| exclude_position != Some(*position))
+            .collect()
     }
 }
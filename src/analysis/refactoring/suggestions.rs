@@ -5,6 +5,12 @@ pub struct SuggestionProvider {
     instruction_db: InstructionDatabase,
 }
 
+impl Default for SuggestionProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SuggestionProvider {
     pub fn new() -> Self {
         Self {
@@ -12,6 +18,11 @@ impl SuggestionProvider {
         }
     }
 
+    pub fn with_instruction_db(mut self, instruction_db: InstructionDatabase) -> Self {
+        self.instruction_db = instruction_db;
+        self
+    }
+
     pub fn find_similar_instructions(&self, unknown: &str) -> Vec<String> {
         self.instruction_db.find_similar_instructions(unknown)
     }
@@ -34,32 +45,13 @@ impl SuggestionProvider {
         let mut suggestions = Vec::new();
         let unknown_lower = unknown.to_lowercase();
 
-        let common_typos = [
-            ("pob", "POB"),
-            ("dod", "DOD"),
-            ("ode", "ODE"),
-            ("sob", "SOB"),
-            ("som", "SOM"),
-            ("soz", "SOZ"),
-            ("stp", "STP"),
-            ("lad", "ŁAD"),
-            ("ład", "ŁAD"),
-            ("wejscie", "WEJSCIE"),
-            ("wyjscie", "WYJSCIE"),
-            ("mno", "MNO"),
-            ("dzi", "DZI"),
-            ("mod", "MOD"),
-            ("sdp", "SDP"),
-            ("pzs", "PZS"),
-            ("dns", "DNS"),
-            ("czm", "CZM"),
-            ("msk", "MSK"),
-            ("pwr", "PWR"),
-        ];
-
-        for (typo, correct) in &common_typos {
-            if unknown_lower == *typo {
-                suggestions.push(correct.to_string());
+        // wrong case or an alias of a mnemonic
+        for instruction in self.instruction_db.instructions() {
+            if instruction
+                .spellings()
+                .any(|spelling| spelling.to_lowercase() == unknown_lower)
+            {
+                suggestions.push(instruction.name.clone());
             }
         }
 
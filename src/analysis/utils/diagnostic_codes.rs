@@ -0,0 +1,216 @@
+use std::fmt;
+use tower_lsp::lsp_types::{
+    CodeDescription, Diagnostic, DiagnosticSeverity, DiagnosticTag, NumberOrString, Url,
+};
+
+/// long explanations of every code, one `### CODE` section each
+pub const DIAGNOSTICS_DOC_URL: &str =
+    "https://github.com/szymonwilczek/asmodeus-lsp/blob/main/docs/diagnostics.md";
+
+macro_rules! diagnostic_codes {
+    ($($variant:ident => $code:literal, $severity:ident, $title:literal, $explanation:literal;)*) => {
+        /// every diagnostic code the server reports
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum DiagnosticCode {
+            $($variant,)*
+        }
+
+        impl DiagnosticCode {
+            pub const ALL: &'static [DiagnosticCode] = &[$(DiagnosticCode::$variant,)*];
+
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $code,)*
+                }
+            }
+
+            pub fn default_severity(self) -> DiagnosticSeverity {
+                match self {
+                    $(Self::$variant => DiagnosticSeverity::$severity,)*
+                }
+            }
+
+            /// short name of the problem
+            pub fn title(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $title,)*
+                }
+            }
+
+            /// what the code means and how to resolve it
+            pub fn explanation(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $explanation,)*
+                }
+            }
+        }
+    };
+}
+
+diagnostic_codes! {
+    Lex001 => "LEX001", ERROR, "Lexer error",
+        "The line contains text the lexer cannot turn into tokens, such as a stray symbol. The line is skipped; the rest of the document is still analyzed.";
+    Par001 => "PAR001", ERROR, "Parser error",
+        "The tokens on the line do not form a valid statement, for example an instruction followed by several operands. The statement is skipped; the rest of the document is still analyzed.";
+    Sem001 => "SEM001", ERROR, "Unknown instruction",
+        "The opcode is not a Machine W instruction. Check the spelling; the quick fix offers the closest known instruction.";
+    Sem002 => "SEM002", ERROR, "Unknown instruction or undefined macro",
+        "An upper-case name used as a statement is neither an instruction nor a macro defined in the file.";
+    Sem003 => "SEM003", ERROR, "Unexpected operand",
+        "The instruction takes no operand, for example `STP` or `WYJSCIE`. Remove the operand.";
+    Sem004 => "SEM004", ERROR, "Missing operand",
+        "The instruction needs an operand, an address, a label or an immediate value.";
+    Sem005 => "SEM005", ERROR, "Invalid character",
+        "The code part of the line contains a character that is not allowed outside comments.";
+    Sem006 => "SEM006", ERROR, "Invalid label name",
+        "Labels must start with a letter or underscore and contain only letters, digits and underscores.";
+    Sem007 => "SEM007", ERROR, "Label conflicts with instruction",
+        "A label named like an instruction makes operands ambiguous. Rename the label.";
+    Sem008 => "SEM008", ERROR, "Undefined label",
+        "The operand names a label that is not defined in the file. The quick fixes rename it to a similar label or create it.";
+    Sem009 => "SEM009", ERROR, "Duplicate label",
+        "The same label is defined more than once, so references to it are ambiguous.";
+    Sem010 => "SEM010", ERROR, "Label operand expected",
+        "Jumps take a label, not an immediate value: write `SOB loop`, not `SOB #loop`.";
+    Sem011 => "SEM011", ERROR, "Immediate operand expected",
+        "The instruction takes an immediate value, written with `#`, for example `MSK #5`.";
+    Sem012 => "SEM012", ERROR, "Store to an immediate value",
+        "`ŁAD` stores AK to memory, so its operand must be an address or label, not `#value`.";
+    Sem013 => "SEM013", ERROR, "Value does not fit in a word",
+        "The literal is wider than a memory word of the configured memory model.";
+    Sem014 => "SEM014", ERROR, "Address past the end of memory",
+        "The address is larger than the configured address space allows.";
+    Sem015 => "SEM015", ERROR, "Malformed number literal",
+        "A decimal, `0x` or `0b` literal contains digits that do not belong to its radix.";
+    Sem016 => "SEM016", WARNING, "Unreachable code",
+        "No path from the program entry or an interrupt handler reaches this code.";
+    Sem017 => "SEM017", WARNING, "Program runs past its last instruction",
+        "Some path reaches the end of the program without `STP`, so the machine runs into whatever follows in memory.";
+    Sem018 => "SEM018", WARNING, "Pop from an empty stack",
+        "`PZS` runs when nothing has been pushed with `SDP` on some or all paths.";
+    Sem019 => "SEM019", WARNING, "Stack depth mismatch",
+        "Paths joining here have pushed a different number of items, so later pops depend on the path taken.";
+    Sem020 => "SEM020", WARNING, "Items left on the stack",
+        "The program stops with items still pushed, which usually means a missing `PZS`.";
+    Sem021 => "SEM021", WARNING, "Read before write",
+        "The cell is read on some path before anything is stored to it; reserved (`RPA`) cells and memory past the program hold no defined value.";
+    Sem022 => "SEM022", WARNING, "Write never read",
+        "The value stored here is never read by any instruction.";
+    Sem023 => "SEM023", WARNING, "Branch always taken",
+        "AK is known at this point and the branch always jumps, so the condition is redundant.";
+    Sem024 => "SEM024", WARNING, "Branch never taken",
+        "AK is known at this point and the branch never jumps, so its target is only reachable some other way.";
+    Sem025 => "SEM025", ERROR, "Division by zero",
+        "`DZI` or `MOD` divides by the literal zero.";
+    Sem026 => "SEM026", WARNING, "Possible division by zero",
+        "The divisor cell is never written or only ever holds zero. The quick fix guards the division with a zero check.";
+    Sem027 => "SEM027", WARNING, "PWR outside an interrupt handler",
+        "`PWR` returns from an interrupt, but the main program reaches it.";
+    Sem028 => "SEM028", WARNING, "Interrupt handler without PWR",
+        "A path out of the handler does not end in `PWR`, so it falls into other code or data.";
+    Sem029 => "SEM029", ERROR, "Interrupt mask too wide",
+        "The `MSK` mask sets bits past the configured number of interrupt lines.";
+    Sem030 => "SEM030", WARNING, "Unknown interrupt handler",
+        "An `; asmod-handler` pragma names a label that is not defined in the file.";
+    Sem031 => "SEM031", WARNING, "Infinite loop",
+        "Nothing in the loop can leave it: no branch, stop, input or macro call.";
+    Sem032 => "SEM032", HINT, "Redundant jump",
+        "The jump goes to the instruction right after it and can be removed.";
+    Sem033 => "SEM033", HINT, "Jump to a jump",
+        "The target only jumps on; jumping to the final target directly saves a cycle.";
+    Sem034 => "SEM034", WARNING, "Store into code",
+        "`ŁAD` overwrites an instruction of the program. Self-modifying code is rarely intended.";
+    Sem035 => "SEM035", ERROR, "Jump into data",
+        "The jump lands on a data cell, which would be executed as an instruction.";
+    Sem036 => "SEM036", WARNING, "Execution runs into data",
+        "Execution continues from the last instruction into a data cell. Add `STP` or a jump before the data.";
+    Sem037 => "SEM037", HINT, "Unused label",
+        "Nothing jumps to or reads the label. Entry labels, interrupt handlers and exported labels are exempt.";
+    Sem038 => "SEM038", WARNING, "Unused suppression",
+        "An `; asmod-disable...` comment silences no diagnostic, because the problem was fixed or the code is misspelled. Remove the code from the comment.";
+    Sem039 => "SEM039", ERROR, "Extended instruction not allowed",
+        "`MNO`, `DZI` and `MOD` belong to the extended instruction set, but the project targets the base ISA (`isa = \"base\"`). The quick fix rewrites the instruction as a `DOD` / `ODE` loop.";
+    Sem040 => "SEM040", INFORMATION, "Label naming",
+        "The label does not follow the naming policy set in the project configuration, for example `snake_case`.";
+    Sem041 => "SEM041", INFORMATION, "Non-canonical mnemonic",
+        "The instruction is written in lowercase or with an alias such as `LAD` or `WEJŚCIE`. Reported only with `normalize = true` under `[mnemonics]`; the quick fix rewrites one or every mnemonic in the file to its canonical spelling.";
+}
+
+/// groups of rules a project can switch off together
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RuleGroup {
+    /// redundant code and naming
+    Style,
+    /// stack, memory and value tracking
+    Dataflow,
+    /// interrupt handlers and masks
+    Interrupts,
+}
+
+impl DiagnosticCode {
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|known| known.as_str() == code)
+    }
+
+    /// code of a diagnostic, from its `code` or, when a client dropped it,
+    /// from its `data`
+    pub fn of(diagnostic: &Diagnostic) -> Option<Self> {
+        match &diagnostic.code {
+            Some(NumberOrString::String(code)) => Self::from_code(code),
+            _ => diagnostic
+                .data
+                .as_ref()
+                .and_then(|data| data.get("code"))
+                .and_then(|code| code.as_str())
+                .and_then(Self::from_code),
+        }
+    }
+
+    /// rule group the code can be switched off with, none for errors and
+    /// core control flow
+    pub fn group(self) -> Option<RuleGroup> {
+        match self {
+            Self::Sem032 | Self::Sem033 | Self::Sem037 | Self::Sem040 | Self::Sem041 => {
+                Some(RuleGroup::Style)
+            }
+            Self::Sem018
+            | Self::Sem019
+            | Self::Sem020
+            | Self::Sem021
+            | Self::Sem022
+            | Self::Sem023
+            | Self::Sem024
+            | Self::Sem026 => Some(RuleGroup::Dataflow),
+            Self::Sem027 | Self::Sem028 | Self::Sem029 | Self::Sem030 => Some(RuleGroup::Interrupts),
+            _ => None,
+        }
+    }
+
+    /// tags clients use to render the diagnostic, e.g. faded unused code
+    pub fn tags(self) -> Option<Vec<DiagnosticTag>> {
+        match self {
+            Self::Sem016 | Self::Sem032 | Self::Sem037 => Some(vec![DiagnosticTag::UNNECESSARY]),
+            _ => None,
+        }
+    }
+
+    /// link to the explanation of the code
+    pub fn code_description(self) -> CodeDescription {
+        let href = format!("{}#{}", DIAGNOSTICS_DOC_URL, self.as_str().to_lowercase());
+        CodeDescription {
+            href: Url::parse(&href).expect("diagnostics doc URL is valid"),
+        }
+    }
+}
+
+impl AsRef<str> for DiagnosticCode {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Display for DiagnosticCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
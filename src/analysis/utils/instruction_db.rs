@@ -1,16 +1,100 @@
+use super::levenshtein_distance;
+use crate::LspError;
+use serde::Deserialize;
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
 
-#[derive(Debug, Clone)]
+/// Machine W instruction set the database starts from
+pub const DEFAULT_INSTRUCTION_SET: &str = include_str!("../../../isa/machine-w.toml");
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct InstructionInfo {
-    pub name: &'static str,
-    pub description: &'static str,
-    pub operation: &'static str,
+    #[serde(rename = "mnemonic")]
+    pub name: String,
+    /// other spellings of the mnemonic
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub description: String,
+    pub operation: String,
     pub category: InstructionCategory,
+    #[serde(rename = "operand")]
     pub operand_type: OperandType,
+    /// estimated cost in machine cycles
+    #[serde(default = "default_cycles")]
+    pub cycles: u32,
+    #[serde(rename = "extended", default)]
     pub is_extended: bool,
+    #[serde(default)]
+    pub examples: Vec<String>,
+    #[serde(default)]
+    parameter: Option<ParameterInfo>,
+}
+
+fn default_cycles() -> u32 {
+    1
+}
+
+/// name and description of an instruction operand
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParameterInfo {
+    pub name: String,
+    pub description: String,
+}
+
+impl InstructionInfo {
+    /// operand as given in the definition, otherwise derived from the operand
+    /// type; none for instructions without an operand
+    pub fn parameter(&self) -> Option<ParameterInfo> {
+        if let Some(parameter) = &self.parameter {
+            return Some(parameter.clone());
+        }
+        let (name, description) = match self.operand_type {
+            OperandType::None => return None,
+            OperandType::Flexible => ("operand", "Memory address, immediate value (#42), or label"),
+            OperandType::AddressOrLabelOnly => ("address", "Memory address or label"),
+            OperandType::LabelOnly => ("label", "Target label or address to jump to"),
+            OperandType::ImmediateOnly => ("value", "Immediate value (#42)"),
+        };
+        Some(ParameterInfo {
+            name: name.to_string(),
+            description: description.to_string(),
+        })
+    }
+
+    /// mnemonic followed by its aliases
+    pub fn spellings(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.name.as_str()).chain(self.aliases.iter().map(String::as_str))
+    }
+}
+
+/// instruction set a project targets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IsaProfile {
+    /// base Machine W, without `MNO`, `DZI` and `MOD`
+    Base,
+    #[default]
+    Extended,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+impl IsaProfile {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Base => "base",
+            Self::Extended => "extended",
+        }
+    }
+
+    pub fn allows(self, instruction: &InstructionInfo) -> bool {
+        self == Self::Extended || !instruction.is_extended
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum InstructionCategory {
     Arithmetic,
     Memory,
@@ -20,203 +104,137 @@ pub enum InstructionCategory {
     InputOutput,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 pub enum OperandType {
+    #[serde(rename = "none")]
     None,
+    #[serde(rename = "address")]
     AddressOrLabelOnly,
+    #[serde(rename = "label")]
     LabelOnly,
+    #[serde(rename = "immediate")]
     ImmediateOnly,
+    #[serde(rename = "flexible")]
     Flexible,
 }
 
-#[derive(Debug)]
+/// the instructions of a definition file, cheap to clone; mnemonics and
+/// aliases are looked up case-insensitively unless made case-sensitive
+#[derive(Debug, Clone)]
 pub struct InstructionDatabase {
-    instructions: HashMap<&'static str, InstructionInfo>,
+    instructions: Arc<Vec<InstructionInfo>>,
+    /// mnemonic or alias to position in instructions
+    index: Arc<HashMap<String, usize>>,
+    /// the same keyed by uppercase spelling
+    folded_index: Arc<HashMap<String, usize>>,
+    case_sensitive: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct InstructionSetFile {
+    instruction: Vec<InstructionInfo>,
+}
+
+impl Default for InstructionDatabase {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl InstructionDatabase {
+    /// the embedded Machine W instruction set
     pub fn new() -> Self {
-        let mut db = Self {
-            instructions: HashMap::new(),
-        };
-        db.initialize();
-        db
+        Self::builtin().clone()
+    }
+
+    /// shared instance of the embedded instruction set
+    pub fn builtin() -> &'static Self {
+        static BUILTIN: OnceLock<InstructionDatabase> = OnceLock::new();
+        BUILTIN.get_or_init(|| {
+            Self::from_toml(DEFAULT_INSTRUCTION_SET).expect("embedded instruction set is valid")
+        })
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, LspError> {
+        let file: InstructionSetFile = toml::from_str(text)
+            .map_err(|error| LspError::ConfigError(error.to_string()))?;
+        Self::from_instructions(file.instruction)
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, LspError> {
+        let file: InstructionSetFile = serde_json::from_str(text)?;
+        Self::from_instructions(file.instruction)
+    }
+
+    /// parses path as TOML or, with a `.json` extension, as JSON
+    pub fn from_file(path: &Path) -> Result<Self, LspError> {
+        let text = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => Self::from_json(&text),
+            _ => Self::from_toml(&text),
+        }
     }
 
-    fn initialize(&mut self) {
-        let instructions = [
-            // Arithmetic instructions
-            InstructionInfo {
-                name: "DOD",
-                description: "Add value to accumulator: (AK) + (operand) → AK",
-                operation: "(AK) + (operand) → AK",
-                category: InstructionCategory::Arithmetic,
-                operand_type: OperandType::Flexible,
-                is_extended: false,
-            },
-            InstructionInfo {
-                name: "ODE",
-                description: "Subtract value from accumulator: (AK) - (operand) → AK",
-                operation: "(AK) - (operand) → AK",
-                category: InstructionCategory::Arithmetic,
-                operand_type: OperandType::Flexible,
-                is_extended: false,
-            },
-            // Memory instructions
-            InstructionInfo {
-                name: "POB",
-                description: "Load value into accumulator: (operand) → AK",
-                operation: "(operand) → AK",
-                category: InstructionCategory::Memory,
-                operand_type: OperandType::Flexible,
-                is_extended: false,
-            },
-            InstructionInfo {
-                name: "ŁAD",
-                description: "Store accumulator to memory: (AK) → (address)",
-                operation: "(AK) → (address)",
-                category: InstructionCategory::Memory,
-                operand_type: OperandType::AddressOrLabelOnly,
-                is_extended: false,
-            },
-            // Control flow instructions
-            InstructionInfo {
-                name: "SOB",
-                description: "Unconditional jump to label",
-                operation: "Unconditional jump",
-                category: InstructionCategory::ControlFlow,
-                operand_type: OperandType::LabelOnly,
-                is_extended: false,
-            },
-            InstructionInfo {
-                name: "SOM",
-                description: "Jump to label if AK < 0",
-                operation: "Jump if AK < 0",
-                category: InstructionCategory::ControlFlow,
-                operand_type: OperandType::LabelOnly,
-                is_extended: false,
-            },
-            InstructionInfo {
-                name: "SOZ",
-                description: "Jump to label if AK = 0",
-                operation: "Jump if AK = 0",
-                category: InstructionCategory::ControlFlow,
-                operand_type: OperandType::LabelOnly,
-                is_extended: false,
-            },
-            InstructionInfo {
-                name: "STP",
-                description: "Stop program execution",
-                operation: "Halt execution",
-                category: InstructionCategory::ControlFlow,
-                operand_type: OperandType::None,
-                is_extended: false,
-            },
-            // Stack instructions
-            InstructionInfo {
-                name: "SDP",
-                description: "Push accumulator to stack: (AK) → stack",
-                operation: "(AK) → stack",
-                category: InstructionCategory::Stack,
-                operand_type: OperandType::None,
-                is_extended: false,
-            },
-            InstructionInfo {
-                name: "PZS",
-                description: "Pop from stack to accumulator: stack → AK",
-                operation: "stack → AK",
-                category: InstructionCategory::Stack,
-                operand_type: OperandType::None,
-                is_extended: false,
-            },
-            // Interrupt instructions
-            InstructionInfo {
-                name: "DNS",
-                description: "Disable interrupt handling",
-                operation: "Disable interrupts",
-                category: InstructionCategory::Interrupt,
-                operand_type: OperandType::None,
-                is_extended: false,
-            },
-            InstructionInfo {
-                name: "CZM",
-                description: "Clear interrupt mask register",
-                operation: "Clear interrupt mask",
-                category: InstructionCategory::Interrupt,
-                operand_type: OperandType::None,
-                is_extended: false,
-            },
-            InstructionInfo {
-                name: "MSK",
-                description: "Set interrupt mask register",
-                operation: "Set interrupt mask",
-                category: InstructionCategory::Interrupt,
-                operand_type: OperandType::ImmediateOnly,
-                is_extended: false,
-            },
-            InstructionInfo {
-                name: "PWR",
-                description: "Return from interrupt handler",
-                operation: "Return from interrupt",
-                category: InstructionCategory::Interrupt,
-                operand_type: OperandType::None,
-                is_extended: false,
-            },
-            // I/O instructions
-            InstructionInfo {
-                name: "WEJSCIE",
-                description: "Input value from user",
-                operation: "Input → AK",
-                category: InstructionCategory::InputOutput,
-                operand_type: OperandType::None,
-                is_extended: false,
-            },
-            InstructionInfo {
-                name: "WYJSCIE",
-                description: "Output accumulator value",
-                operation: "Output AK",
-                category: InstructionCategory::InputOutput,
-                operand_type: OperandType::None,
-                is_extended: false,
-            },
-            // Extended instructions
-            InstructionInfo {
-                name: "MNO",
-                description: "Multiply: (AK) * (operand) → AK [Extended]",
-                operation: "(AK) * (operand) → AK",
-                category: InstructionCategory::Arithmetic,
-                operand_type: OperandType::Flexible,
-                is_extended: true,
-            },
-            InstructionInfo {
-                name: "DZI",
-                description: "Divide: (AK) / (operand) → AK [Extended]",
-                operation: "(AK) / (operand) → AK",
-                category: InstructionCategory::Arithmetic,
-                operand_type: OperandType::Flexible,
-                is_extended: true,
-            },
-            InstructionInfo {
-                name: "MOD",
-                description: "Modulo: (AK) % (operand) → AK [Extended]",
-                operation: "(AK) % (operand) → AK",
-                category: InstructionCategory::Arithmetic,
-                operand_type: OperandType::Flexible,
-                is_extended: true,
-            },
-        ];
+    fn from_instructions(instructions: Vec<InstructionInfo>) -> Result<Self, LspError> {
+        let mut index = HashMap::new();
+        let mut folded_index = HashMap::new();
+        for (position, instruction) in instructions.iter().enumerate() {
+            if instruction.name.trim().is_empty() {
+                return Err(LspError::ConfigError(
+                    "instruction with an empty mnemonic".to_string(),
+                ));
+            }
+            for spelling in instruction.spellings() {
+                if let Some(other) = folded_index.insert(spelling.to_uppercase(), position) {
+                    return Err(LspError::ConfigError(format!(
+                        "'{}' is defined by both '{}' and '{}'",
+                        spelling, instructions[other].name, instruction.name
+                    )));
+                }
+                index.insert(spelling.to_string(), position);
+            }
+        }
+
+        Ok(Self {
+            instructions: Arc::new(instructions),
+            index: Arc::new(index),
+            folded_index: Arc::new(folded_index),
+            case_sensitive: false,
+        })
+    }
+
+    /// whether `pob` stays an unknown instruction instead of meaning `POB`
+    pub fn with_case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
 
-        for instruction in instructions {
-            self.instructions.insert(instruction.name, instruction);
+    fn position(&self, spelling: &str) -> Option<usize> {
+        match self.index.get(spelling) {
+            Some(&position) => Some(position),
+            None if !self.case_sensitive => self.folded_index.get(&spelling.to_uppercase()).copied(),
+            None => None,
         }
     }
 
-    pub fn get_instruction(&self, name: &str) -> Option<&InstructionInfo> {
-        self.instructions.get(name)
+    /// every instruction, in definition file order
+    pub fn instructions(&self) -> impl Iterator<Item = &InstructionInfo> {
+        self.instructions.iter()
     }
 
-    pub fn is_valid_instruction(&self, name: &str) -> bool {
-        self.instructions.contains_key(name)
+    /// instruction spelled as its mnemonic or one of its aliases
+    pub fn get_instruction(&self, spelling: &str) -> Option<&InstructionInfo> {
+        self.position(spelling).map(|position| &self.instructions[position])
+    }
+
+    pub fn is_valid_instruction(&self, spelling: &str) -> bool {
+        self.position(spelling).is_some()
+    }
+
+    /// mnemonic of the instruction spelled, e.g. `ŁAD` for `lad`
+    pub fn canonical_name(&self, spelling: &str) -> Option<&str> {
+        self.get_instruction(spelling).map(|info| info.name.as_str())
     }
 
     pub fn get_instructions_by_category(
@@ -224,7 +242,7 @@ impl InstructionDatabase {
         category: InstructionCategory,
     ) -> Vec<&InstructionInfo> {
         self.instructions
-            .values()
+            .iter()
             .filter(|info| info.category == category)
             .collect()
     }
@@ -233,11 +251,11 @@ impl InstructionDatabase {
         let mut suggestions = Vec::new();
         let unknown_upper = unknown.to_uppercase();
 
-        for &instruction_name in self.instructions.keys() {
-            let distance = levenshtein_distance(&unknown_upper, instruction_name);
+        for instruction in self.instructions.iter() {
+            let distance = levenshtein_distance(&unknown_upper, &instruction.name);
             if distance <= 2 {
                 // max 2 character differences
-                suggestions.push(instruction_name.to_string());
+                suggestions.push(instruction.name.clone());
             }
         }
 
@@ -246,40 +264,3 @@ impl InstructionDatabase {
         suggestions
     }
 }
-
-fn levenshtein_distance(a: &str, b: &str) -> usize {
-    let a_chars: Vec<char> = a.chars().collect();
-    let b_chars: Vec<char> = b.chars().collect();
-    let a_len = a_chars.len();
-    let b_len = b_chars.len();
-
-    let mut matrix = vec![vec![0; b_len + 1]; a_len + 1];
-
-    // first row and column
-    for i in 0..=a_len {
-        matrix[i][0] = i;
-    }
-    for j in 0..=b_len {
-        matrix[0][j] = j;
-    }
-
-    // matrix
-    for i in 1..=a_len {
-        for j in 1..=b_len {
-            let cost = if a_chars[i - 1] == b_chars[j - 1] {
-                0
-            } else {
-                1
-            };
-            matrix[i][j] = std::cmp::min(
-                std::cmp::min(
-                    matrix[i - 1][j] + 1, // deletion
-                    matrix[i][j - 1] + 1, // insertion
-                ),
-                matrix[i - 1][j - 1] + cost, // substitution
-            );
-        }
-    }
-
-    matrix[a_len][b_len]
-}
@@ -0,0 +1,76 @@
+use super::PositionEncoding;
+use tower_lsp::lsp_types::Position;
+
+/// byte offsets of line starts, kept in sync with document text
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineIndex {
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(text: &str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(newline_offsets(text, 0));
+        Self { line_starts }
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// byte offset where line starts (clamped to the last line)
+    pub fn line_start(&self, line: usize) -> usize {
+        self.line_starts[line.min(self.line_starts.len() - 1)]
+    }
+
+    /// line text without its line terminator
+    pub fn line_text<'a>(&self, text: &'a str, line: usize) -> Option<&'a str> {
+        if line >= self.line_starts.len() {
+            return None;
+        }
+
+        let start = self.line_starts[line];
+        let end = self
+            .line_starts
+            .get(line + 1)
+            .copied()
+            .unwrap_or(text.len());
+
+        Some(text[start..end].trim_end_matches(['\n', '\r']))
+    }
+
+    /// LSP position to byte offset, clamped to the document
+    pub fn offset(&self, text: &str, position: Position, encoding: PositionEncoding) -> usize {
+        let line = position.line as usize;
+        if line >= self.line_starts.len() {
+            return text.len();
+        }
+
+        let line_text = self.line_text(text, line).unwrap_or("");
+        self.line_starts[line] + encoding.to_byte_offset(line_text, position.character)
+    }
+
+    /// keeps line starts in sync after `text[start..end]` was replaced with `new_text`
+    pub fn apply_edit(&mut self, start: usize, end: usize, new_text: &str) {
+        let first_affected = self.line_starts.partition_point(|&offset| offset <= start);
+        let last_affected = self.line_starts.partition_point(|&offset| offset <= end);
+
+        let shift = new_text.len() as isize - (end - start) as isize;
+        let inserted = newline_offsets(new_text, start);
+        let shifted_tail = self.line_starts[last_affected..]
+            .iter()
+            .map(|&offset| (offset as isize + shift) as usize);
+
+        let tail: Vec<usize> = inserted.chain(shifted_tail).collect();
+        self.line_starts.truncate(first_affected);
+        self.line_starts.extend(tail);
+    }
+}
+
+/// offsets just past each '\n' in text, shifted by base
+fn newline_offsets(text: &str, base: usize) -> impl Iterator<Item = usize> + '_ {
+    text.bytes()
+        .enumerate()
+        .filter(|(_, byte)| *byte == b'\n')
+        .map(move |(idx, _)| base + idx + 1)
+}
@@ -0,0 +1,144 @@
+use super::is_word_char;
+
+/// Asmodeus encodes instructions as 16-bit words with an 8-bit argument field
+pub const DEFAULT_WORD_BITS: u32 = 16;
+pub const DEFAULT_ADDRESS_BITS: u32 = 8;
+
+/// Machine W word width and address space
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryModel {
+    pub word_bits: u32,
+    pub address_bits: u32,
+}
+
+impl Default for MemoryModel {
+    fn default() -> Self {
+        Self::new(DEFAULT_WORD_BITS, DEFAULT_ADDRESS_BITS)
+    }
+}
+
+impl MemoryModel {
+    /// widths are clamped to 1..=32 bits
+    pub fn new(word_bits: u32, address_bits: u32) -> Self {
+        Self {
+            word_bits: word_bits.clamp(1, 32),
+            address_bits: address_bits.clamp(1, 32),
+        }
+    }
+
+    /// number of addressable words
+    pub fn memory_size(&self) -> i128 {
+        1 << self.address_bits
+    }
+
+    /// fits as either a signed or an unsigned word
+    pub fn fits_word(&self, value: i128) -> bool {
+        value >= -(1 << (self.word_bits - 1)) && value < (1 << self.word_bits)
+    }
+
+    pub fn fits_address(&self, value: i128) -> bool {
+        (0..self.memory_size()).contains(&value)
+    }
+
+    /// bit pattern stored in a word (value modulo 2^word_bits)
+    pub fn word_pattern(&self, value: i128) -> u64 {
+        value.rem_euclid(1 << self.word_bits) as u64
+    }
+
+    /// word bit pattern read as two's complement
+    pub fn signed_value(&self, pattern: u64) -> i64 {
+        let pattern = pattern as i64;
+        if pattern >= 1 << (self.word_bits - 1) {
+            pattern - (1 << self.word_bits)
+        } else {
+            pattern
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiteralRadix {
+    Decimal,
+    Hexadecimal,
+    Binary,
+}
+
+impl LiteralRadix {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Decimal => "decimal",
+            Self::Hexadecimal => "hexadecimal",
+            Self::Binary => "binary",
+        }
+    }
+}
+
+/// radix a literal is written in, judged by its prefix
+pub fn literal_radix(text: &str) -> LiteralRadix {
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    match digits.get(..2) {
+        Some("0x") | Some("0X") => LiteralRadix::Hexadecimal,
+        Some("0b") | Some("0B") => LiteralRadix::Binary,
+        _ => LiteralRadix::Decimal,
+    }
+}
+
+/// value of a decimal, `0x` or `0b` literal with optional `-`; values too
+/// large for i128 saturate so they still count as overflowing
+pub fn parse_number_literal(text: &str) -> Option<i128> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+
+    let (radix, digits) = match literal_radix(digits) {
+        LiteralRadix::Hexadecimal => (16, &digits[2..]),
+        LiteralRadix::Binary => (2, &digits[2..]),
+        LiteralRadix::Decimal => (10, digits),
+    };
+
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+
+    let magnitude = digits.chars().fold(0i128, |acc, c| {
+        acc.saturating_mul(radix as i128)
+            .saturating_add(c.to_digit(radix).unwrap() as i128)
+    });
+
+    Some(if negative { -magnitude } else { magnitude })
+}
+
+/// words starting with a digit, with a directly preceding `-`, as
+/// (text, start, end) char columns; label definitions like `1abc:` are left
+/// to label validation
+pub fn find_number_literals(code: &str) -> Vec<(String, usize, usize)> {
+    let chars: Vec<char> = code.chars().collect();
+    let mut literals = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if !is_word_char(chars[i]) {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        while i < chars.len() && is_word_char(chars[i]) {
+            i += 1;
+        }
+
+        if !chars[start].is_ascii_digit() || chars.get(i) == Some(&':') {
+            continue;
+        }
+
+        let start = if start > 0 && chars[start - 1] == '-' {
+            start - 1
+        } else {
+            start
+        };
+        literals.push((chars[start..i].iter().collect(), start, i));
+    }
+
+    literals
+}
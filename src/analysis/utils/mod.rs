@@ -1,7 +1,18 @@
+pub mod diagnostic_codes;
 pub mod instruction_db;
+pub mod line_index;
+pub mod memory_model;
+pub mod position_encoding;
 pub mod text_utils;
 pub mod position_utils;
 
-pub use instruction_db::{InstructionDatabase, InstructionInfo, InstructionCategory, OperandType};
+pub use diagnostic_codes::{DiagnosticCode, RuleGroup, DIAGNOSTICS_DOC_URL};
+pub use instruction_db::{
+    InstructionCategory, InstructionDatabase, InstructionInfo, IsaProfile, OperandType,
+    ParameterInfo, DEFAULT_INSTRUCTION_SET,
+};
+pub use line_index::LineIndex;
+pub use memory_model::*;
+pub use position_encoding::{PositionConverter, PositionEncoding};
 pub use text_utils::*;
 pub use position_utils::*;
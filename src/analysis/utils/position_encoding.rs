@@ -0,0 +1,244 @@
+use tower_lsp::lsp_types::*;
+
+/// unit of `Position::character` agreed with the client
+///
+/// providers work on char columns (what lexariel reports), conversion to and
+/// from the negotiated encoding happens only at the server boundary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PositionEncoding {
+    Utf8,
+    #[default]
+    Utf16,
+    Utf32,
+}
+
+impl PositionEncoding {
+    /// first encoding offered by the client that we support, UTF-16 otherwise
+    pub fn negotiate(client_encodings: Option<&[PositionEncodingKind]>) -> Self {
+        client_encodings
+            .unwrap_or_default()
+            .iter()
+            .find_map(Self::from_kind)
+            .unwrap_or_default()
+    }
+
+    pub fn from_kind(kind: &PositionEncodingKind) -> Option<Self> {
+        match kind.as_str() {
+            "utf-8" => Some(Self::Utf8),
+            "utf-16" => Some(Self::Utf16),
+            "utf-32" => Some(Self::Utf32),
+            _ => None,
+        }
+    }
+
+    pub fn kind(self) -> PositionEncodingKind {
+        match self {
+            Self::Utf8 => PositionEncodingKind::UTF8,
+            Self::Utf16 => PositionEncodingKind::UTF16,
+            Self::Utf32 => PositionEncodingKind::UTF32,
+        }
+    }
+
+    fn char_width(self, ch: char) -> usize {
+        match self {
+            Self::Utf8 => ch.len_utf8(),
+            Self::Utf16 => ch.len_utf16(),
+            Self::Utf32 => 1,
+        }
+    }
+
+    /// column in this encoding to char column, clamped to line length;
+    /// columns pointing inside a character snap to its start
+    pub fn to_char_column(self, line: &str, column: u32) -> usize {
+        let mut units = 0;
+        for (char_idx, ch) in line.chars().enumerate() {
+            let width = self.char_width(ch);
+            if units + width > column as usize {
+                return char_idx;
+            }
+            units += width;
+        }
+        line.chars().count()
+    }
+
+    /// char column to column in this encoding
+    pub fn from_char_column(self, line: &str, char_column: usize) -> u32 {
+        line.chars()
+            .take(char_column)
+            .map(|ch| self.char_width(ch))
+            .sum::<usize>() as u32
+    }
+
+    /// column in this encoding to byte offset within line
+    pub fn to_byte_offset(self, line: &str, column: u32) -> usize {
+        line.char_indices()
+            .nth(self.to_char_column(line, column))
+            .map(|(byte_idx, _)| byte_idx)
+            .unwrap_or(line.len())
+    }
+}
+
+/// converts positions between client encoding and internal char columns
+pub struct PositionConverter<'a> {
+    lines: Vec<&'a str>,
+    encoding: PositionEncoding,
+}
+
+impl<'a> PositionConverter<'a> {
+    pub fn new(content: &'a str, encoding: PositionEncoding) -> Self {
+        Self {
+            lines: content.lines().collect(),
+            encoding,
+        }
+    }
+
+    fn line(&self, line: u32) -> &str {
+        self.lines.get(line as usize).copied().unwrap_or("")
+    }
+
+    // client -> internal
+
+    pub fn to_internal_position(&self, position: Position) -> Position {
+        if self.encoding == PositionEncoding::Utf32 {
+            return position;
+        }
+        Position {
+            line: position.line,
+            character: self
+                .encoding
+                .to_char_column(self.line(position.line), position.character)
+                as u32,
+        }
+    }
+
+    pub fn to_internal_range(&self, range: Range) -> Range {
+        Range {
+            start: self.to_internal_position(range.start),
+            end: self.to_internal_position(range.end),
+        }
+    }
+
+    pub fn to_internal_diagnostic(&self, diagnostic: &Diagnostic) -> Diagnostic {
+        Diagnostic {
+            range: self.to_internal_range(diagnostic.range),
+            ..diagnostic.clone()
+        }
+    }
+
+    // internal -> client
+
+    pub fn to_lsp_position(&self, position: Position) -> Position {
+        if self.encoding == PositionEncoding::Utf32 {
+            return position;
+        }
+        Position {
+            line: position.line,
+            character: self
+                .encoding
+                .from_char_column(self.line(position.line), position.character as usize),
+        }
+    }
+
+    pub fn to_lsp_range(&self, range: Range) -> Range {
+        Range {
+            start: self.to_lsp_position(range.start),
+            end: self.to_lsp_position(range.end),
+        }
+    }
+
+    pub fn to_lsp_diagnostic(&self, mut diagnostic: Diagnostic) -> Diagnostic {
+        diagnostic.range = self.to_lsp_range(diagnostic.range);
+        if let Some(related) = diagnostic.related_information.as_mut() {
+            for info in related.iter_mut() {
+                info.location.range = self.to_lsp_range(info.location.range);
+            }
+        }
+        diagnostic
+    }
+
+    pub fn to_lsp_location(&self, mut location: Location) -> Location {
+        location.range = self.to_lsp_range(location.range);
+        location
+    }
+
+    pub fn to_lsp_hover(&self, mut hover: Hover) -> Hover {
+        hover.range = hover.range.map(|range| self.to_lsp_range(range));
+        hover
+    }
+
+    pub fn to_lsp_inlay_hint(&self, mut hint: InlayHint) -> InlayHint {
+        hint.position = self.to_lsp_position(hint.position);
+        hint
+    }
+
+    pub fn to_lsp_workspace_edit(&self, mut edit: WorkspaceEdit) -> WorkspaceEdit {
+        if let Some(changes) = edit.changes.as_mut() {
+            for edits in changes.values_mut() {
+                for text_edit in edits.iter_mut() {
+                    text_edit.range = self.to_lsp_range(text_edit.range);
+                }
+            }
+        }
+        edit
+    }
+
+    pub fn to_lsp_code_action(&self, action: CodeActionOrCommand) -> CodeActionOrCommand {
+        match action {
+            CodeActionOrCommand::CodeAction(mut code_action) => {
+                code_action.edit = code_action
+                    .edit
+                    .map(|edit| self.to_lsp_workspace_edit(edit));
+                code_action.diagnostics = code_action.diagnostics.map(|diagnostics| {
+                    diagnostics
+                        .into_iter()
+                        .map(|diagnostic| self.to_lsp_diagnostic(diagnostic))
+                        .collect()
+                });
+                CodeActionOrCommand::CodeAction(code_action)
+            }
+            command => command,
+        }
+    }
+
+    /// semantic tokens are delta encoded, so decode, convert and encode again
+    pub fn to_lsp_semantic_tokens(&self, tokens: Vec<SemanticToken>) -> Vec<SemanticToken> {
+        if self.encoding == PositionEncoding::Utf32 {
+            return tokens;
+        }
+
+        let mut converted = Vec::with_capacity(tokens.len());
+        let (mut line, mut start) = (0, 0);
+        let (mut prev_line, mut prev_start) = (0, 0);
+
+        for token in tokens {
+            line += token.delta_line;
+            start = if token.delta_line == 0 {
+                start + token.delta_start
+            } else {
+                token.delta_start
+            };
+
+            let text = self.line(line);
+            let lsp_start = self.encoding.from_char_column(text, start as usize);
+            let lsp_end = self
+                .encoding
+                .from_char_column(text, (start + token.length) as usize);
+
+            converted.push(SemanticToken {
+                delta_line: line - prev_line,
+                delta_start: if line == prev_line {
+                    lsp_start - prev_start
+                } else {
+                    lsp_start
+                },
+                length: lsp_end - lsp_start,
+                ..token
+            });
+
+            prev_line = line;
+            prev_start = lsp_start;
+        }
+
+        converted
+    }
+}
@@ -1,3 +1,5 @@
+use super::DiagnosticCode;
+use serde_json::json;
 use tower_lsp::lsp_types::*;
 
 /// (line, character) to Range
@@ -37,7 +39,7 @@ pub fn is_valid_position(content: &str, position: Position) -> bool {
     }
 
     let line = lines[position.line as usize];
-    position.character as usize <= line.len()
+    position.character as usize <= line.chars().count()
 }
 
 pub fn get_line_at_position(content: &str, position: Position) -> Option<&str> {
@@ -102,38 +104,26 @@ pub fn coordinates_to_location(
     }
 }
 
-pub fn create_diagnostic(
-    range: Range,
-    severity: DiagnosticSeverity,
-    code: &str,
-    message: String,
-) -> Diagnostic {
+/// diagnostic with the code's default severity, its tags, a link to its
+/// explanation and the code in `data`, so code actions can find it
+pub fn create_diagnostic(range: Range, code: DiagnosticCode, message: String) -> Diagnostic {
     Diagnostic {
         range,
-        severity: Some(severity),
-        code: Some(NumberOrString::String(code.to_string())),
+        severity: Some(code.default_severity()),
+        code: Some(NumberOrString::String(code.as_str().to_string())),
         source: Some("asmodeus-lsp".to_string()),
         message,
         related_information: None,
-        tags: None,
-        code_description: None,
-        data: None,
+        tags: code.tags(),
+        code_description: Some(code.code_description()),
+        data: Some(json!({ "code": code.as_str() })),
     }
 }
 
-/// for parser (parseid) / lexer (lexariel) error with default position
-pub fn create_parser_diagnostic(message: String, error_type: &str) -> Diagnostic {
-    let range = Range {
-        start: Position {
-            line: 0,
-            character: 0,
-        },
-        end: Position {
-            line: 0,
-            character: 1,
-        },
-    };
-    create_diagnostic(range, DiagnosticSeverity::ERROR, error_type, message)
+/// for parser (parseid) / lexer (lexariel) error over the range of the
+/// offending token
+pub fn create_parser_diagnostic(range: Range, message: String, code: DiagnosticCode) -> Diagnostic {
+    create_diagnostic(range, code, message)
 }
 
 /// for semantic error with specified position
@@ -141,7 +131,7 @@ pub fn create_semantic_diagnostic(
     line: usize,
     column: usize,
     length: usize,
-    code: &str,
+    code: DiagnosticCode,
     message: String,
 ) -> Diagnostic {
     let range = Range {
@@ -154,7 +144,7 @@ pub fn create_semantic_diagnostic(
             character: (column + length) as u32,
         },
     };
-    create_diagnostic(range, DiagnosticSeverity::ERROR, code, message)
+    create_diagnostic(range, code, message)
 }
 
 /// checks whether there is : after the symbol
@@ -169,10 +159,6 @@ pub fn is_label_definition_location(content: &str, location: &Location) -> bool
     let line = lines[line_num];
     let end_pos = location.range.end.character as usize;
 
-    if end_pos < line.len() {
-        line.chars().nth(end_pos) == Some(':')
-    } else {
-        false
-    }
+    line.chars().nth(end_pos) == Some(':')
 }
 
@@ -3,15 +3,60 @@ pub fn is_word_char(c: char) -> bool {
     c.is_alphanumeric() || c == '_' || c == 'Ł'
 }
 
+/// all columns below are char columns, not byte offsets
+///
+/// number of chars in text
+pub fn char_len(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// part of line before char column
+pub fn text_before_column(line: &str, column: usize) -> &str {
+    match line.char_indices().nth(column) {
+        Some((byte_idx, _)) => &line[..byte_idx],
+        None => line,
+    }
+}
+
+/// chars between char columns
+pub fn slice_columns(line: &str, start: usize, end: usize) -> String {
+    line.chars().skip(start).take(end.saturating_sub(start)).collect()
+}
+
+/// char column of the first non-whitespace char
+pub fn indentation_width(line: &str) -> usize {
+    line.chars().take_while(|c| c.is_whitespace()).count()
+}
+
+/// byte offset and length of the `;` or `//` starting the comment of line
+fn comment_marker(line: &str) -> Option<(usize, usize)> {
+    let semicolon = line.find(';').map(|pos| (pos, 1));
+    let slashes = line.find("//").map(|pos| (pos, 2));
+    match (semicolon, slashes) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (marker, None) | (None, marker) => marker,
+    }
+}
+
+/// code part of line, without `;` or `//` comment
+pub fn strip_comment(line: &str) -> &str {
+    comment_marker(line).map_or(line, |(pos, _)| &line[..pos])
+}
+
+/// text of the `;` or `//` comment of line, after the marker
+pub fn comment_text(line: &str) -> Option<&str> {
+    comment_marker(line).map(|(pos, len)| &line[pos + len..])
+}
+
 /// finds words at position in line
 /// (word, start_position, end_position) or None if not found
 pub fn get_word_at_position(line: &str, cursor_pos: usize) -> Option<(String, usize, usize)> {
-    if cursor_pos > line.len() {
+    let chars: Vec<char> = line.chars().collect();
+
+    if cursor_pos > chars.len() {
         return None;
     }
 
-    let chars: Vec<char> = line.chars().collect();
-
     // is word char?
     if cursor_pos < chars.len() && !is_word_char(chars[cursor_pos]) {
         return None;
@@ -48,7 +93,7 @@ pub fn is_whole_word_match(line: &str, pos: usize, word: &str) -> bool {
     }
 
     // check char after word
-    let end_pos = pos + word.len();
+    let end_pos = pos + char_len(word);
     if let Some(next_char) = chars.get(end_pos) {
         if is_word_char(*next_char) {
             return false;
@@ -60,7 +105,7 @@ pub fn is_whole_word_match(line: &str, pos: usize, word: &str) -> bool {
 
 /// (has : after)
 pub fn is_label_declaration(line: &str, pos: usize, word: &str) -> bool {
-    let after_word_pos = pos + word.len();
+    let after_word_pos = pos + char_len(word);
     line.chars().nth(after_word_pos) == Some(':')
 }
 
@@ -83,12 +128,13 @@ pub fn find_word_occurrences(content: &str, word: &str) -> Vec<(usize, usize, us
     for (line_num, line) in lines.iter().enumerate() {
         let mut search_pos = 0;
         while let Some(pos) = line[search_pos..].find(word) {
-            let actual_pos = search_pos + pos;
+            let byte_pos = search_pos + pos;
+            let column = char_len(&line[..byte_pos]);
 
-            if is_whole_word_match(line, actual_pos, word) {
-                occurrences.push((line_num, actual_pos, actual_pos + word.len()));
+            if is_whole_word_match(line, column, word) {
+                occurrences.push((line_num, column, column + char_len(word)));
             }
-            search_pos = actual_pos + word.len();
+            search_pos = byte_pos + word.len();
         }
     }
 
@@ -101,8 +147,8 @@ pub fn find_label_definition(label: &str, content: &str) -> Option<(usize, usize
     for (line_num, line) in lines.iter().enumerate() {
         let trimmed = line.trim();
         if trimmed.starts_with(&format!("{}:", label)) {
-            let label_start = line.len() - trimmed.len(); // position after leading whitespace
-            return Some((line_num, label_start, label_start + label.len()));
+            let label_start = indentation_width(line); // position after leading whitespace
+            return Some((line_num, label_start, label_start + char_len(label)));
         }
     }
     None
@@ -124,3 +170,114 @@ pub fn get_label_info(word: &str, content: &str) -> Option<String> {
     }
     None
 }
+
+/// edit distance between two strings, in chars
+pub fn levenshtein_distance(a: &str, b: &str) -> usize {
+    let a_chars: Vec<char> = a.chars().collect();
+    let b_chars: Vec<char> = b.chars().collect();
+    let a_len = a_chars.len();
+    let b_len = b_chars.len();
+
+    let mut matrix = vec![vec![0; b_len + 1]; a_len + 1];
+
+    // first row and column
+    for (i, row) in matrix.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in matrix[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    // matrix
+    for i in 1..=a_len {
+        for j in 1..=b_len {
+            let cost = if a_chars[i - 1] == b_chars[j - 1] {
+                0
+            } else {
+                1
+            };
+            matrix[i][j] = std::cmp::min(
+                std::cmp::min(
+                    matrix[i - 1][j] + 1, // deletion
+                    matrix[i][j - 1] + 1, // insertion
+                ),
+                matrix[i - 1][j - 1] + cost, // substitution
+            );
+        }
+    }
+
+    matrix[a_len][b_len]
+}
+
+/// candidates at most 2 edits away from name (or differing only in case),
+/// closest first
+pub fn find_similar_names<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Vec<&'a str> {
+    let mut similar: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| {
+            let distance = if candidate.eq_ignore_ascii_case(name) {
+                0
+            } else {
+                levenshtein_distance(name, candidate)
+            };
+            (distance, candidate)
+        })
+        .filter(|(distance, _)| *distance <= 2)
+        .collect();
+
+    similar.sort();
+    similar.dedup();
+    similar.into_iter().map(|(_, candidate)| candidate).collect()
+}
+
+/// fills snippet tabstops: `${n:default}` and `$n` become values[n - 1],
+/// falling back to the default, or to nothing for a bare `$n`
+pub fn expand_snippet(snippet: &str, values: &[&str]) -> String {
+    let chars: Vec<char> = snippet.chars().collect();
+    let mut expanded = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i] != '$' {
+            expanded.push(chars[i]);
+            i += 1;
+            continue;
+        }
+
+        let braced = chars.get(i + 1) == Some(&'{');
+        let digits_start = if braced { i + 2 } else { i + 1 };
+        let mut j = digits_start;
+        while j < chars.len() && chars[j].is_ascii_digit() {
+            j += 1;
+        }
+        let Ok(index) = chars[digits_start..j].iter().collect::<String>().parse::<usize>() else {
+            expanded.push('$');
+            i += 1;
+            continue;
+        };
+
+        let mut default = String::new();
+        if braced {
+            if chars.get(j) == Some(&':') {
+                j += 1;
+                while j < chars.len() && chars[j] != '}' {
+                    default.push(chars[j]);
+                    j += 1;
+                }
+            }
+            j += 1; // closing brace
+        }
+
+        match values.get(index.wrapping_sub(1)) {
+            Some(value) => expanded.push_str(value),
+            None => expanded.push_str(&default),
+        }
+        i = j;
+    }
+
+    expanded
+}
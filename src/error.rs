@@ -1,6 +1,11 @@
 use thiserror::Error;
+use tower_lsp::jsonrpc::{self, ErrorCode};
+
+/// LSP `RequestFailed`: the request was valid but could not be carried out
+const REQUEST_FAILED: i64 = -32803;
 
 #[derive(Error, Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum LspError {
     #[error("Lexer error: {0}")]
     LexerError(#[from] lexariel::LexerError),
@@ -13,4 +18,32 @@ pub enum LspError {
 
     #[error("JSON serialization error: {0}")]
     JsonError(#[from] serde_json::Error),
+
+    #[error("Invalid configuration: {0}")]
+    ConfigError(String),
+
+    #[error(
+        "Label '{name}' is defined {} times (lines {}), remove the duplicate definitions first",
+        .lines.len(),
+        format_lines(.lines)
+    )]
+    AmbiguousLabel { name: String, lines: Vec<usize> },
+}
+
+fn format_lines(lines: &[usize]) -> String {
+    lines
+        .iter()
+        .map(|line| line.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl From<LspError> for jsonrpc::Error {
+    fn from(error: LspError) -> Self {
+        jsonrpc::Error {
+            code: ErrorCode::ServerError(REQUEST_FAILED),
+            message: error.to_string().into(),
+            data: None,
+        }
+    }
 }
@@ -2,11 +2,7 @@ use anyhow::Result;
 use tower_lsp::{LspService, Server};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-mod error;
-mod analysis;
-mod server;
-
-use server::AsmodeusLanguageServer;
+use asmodeus_lsp::AsmodeusLanguageServer;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -25,7 +21,7 @@ async fn main() -> Result<()> {
     let stdin = tokio::io::stdin();
     let stdout = tokio::io::stdout();
 
-    let (service, socket) = LspService::new(|client| AsmodeusLanguageServer::new(client));
+    let (service, socket) = LspService::new(AsmodeusLanguageServer::new);
     
     Server::new(stdin, stdout, socket).serve(service).await;
 
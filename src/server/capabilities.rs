@@ -1,9 +1,12 @@
+use crate::analysis::language::semantic_tokens::semantic_tokens_legend;
+use crate::analysis::utils::PositionEncoding;
 use tower_lsp::lsp_types::*;
 
-pub fn create_server_capabilities() -> ServerCapabilities {
+pub fn create_server_capabilities(position_encoding: PositionEncoding) -> ServerCapabilities {
     ServerCapabilities {
+        position_encoding: Some(position_encoding.kind()),
         text_document_sync: Some(TextDocumentSyncCapability::Kind(
-            TextDocumentSyncKind::FULL,
+            TextDocumentSyncKind::INCREMENTAL,
         )),
         completion_provider: Some(CompletionOptions {
             resolve_provider: Some(false),
@@ -13,12 +16,13 @@ pub fn create_server_capabilities() -> ServerCapabilities {
             completion_item: None,
         }),
         hover_provider: Some(HoverProviderCapability::Simple(true)),
+        inlay_hint_provider: Some(OneOf::Left(true)),
         definition_provider: Some(OneOf::Left(true)),
         references_provider: Some(OneOf::Left(true)),
         document_symbol_provider: Some(OneOf::Left(true)),
         semantic_tokens_provider: Some(
             SemanticTokensServerCapabilities::SemanticTokensOptions(SemanticTokensOptions {
-                legend: create_semantic_tokens_legend(),
+                legend: semantic_tokens_legend(),
                 range: Some(true),
                 full: Some(SemanticTokensFullOptions::Bool(true)),
                 ..Default::default()
@@ -35,19 +39,3 @@ pub fn create_server_capabilities() -> ServerCapabilities {
         ..Default::default()
     }
 }
-
-fn create_semantic_tokens_legend() -> SemanticTokensLegend {
-    SemanticTokensLegend {
-        token_types: vec![
-            SemanticTokenType::KEYWORD,      // instructions
-            SemanticTokenType::FUNCTION,     // labels
-            SemanticTokenType::NUMBER,       // numbers
-            SemanticTokenType::OPERATOR,     // operators (#, [, ])
-            SemanticTokenType::COMMENT,      // comments
-            SemanticTokenType::STRING,       // strings
-        ],
-        token_modifiers: vec![
-            SemanticTokenModifier::DEPRECATED, // not used currently
-        ],
-    }
-}
@@ -1,51 +1,94 @@
+use crate::analysis::utils::{PositionConverter, PositionEncoding};
 use crate::analysis::{DocumentState, SemanticAnalyzer};
 use dashmap::DashMap;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use tower_lsp::jsonrpc::Result as LspResult;
 use tower_lsp::lsp_types::*;
 
+/// request handlers translate positions between the negotiated client encoding
+/// and the char columns used by the analyzer
 #[derive(Debug)]
 pub struct RequestHandlers {
-    analyzer: Arc<SemanticAnalyzer>,
+    analyzer: Arc<RwLock<Arc<SemanticAnalyzer>>>,
     documents: Arc<DashMap<Url, DocumentState>>,
+    position_encoding: Arc<RwLock<PositionEncoding>>,
 }
 
 impl RequestHandlers {
     pub fn new(
-        analyzer: Arc<SemanticAnalyzer>,
+        analyzer: Arc<RwLock<Arc<SemanticAnalyzer>>>,
         documents: Arc<DashMap<Url, DocumentState>>,
+        position_encoding: Arc<RwLock<PositionEncoding>>,
     ) -> Self {
         Self {
             analyzer,
             documents,
+            position_encoding,
         }
     }
 
+    /// analyzer for the current workspace configuration
+    fn analyzer(&self) -> Arc<SemanticAnalyzer> {
+        self.analyzer.read().unwrap().clone()
+    }
+
+    fn converter<'a>(&self, content: &'a str) -> PositionConverter<'a> {
+        PositionConverter::new(content, *self.position_encoding.read().unwrap())
+    }
+
     // Completion
     pub async fn handle_completion(
         &self,
         params: CompletionParams,
     ) -> LspResult<Option<CompletionResponse>> {
         let uri = &params.text_document_position.text_document.uri;
-        let position = params.text_document_position.position;
 
         let document = match self.documents.get(uri) {
             Some(doc) => doc,
             None => return Ok(None),
         };
 
-        let completions = self.analyzer.get_completions(&document.content, position);
+        let converter = self.converter(&document.content);
+        let position = converter.to_internal_position(params.text_document_position.position);
+
+        let completions = self.analyzer().get_completions(&document.content, position);
         Ok(Some(CompletionResponse::Array(completions)))
     }
 
     // Hover
     pub async fn handle_hover(&self, params: HoverParams) -> LspResult<Option<Hover>> {
         let uri = &params.text_document_position_params.text_document.uri;
-        let position = params.text_document_position_params.position;
 
         if let Some(document) = self.documents.get(uri) {
-            let hover_info = self.analyzer.get_hover_info(&document.content, position);
-            return Ok(hover_info);
+            let converter = self.converter(&document.content);
+            let position =
+                converter.to_internal_position(params.text_document_position_params.position);
+
+            let hover_info = self.analyzer().get_hover_info(&document.content, position);
+            return Ok(hover_info.map(|hover| converter.to_lsp_hover(hover)));
+        }
+
+        Ok(None)
+    }
+
+    // Inlay hints
+    pub async fn handle_inlay_hint(
+        &self,
+        params: InlayHintParams,
+    ) -> LspResult<Option<Vec<InlayHint>>> {
+        let uri = &params.text_document.uri;
+
+        if let Some(document) = self.documents.get(uri) {
+            let converter = self.converter(&document.content);
+            let range = converter.to_internal_range(params.range);
+
+            let hints = self
+                .analyzer()
+                .get_inlay_hints(&document.content, range)
+                .into_iter()
+                .map(|hint| converter.to_lsp_inlay_hint(hint))
+                .collect();
+            return Ok(Some(hints));
         }
 
         Ok(None)
@@ -57,12 +100,31 @@ impl RequestHandlers {
         params: GotoDefinitionParams,
     ) -> LspResult<Option<GotoDefinitionResponse>> {
         let uri = &params.text_document_position_params.text_document.uri;
-        let position = params.text_document_position_params.position;
 
         if let Some(document) = self.documents.get(uri) {
+            let converter = self.converter(&document.content);
+            let position =
+                converter.to_internal_position(params.text_document_position_params.position);
+
+            // explain instead of silently jumping to one of several definitions
+            self.analyzer()
+                .check_label_ambiguity(&document.content, position)?;
+
             let definition = self
-                .analyzer
-                .get_definition(&document.content, position, uri);
+                .analyzer()
+                .get_definition(&document.content, position, uri)
+                .map(|definition| match definition {
+                    GotoDefinitionResponse::Scalar(location) => {
+                        GotoDefinitionResponse::Scalar(converter.to_lsp_location(location))
+                    }
+                    GotoDefinitionResponse::Array(locations) => GotoDefinitionResponse::Array(
+                        locations
+                            .into_iter()
+                            .map(|location| converter.to_lsp_location(location))
+                            .collect(),
+                    ),
+                    links => links,
+                });
             return Ok(definition);
         }
 
@@ -75,15 +137,22 @@ impl RequestHandlers {
         params: ReferenceParams,
     ) -> LspResult<Option<Vec<Location>>> {
         let uri = &params.text_document_position.text_document.uri;
-        let position = params.text_document_position.position;
 
         if let Some(document) = self.documents.get(uri) {
-            let references = self.analyzer.find_references(
-                &document.content,
-                position,
-                uri,
-                params.context.include_declaration,
-            );
+            let converter = self.converter(&document.content);
+            let position = converter.to_internal_position(params.text_document_position.position);
+
+            let references = self
+                .analyzer()
+                .find_references(
+                    &document.content,
+                    position,
+                    uri,
+                    params.context.include_declaration,
+                )
+                .into_iter()
+                .map(|location| converter.to_lsp_location(location))
+                .collect();
             return Ok(Some(references));
         }
 
@@ -98,7 +167,11 @@ impl RequestHandlers {
         let uri = &params.text_document.uri;
 
         if let Some(document) = self.documents.get(uri) {
-            let symbols = self.analyzer.get_document_symbols(&document.content);
+            let converter = self.converter(&document.content);
+            let mut symbols = self.analyzer().get_document_symbols(&document.content);
+            for symbol in symbols.iter_mut() {
+                symbol.location.range = converter.to_lsp_range(symbol.location.range);
+            }
             return Ok(Some(DocumentSymbolResponse::Flat(symbols)));
         }
 
@@ -116,12 +189,17 @@ impl RequestHandlers {
         for document_ref in self.documents.iter() {
             let (uri_key, document) = document_ref.pair();
             let uri = uri_key.clone();
+            let converter = self.converter(&document.content);
 
-            let mut symbols = self.analyzer.get_document_symbols(&document.content);
+            let mut symbols = self.analyzer().get_document_symbols(&document.content);
 
-            self.analyzer
+            self.analyzer()
                 .filter_workspace_symbols(&mut symbols, query, &uri);
 
+            for symbol in symbols.iter_mut() {
+                symbol.location.range = converter.to_lsp_range(symbol.location.range);
+            }
+
             all_symbols.extend(symbols);
         }
 
@@ -136,10 +214,11 @@ impl RequestHandlers {
         let uri = &params.text_document.uri;
 
         if let Some(document) = self.documents.get(uri) {
-            let tokens = self.analyzer.get_semantic_tokens(&document.content);
+            let converter = self.converter(&document.content);
+            let tokens = self.analyzer().get_semantic_tokens(&document.content);
             return Ok(Some(SemanticTokensResult::Tokens(SemanticTokens {
                 result_id: None,
-                data: tokens,
+                data: converter.to_lsp_semantic_tokens(tokens),
             })));
         }
 
@@ -152,12 +231,26 @@ impl RequestHandlers {
         params: CodeActionParams,
     ) -> LspResult<Option<CodeActionResponse>> {
         let uri = &params.text_document.uri;
-        let range = params.range;
 
         if let Some(document) = self.documents.get(uri) {
-            let actions =
-                self.analyzer
-                    .get_code_actions(&document.content, range, uri, &params.context);
+            let converter = self.converter(&document.content);
+            let range = converter.to_internal_range(params.range);
+            let context = CodeActionContext {
+                diagnostics: params
+                    .context
+                    .diagnostics
+                    .iter()
+                    .map(|diagnostic| converter.to_internal_diagnostic(diagnostic))
+                    .collect(),
+                ..params.context.clone()
+            };
+
+            let actions = self
+                .analyzer()
+                .get_code_actions(&document.content, range, uri, &context)
+                .into_iter()
+                .map(|action| converter.to_lsp_code_action(action))
+                .collect();
             return Ok(Some(actions));
         }
 
@@ -170,11 +263,14 @@ impl RequestHandlers {
         params: SignatureHelpParams,
     ) -> LspResult<Option<SignatureHelp>> {
         let uri = &params.text_document_position_params.text_document.uri;
-        let position = params.text_document_position_params.position;
 
         if let Some(document) = self.documents.get(uri) {
+            let converter = self.converter(&document.content);
+            let position =
+                converter.to_internal_position(params.text_document_position_params.position);
+
             let signature = self
-                .analyzer
+                .analyzer()
                 .get_signature_help(&document.content, position);
             return Ok(signature);
         }
@@ -188,11 +284,16 @@ impl RequestHandlers {
         params: TextDocumentPositionParams,
     ) -> LspResult<Option<PrepareRenameResponse>> {
         let uri = &params.text_document.uri;
-        let position = params.position;
 
         if let Some(document) = self.documents.get(uri) {
-            let range = self.analyzer.get_rename_range(&document.content, position);
-            return Ok(range.map(PrepareRenameResponse::Range));
+            let converter = self.converter(&document.content);
+            let position = converter.to_internal_position(params.position);
+
+            self.analyzer()
+                .check_rename_target(&document.content, position)?;
+
+            let range = self.analyzer().get_rename_range(&document.content, position);
+            return Ok(range.map(|range| PrepareRenameResponse::Range(converter.to_lsp_range(range))));
         }
 
         Ok(None)
@@ -201,14 +302,19 @@ impl RequestHandlers {
     // Rename
     pub async fn handle_rename(&self, params: RenameParams) -> LspResult<Option<WorkspaceEdit>> {
         let uri = &params.text_document_position.text_document.uri;
-        let position = params.text_document_position.position;
         let new_name = &params.new_name;
 
         if let Some(document) = self.documents.get(uri) {
+            let converter = self.converter(&document.content);
+            let position = converter.to_internal_position(params.text_document_position.position);
+
+            self.analyzer()
+                .check_rename_target(&document.content, position)?;
+
             let edit = self
-                .analyzer
+                .analyzer()
                 .rename_symbol(&document.content, position, new_name, uri);
-            return Ok(edit);
+            return Ok(edit.map(|edit| converter.to_lsp_workspace_edit(edit)));
         }
 
         Ok(None)
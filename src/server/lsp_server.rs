@@ -1,36 +1,67 @@
 use dashmap::DashMap;
-use std::sync::Arc;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
 use tower_lsp::jsonrpc::Result as LspResult;
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer};
 
+use crate::analysis::core::{WorkspaceConfig, CONFIG_FILE_NAMES};
+use crate::analysis::utils::{PositionConverter, PositionEncoding};
 use crate::analysis::{DocumentState, SemanticAnalyzer};
 use crate::server::{capabilities::create_server_capabilities, handlers::RequestHandlers};
 
 #[derive(Debug)]
 pub struct AsmodeusLanguageServer {
     client: Client,
-    analyzer: Arc<SemanticAnalyzer>,
+    /// replaced whenever the workspace configuration changes
+    analyzer: Arc<RwLock<Arc<SemanticAnalyzer>>>,
     documents: Arc<DashMap<Url, DocumentState>>,
+    position_encoding: Arc<RwLock<PositionEncoding>>,
     handlers: RequestHandlers,
+    workspace_root: RwLock<Option<PathBuf>>,
+    /// client can register file watchers for the configuration files
+    watch_config: AtomicBool,
 }
 
 impl AsmodeusLanguageServer {
     pub fn new(client: Client) -> Self {
-        let analyzer = Arc::new(SemanticAnalyzer::new());
+        let analyzer = Arc::new(RwLock::new(Arc::new(SemanticAnalyzer::new())));
         let documents = Arc::new(DashMap::new());
-        let handlers = RequestHandlers::new(analyzer.clone(), documents.clone());
+        let position_encoding = Arc::new(RwLock::new(PositionEncoding::default()));
+        let handlers = RequestHandlers::new(
+            analyzer.clone(),
+            documents.clone(),
+            position_encoding.clone(),
+        );
 
         Self {
             client,
             analyzer,
             documents,
+            position_encoding,
             handlers,
+            workspace_root: RwLock::new(None),
+            watch_config: AtomicBool::new(false),
         }
     }
 
+    fn analyzer(&self) -> Arc<SemanticAnalyzer> {
+        self.analyzer.read().unwrap().clone()
+    }
+
+    fn position_encoding(&self) -> PositionEncoding {
+        *self.position_encoding.read().unwrap()
+    }
+
     async fn analyze_and_publish_diagnostics(&self, uri: &Url, content: &str) {
-        let diagnostics = self.analyzer.analyze_document(content, uri);
+        let converter = PositionConverter::new(content, self.position_encoding());
+        let diagnostics: Vec<Diagnostic> = self
+            .analyzer()
+            .analyze_document(content, uri)
+            .into_iter()
+            .map(|diagnostic| converter.to_lsp_diagnostic(diagnostic))
+            .collect();
 
         if let Some(document) = self.documents.get(uri) {
             if document.get_uri() != uri {
@@ -46,18 +77,117 @@ impl AsmodeusLanguageServer {
             .publish_diagnostics(uri.clone(), diagnostics, None)
             .await;
     }
+
+    /// rebuilds the analyzer from the configuration file in the workspace
+    /// root; a broken file keeps the previous configuration
+    async fn reload_config(&self) {
+        let root = self.workspace_root.read().unwrap().clone();
+        let Some(path) = root.as_deref().and_then(WorkspaceConfig::find) else {
+            *self.analyzer.write().unwrap() = Arc::new(SemanticAnalyzer::new());
+            return;
+        };
+
+        let analyzer = WorkspaceConfig::from_file(&path).and_then(|config| {
+            let instruction_db = config.instruction_database()?;
+            Ok(SemanticAnalyzer::with_config(config).with_instruction_db(instruction_db))
+        });
+        match analyzer {
+            Ok(analyzer) => {
+                *self.analyzer.write().unwrap() = Arc::new(analyzer);
+                self.client
+                    .log_message(
+                        MessageType::INFO,
+                        format!("Loaded configuration from {}", path.display()),
+                    )
+                    .await;
+            }
+            Err(error) => {
+                self.client
+                    .show_message(
+                        MessageType::WARNING,
+                        format!("{}: {}", path.display(), error),
+                    )
+                    .await;
+            }
+        }
+    }
+
+    /// analyzes every open document again, e.g. after a configuration change
+    async fn republish_all_diagnostics(&self) {
+        let open: Vec<(Url, String)> = self
+            .documents
+            .iter()
+            .map(|document| (document.key().clone(), document.content.clone()))
+            .collect();
+
+        for (uri, content) in open {
+            self.analyze_and_publish_diagnostics(&uri, &content).await;
+        }
+    }
+}
+
+/// first workspace folder, or the root URI of clients without folders
+fn workspace_root(params: &InitializeParams) -> Option<PathBuf> {
+    #[allow(deprecated)]
+    let root_uri = params.root_uri.as_ref();
+    params
+        .workspace_folders
+        .as_ref()
+        .and_then(|folders| folders.first())
+        .map(|folder| &folder.uri)
+        .or(root_uri)
+        .and_then(|uri| uri.to_file_path().ok())
 }
 
 #[tower_lsp::async_trait]
 impl LanguageServer for AsmodeusLanguageServer {
-    async fn initialize(&self, _: InitializeParams) -> LspResult<InitializeResult> {
+    async fn initialize(&self, params: InitializeParams) -> LspResult<InitializeResult> {
+        let watch_config = params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.did_change_watched_files)
+            .and_then(|watched| watched.dynamic_registration)
+            .unwrap_or(false);
+        self.watch_config.store(watch_config, Ordering::Relaxed);
+        *self.workspace_root.write().unwrap() = workspace_root(&params);
+        self.reload_config().await;
+
+        let client_encodings = params
+            .capabilities
+            .general
+            .and_then(|general| general.position_encodings);
+        let encoding = PositionEncoding::negotiate(client_encodings.as_deref());
+        *self.position_encoding.write().unwrap() = encoding;
+
         Ok(InitializeResult {
-            capabilities: create_server_capabilities(),
+            capabilities: create_server_capabilities(encoding),
             ..Default::default()
         })
     }
 
     async fn initialized(&self, _: InitializedParams) {
+        if self.watch_config.load(Ordering::Relaxed) {
+            let watchers = CONFIG_FILE_NAMES
+                .iter()
+                .map(|name| FileSystemWatcher {
+                    glob_pattern: GlobPattern::String(format!("**/{}", name)),
+                    kind: None,
+                })
+                .collect();
+            let registration = Registration {
+                id: "asmodeus-lsp-config".to_string(),
+                method: "workspace/didChangeWatchedFiles".to_string(),
+                register_options: serde_json::to_value(DidChangeWatchedFilesRegistrationOptions {
+                    watchers,
+                })
+                .ok(),
+            };
+            if let Err(error) = self.client.register_capability(vec![registration]).await {
+                tracing::warn!("Could not watch configuration files: {}", error);
+            }
+        }
+
         self.client
             .log_message(MessageType::INFO, "Asmodeus LSP Server initialized!")
             .await;
@@ -74,7 +204,8 @@ impl LanguageServer for AsmodeusLanguageServer {
         let version = params.text_document.version;
 
         // save document
-        let document = DocumentState::new(uri.clone(), content.clone(), version);
+        let document = DocumentState::new(uri.clone(), content.clone(), version)
+            .with_position_encoding(self.position_encoding());
         self.documents.insert(uri.clone(), document);
 
         self.analyze_and_publish_diagnostics(&uri, &content).await;
@@ -88,16 +219,19 @@ impl LanguageServer for AsmodeusLanguageServer {
         let uri = params.text_document.uri;
         let version = params.text_document.version;
 
-        if let Some(change) = params.content_changes.into_iter().next() {
-            let content = change.text;
-
-            // update document
-            if let Some(mut document) = self.documents.get_mut(&uri) {
-                document.update_content(content.clone(), version);
+        // apply edits in order, release the lock before analysis
+        let content = match self.documents.get_mut(&uri) {
+            Some(mut document) => {
+                document.apply_changes(params.content_changes, version);
+                document.content.clone()
             }
+            None => {
+                tracing::warn!("Received changes for unknown document: {}", uri);
+                return;
+            }
+        };
 
-            self.analyze_and_publish_diagnostics(&uri, &content).await;
-        }
+        self.analyze_and_publish_diagnostics(&uri, &content).await;
     }
 
     async fn did_save(&self, params: DidSaveTextDocumentParams) {
@@ -109,6 +243,20 @@ impl LanguageServer for AsmodeusLanguageServer {
             .await;
     }
 
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        let root = self.workspace_root.read().unwrap().clone();
+        let config_changed = params.changes.iter().any(|change| {
+            change.uri.to_file_path().is_ok_and(|path| {
+                WorkspaceConfig::is_config_file(&path) && path.parent() == root.as_deref()
+            })
+        });
+
+        if config_changed {
+            self.reload_config().await;
+            self.republish_all_diagnostics().await;
+        }
+    }
+
     // Language features - handlers delegation
     async fn completion(&self, params: CompletionParams) -> LspResult<Option<CompletionResponse>> {
         self.handlers.handle_completion(params).await
@@ -118,6 +266,10 @@ impl LanguageServer for AsmodeusLanguageServer {
         self.handlers.handle_hover(params).await
     }
 
+    async fn inlay_hint(&self, params: InlayHintParams) -> LspResult<Option<Vec<InlayHint>>> {
+        self.handlers.handle_inlay_hint(params).await
+    }
+
     async fn goto_definition(
         &self,
         params: GotoDefinitionParams,
@@ -9,30 +9,30 @@ async fn test_complete_asmodeus_program() {
 
 main:
     POB #0          ; Load first number
-    ŁAD #100        ; Store as fib_a address
+    ŁAD 100         ; Store as fib_a address
     POB #1          ; Load second number  
-    ŁAD #101        ; Store as fib_b address
+    ŁAD 101         ; Store as fib_b address
     POB #10         ; Load counter
-    ŁAD #102        ; Store counter address
+    ŁAD 102         ; Store counter address
 
 loop:
-    POB #102        ; Load counter
+    POB 102         ; Load counter
     SOZ end         ; If zero, end program
     
-    POB #100        ; Load fib_a
-    DOD #101        ; Add fib_b
-    ŁAD #103        ; Store temp
+    POB 100         ; Load fib_a
+    DOD 101         ; Add fib_b
+    ŁAD 103         ; Store temp
     
-    POB #101        ; Load fib_b
-    ŁAD #100        ; Store as new fib_a
+    POB 101         ; Load fib_b
+    ŁAD 100         ; Store as new fib_a
     
-    POB #103        ; Load temp
-    ŁAD #101        ; Store as new fib_b
+    POB 103         ; Load temp
+    ŁAD 101         ; Store as new fib_b
     WYJSCIE         ; Output result
     
-    POB #102        ; Load counter
+    POB 102         ; Load counter
     ODE #1          ; Subtract 1
-    ŁAD #102        ; Store counter
+    ŁAD 102         ; Store counter
     
     SOB loop        ; Jump back to loop
 
@@ -79,7 +79,7 @@ end:
         &uri,
         true,
     );
-    assert_eq!(loop_references.len(), 3); // definition + reference from SOB loop
+    assert_eq!(loop_references.len(), 2); // definition + reference from SOB loop
 
     // hover on different elements
     let hover_instruction = analyzer.get_hover_info(
@@ -167,6 +167,7 @@ start:
         },
         severity: Some(DiagnosticSeverity::ERROR),
         message: "Unknown instruction: 'DOX'".to_string(),
+        code: Some(NumberOrString::String("SEM001".to_string())),
         source: Some("asmodeus-lsp".to_string()),
         ..Default::default()
     };
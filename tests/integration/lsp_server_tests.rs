@@ -72,7 +72,14 @@ end:
     
     // 1. analyze document
     let diagnostics = analyzer.analyze_document(content, &uri);
-    assert!(diagnostics.is_empty());
+    // the loop never exits, so the STP after it is unreachable and 'end' unused
+    assert_eq!(diagnostics.len(), 3);
+    assert_eq!(diagnostics[0].code, Some(NumberOrString::String("SEM037".to_string())));
+    assert_eq!(diagnostics[0].range.start.line, 5);
+    assert_eq!(diagnostics[1].code, Some(NumberOrString::String("SEM016".to_string())));
+    assert_eq!(diagnostics[1].range.start.line, 6);
+    assert_eq!(diagnostics[2].code, Some(NumberOrString::String("SEM031".to_string())));
+    assert_eq!(diagnostics[2].range.start.line, 4);
     
     // 2. get completions
     let completions = analyzer.get_completions(content, Position { line: 1, character: 4 });
@@ -1,4 +1,4 @@
-use asmodeus_lsp::analysis::utils::{create_diagnostic, position_to_range};
+use asmodeus_lsp::analysis::utils::{create_diagnostic, position_to_range, DiagnosticCode};
 use tower_lsp::lsp_types::*;
 
 pub fn test_uri(filename: &str) -> Url {
@@ -13,8 +13,8 @@ pub fn test_range(start_line: u32, start_char: u32, end_char: u32) -> Range {
     position_to_range(start_line, start_char, end_char)
 }
 
-pub fn test_diagnostic(range: Range, severity: DiagnosticSeverity, message: &str) -> Diagnostic {
-    create_diagnostic(range, severity, "TEST001", message.to_string())
+pub fn test_diagnostic(range: Range, code: DiagnosticCode, message: &str) -> Diagnostic {
+    create_diagnostic(range, code, message.to_string())
 }
 
 pub const SAMPLE_VALID_PROGRAM: &str = r#"
@@ -6,7 +6,6 @@ use tower_lsp::lsp_types::*;
 fn test_semantic_analyzer_creation() {
     let _analyzer = SemanticAnalyzer::new();
     // can be created?
-    assert!(true);
 }
 
 #[test]
@@ -0,0 +1,296 @@
+use asmodeus_lsp::analysis::core::{LabelNaming, SeverityLevel, WorkspaceConfig};
+use asmodeus_lsp::analysis::flow::CheckLevel;
+use asmodeus_lsp::analysis::utils::{
+    create_diagnostic, position_to_range, DiagnosticCode, IsaProfile, MemoryModel,
+};
+use asmodeus_lsp::analysis::SemanticAnalyzer;
+use std::path::Path;
+use tower_lsp::lsp_types::*;
+
+fn diagnostic(code: DiagnosticCode) -> Diagnostic {
+    create_diagnostic(
+        position_to_range(0, 0, 3),
+        code,
+        code.title().to_string())
+}
+
+fn codes(config: WorkspaceConfig, content: &str) -> Vec<DiagnosticCode> {
+    let uri = Url::parse("file:///test.asmod").unwrap();
+    SemanticAnalyzer::with_config(config)
+        .analyze_document(content, &uri)
+        .iter()
+        .filter_map(DiagnosticCode::of)
+        .collect()
+}
+
+#[test]
+fn test_parse_toml_config() {
+    let config = WorkspaceConfig::from_toml(
+        r#"
+isa = "base"
+
+[severity]
+SEM037 = "off"
+SEM016 = "info"
+
+[rules]
+dataflow = false
+
+[memory]
+word-bits = 12
+
+[labels]
+naming = "snake_case"
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(config.isa, IsaProfile::Base);
+    assert_eq!(config.severity.get("SEM037"), Some(&SeverityLevel::Off));
+    assert_eq!(config.severity.get("SEM016"), Some(&SeverityLevel::Information));
+    assert!(config.rules.style && !config.rules.dataflow && config.rules.interrupts);
+    assert_eq!(config.memory_model(), MemoryModel::new(12, 8));
+    assert_eq!(config.labels.naming, LabelNaming::SnakeCase);
+}
+
+#[test]
+fn test_parse_json_config() {
+    let config = WorkspaceConfig::from_json(
+        r#"{ "severity": { "SEM032": "error" }, "labels": { "naming": "camelCase" } }"#,
+    )
+    .unwrap();
+
+    assert_eq!(config.severity.get("SEM032"), Some(&SeverityLevel::Error));
+    assert_eq!(config.labels.naming, LabelNaming::CamelCase);
+    assert_eq!(config.isa, IsaProfile::Extended);
+    assert_eq!(WorkspaceConfig::from_json("{}").unwrap(), WorkspaceConfig::default());
+}
+
+#[test]
+fn test_invalid_config_is_rejected() {
+    assert!(WorkspaceConfig::from_toml("[severity]\nSEM999 = \"off\"").is_err());
+    assert!(WorkspaceConfig::from_toml("[severity]\nSEM037 = \"loud\"").is_err());
+    assert!(WorkspaceConfig::from_toml("unknown-key = 1").is_err());
+    assert!(WorkspaceConfig::from_json("{ \"rules\": { \"style\": \"yes\" } }").is_err());
+    assert!(WorkspaceConfig::from_toml("[interrupts]\nlines = 0").is_err());
+    assert!(WorkspaceConfig::from_toml("[layout]\nstore-into-code = \"hint\"").is_err());
+}
+
+#[test]
+fn test_apply_severities_and_groups() {
+    let config = WorkspaceConfig::from_toml(
+        "[severity]\nSEM016 = \"error\"\nSEM001 = \"off\"\n[rules]\ninterrupts = false",
+    )
+    .unwrap();
+
+    let applied = config.apply(vec![
+        diagnostic(DiagnosticCode::Sem016),
+        diagnostic(DiagnosticCode::Sem001),
+        diagnostic(DiagnosticCode::Sem027),
+        diagnostic(DiagnosticCode::Sem037),
+    ]);
+
+    assert_eq!(applied.len(), 2);
+    assert_eq!(DiagnosticCode::of(&applied[0]), Some(DiagnosticCode::Sem016));
+    assert_eq!(applied[0].severity, Some(DiagnosticSeverity::ERROR));
+    assert_eq!(DiagnosticCode::of(&applied[1]), Some(DiagnosticCode::Sem037));
+    assert_eq!(applied[1].severity, Some(DiagnosticSeverity::HINT));
+}
+
+#[test]
+fn test_config_drives_analysis() {
+    let content = "start: POB a\n       MNO a\n       STP\na: RST 2\nmyCell: RST 0\n";
+
+    let default_codes = codes(WorkspaceConfig::default(), content);
+    assert_eq!(default_codes, vec![DiagnosticCode::Sem037]);
+
+    let config = WorkspaceConfig::from_toml(
+        "isa = \"base\"\n[labels]\nnaming = \"snake_case\"",
+    )
+    .unwrap();
+    let strict = codes(config, content);
+    assert!(strict.contains(&DiagnosticCode::Sem039));
+    assert!(strict.contains(&DiagnosticCode::Sem040));
+
+    let config = WorkspaceConfig::from_toml("[rules]\nstyle = false").unwrap();
+    assert!(codes(config, content).is_empty());
+
+    let config = WorkspaceConfig::from_toml("[labels]\nexported = [\"myCell\"]").unwrap();
+    assert!(codes(config, content).is_empty());
+}
+
+#[test]
+fn test_interrupt_config() {
+    let content = "    MSK #0b10000\n    STP\ntick:\n    POB #1\n    PWR";
+
+    // without the handler declared, tick is dead code nothing refers to
+    let default_codes = codes(WorkspaceConfig::default(), content);
+    assert_eq!(default_codes, vec![DiagnosticCode::Sem037, DiagnosticCode::Sem016]);
+
+    // the configured handler is entered by the machine, and the mask sets a
+    // bit past the last of 4 lines
+    let config =
+        WorkspaceConfig::from_toml("[interrupts]\nlines = 4\nhandlers = [\"tick\"]").unwrap();
+    assert_eq!(config.interrupts.handlers, vec!["tick"]);
+    assert_eq!(codes(config, content), vec![DiagnosticCode::Sem029]);
+}
+
+#[test]
+fn test_layout_config() {
+    let uri = Url::parse("file:///test.asmod").unwrap();
+    let content = "start:\n    POB #1\n    ŁAD start\nx: RST 0";
+    let config = WorkspaceConfig::from_toml(
+        "[layout]\nstore-into-code = \"error\"\nfall-into-data = \"off\"",
+    )
+    .unwrap();
+    assert_eq!(config.layout.store_into_code, CheckLevel::Error);
+    assert_eq!(config.layout.jump_into_data, CheckLevel::Error);
+
+    // the store into code is an error, running into x is not reported
+    let diagnostics = SemanticAnalyzer::with_config(config).analyze_document(content, &uri);
+    let store = diagnostics
+        .iter()
+        .find(|d| DiagnosticCode::of(d) == Some(DiagnosticCode::Sem034))
+        .unwrap();
+    assert_eq!(store.severity, Some(DiagnosticSeverity::ERROR));
+    assert!(!diagnostics.iter().any(|d| DiagnosticCode::of(d) == Some(DiagnosticCode::Sem036)));
+}
+
+#[test]
+fn test_label_naming_policies() {
+    assert!(LabelNaming::SnakeCase.accepts("loop_2"));
+    assert!(LabelNaming::SnakeCase.accepts("_pętla"));
+    assert!(!LabelNaming::SnakeCase.accepts("Loop"));
+    assert!(LabelNaming::CamelCase.accepts("loopEnd"));
+    assert!(!LabelNaming::CamelCase.accepts("loop_end"));
+    assert!(LabelNaming::ScreamingSnakeCase.accepts("LOOP_END"));
+    assert!(LabelNaming::Any.accepts("Whatever_Goes"));
+}
+
+#[test]
+fn test_find_config_file() {
+    let root = tempfile::tempdir().unwrap();
+    assert_eq!(WorkspaceConfig::find(root.path()), None);
+
+    let json = root.path().join(".asmodeus-lsp.json");
+    std::fs::write(&json, r#"{ "isa": "base" }"#).unwrap();
+    assert_eq!(WorkspaceConfig::find(root.path()), Some(json.clone()));
+    assert_eq!(WorkspaceConfig::from_file(&json).unwrap().isa, IsaProfile::Base);
+
+    // TOML wins over JSON
+    let toml = root.path().join(".asmodeus-lsp.toml");
+    std::fs::write(&toml, "[memory]\naddress-bits = 10\n").unwrap();
+    assert_eq!(WorkspaceConfig::find(root.path()), Some(toml.clone()));
+    assert_eq!(WorkspaceConfig::from_file(&toml).unwrap().memory.address_bits, 10);
+
+    assert!(WorkspaceConfig::is_config_file(&toml));
+    assert!(!WorkspaceConfig::is_config_file(Path::new("/work/asmodeus-lsp.toml")));
+}
+
+#[test]
+fn test_base_isa_profile() {
+    let config = WorkspaceConfig::from_toml("isa = \"base\"").unwrap();
+    assert_eq!(config.isa, IsaProfile::Base);
+    assert!(WorkspaceConfig::from_toml("isa = \"risc\"").is_err());
+
+    let analyzer = SemanticAnalyzer::with_config(config);
+    let completions = analyzer.get_completions("", Position { line: 0, character: 0 });
+    let labels: Vec<&str> = completions.iter().map(|c| c.label.as_str()).collect();
+    assert!(labels.contains(&"DOD"));
+    assert!(!labels.iter().any(|label| label.starts_with("MNO") || label.starts_with("DZI")));
+
+    let extended = SemanticAnalyzer::new().get_completions("", Position { line: 0, character: 0 });
+    assert!(extended.iter().any(|c| c.label.starts_with("MNO")));
+}
+
+#[test]
+fn test_custom_instruction_set() {
+    let root = tempfile::tempdir().unwrap();
+    std::fs::create_dir(root.path().join("isa")).unwrap();
+    std::fs::write(
+        root.path().join("isa/lab.toml"),
+        r#"
+[[instruction]]
+mnemonic = "INC"
+operand = "address"
+category = "arithmetic"
+description = "Increment a memory cell"
+operation = "(address) + 1 → (address)"
+
+[[instruction]]
+mnemonic = "STP"
+operand = "none"
+category = "control-flow"
+description = "Stop program execution"
+operation = "Halt execution"
+"#,
+    )
+    .unwrap();
+    let path = root.path().join(".asmodeus-lsp.toml");
+    std::fs::write(&path, "instruction-set = \"isa/lab.toml\"\n").unwrap();
+
+    let config = WorkspaceConfig::from_file(&path).unwrap();
+    assert_eq!(config.instruction_set, Some(root.path().join("isa/lab.toml")));
+    let instruction_db = config.instruction_database().unwrap();
+    let analyzer = SemanticAnalyzer::with_config(config).with_instruction_db(instruction_db);
+
+    let content = "start:\n    INC licznik\n    STP\nlicznik: RST 0";
+    let uri = Url::parse("file:///test.asm").unwrap();
+    let diagnostics = analyzer.analyze_document(content, &uri);
+    assert!(
+        !diagnostics.iter().any(|d| DiagnosticCode::of(d) == Some(DiagnosticCode::Sem002)),
+        "{:?}",
+        diagnostics
+    );
+
+    let help = analyzer
+        .get_signature_help("    INC ", Position { line: 0, character: 8 })
+        .unwrap();
+    assert_eq!(help.signatures[0].label, "INC address");
+
+    // a missing instruction set file is an error
+    std::fs::write(&path, "instruction-set = \"missing.toml\"\n").unwrap();
+    let config = WorkspaceConfig::from_file(&path).unwrap();
+    assert!(config.instruction_database().is_err());
+}
+
+#[test]
+fn test_mnemonic_spelling() {
+    let content =
+        "start: pob a\n       LAD b\n       WEJŚCIE\n       DOD b\n       STP\na: RST 5\nb: RPA";
+    let uri = Url::parse("file:///test.asm").unwrap();
+    let codes = |analyzer: &SemanticAnalyzer| -> Vec<DiagnosticCode> {
+        analyzer
+            .analyze_document(content, &uri)
+            .iter()
+            .filter_map(DiagnosticCode::of)
+            .collect()
+    };
+
+    // aliases and lowercase are accepted as they are by default
+    assert_eq!(codes(&SemanticAnalyzer::new()), vec![]);
+
+    let config = WorkspaceConfig::from_toml("[mnemonics]\nnormalize = true\n").unwrap();
+    let normalized = codes(&SemanticAnalyzer::with_config(config));
+    assert_eq!(normalized, vec![DiagnosticCode::Sem041; 3]);
+
+    let config = WorkspaceConfig::from_toml("[mnemonics]\ncase-sensitive = true\n").unwrap();
+    let strict = codes(&SemanticAnalyzer::with_config(config));
+    assert!(strict.contains(&DiagnosticCode::Sem001));
+    assert!(!strict.contains(&DiagnosticCode::Sem041));
+
+    assert!(WorkspaceConfig::from_toml("[mnemonics]\nuppercase = true\n").is_err());
+}
+
+#[test]
+fn test_suppressing_disabled_codes() {
+    let content = "; asmod-disable-file SEM036 SEM037\nstart:\n    POB #1\nspare: RST 0";
+    assert!(codes(WorkspaceConfig::default(), content).is_empty());
+
+    // the config already drops both codes, the pragma is not reported unused
+    let config = WorkspaceConfig::from_toml(
+        "[rules]\nstyle = false\n\n[layout]\nfall-into-data = \"off\"",
+    )
+    .unwrap();
+    assert!(codes(config, content).is_empty());
+}
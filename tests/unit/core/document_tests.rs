@@ -0,0 +1,99 @@
+use asmodeus_lsp::analysis::DocumentState;
+use tower_lsp::lsp_types::*;
+
+fn ranged_change(
+    start_line: u32,
+    start_char: u32,
+    end_line: u32,
+    end_char: u32,
+    text: &str,
+) -> TextDocumentContentChangeEvent {
+    TextDocumentContentChangeEvent {
+        range: Some(Range {
+            start: Position { line: start_line, character: start_char },
+            end: Position { line: end_line, character: end_char },
+        }),
+        range_length: None,
+        text: text.to_string(),
+    }
+}
+
+fn test_document(content: &str) -> DocumentState {
+    let uri = Url::parse("file:///test.asmod").unwrap();
+    DocumentState::new(uri, content.to_string(), 1)
+}
+
+#[test]
+fn test_apply_incremental_insert() {
+    let mut doc = test_document("start:\n    POB #42\n    STP");
+
+    doc.apply_changes(vec![ranged_change(2, 0, 2, 0, "    WYJSCIE\n")], 2);
+
+    assert_eq!(doc.content, "start:\n    POB #42\n    WYJSCIE\n    STP");
+    assert_eq!(doc.version, 2);
+    assert_eq!(doc.line_index().line_count(), 4);
+}
+
+#[test]
+fn test_apply_changes_in_order() {
+    let mut doc = test_document("start:\n    POB #42\n    STP");
+
+    // second change is relative to the result of the first one
+    doc.apply_changes(
+        vec![
+            ranged_change(1, 9, 1, 11, "7"),
+            ranged_change(1, 10, 1, 10, "\n    DOD #1"),
+        ],
+        2,
+    );
+
+    assert_eq!(doc.content, "start:\n    POB #7\n    DOD #1\n    STP");
+}
+
+#[test]
+fn test_apply_multiline_delete() {
+    let mut doc = test_document("start:\n    POB #42\n    DOD #1\n    STP");
+
+    doc.apply_changes(vec![ranged_change(1, 0, 3, 0, "")], 2);
+
+    assert_eq!(doc.content, "start:\n    STP");
+    assert_eq!(doc.line_index().line_count(), 2);
+}
+
+#[test]
+fn test_apply_full_replacement() {
+    let mut doc = test_document("start:\n    POB #42");
+
+    doc.apply_changes(
+        vec![TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: "end:\n    STP".to_string(),
+        }],
+        2,
+    );
+
+    assert_eq!(doc.content, "end:\n    STP");
+}
+
+#[test]
+fn test_apply_edit_after_multibyte_characters() {
+    let mut doc = test_document("    ŁAD wynik ; wejście\n    STP");
+
+    // columns are UTF-16 units: "    ŁAD " is 8 units
+    doc.apply_changes(vec![ranged_change(0, 8, 0, 13, "suma")], 2);
+
+    assert_eq!(doc.content, "    ŁAD suma ; wejście\n    STP");
+}
+
+#[test]
+fn test_version_is_monotonic() {
+    let mut doc = test_document("start:");
+
+    doc.apply_changes(vec![ranged_change(0, 6, 0, 6, "\n    STP")], 5);
+    assert_eq!(doc.version, 5);
+
+    doc.apply_changes(vec![ranged_change(0, 0, 0, 0, "; header\n")], 3);
+    assert_eq!(doc.version, 5);
+    assert_eq!(doc.content, "; header\nstart:\n    STP");
+}
@@ -1,2 +1,6 @@
 mod analyzer_tests;
+mod config_tests;
 mod diagnostics_tests;
+mod document_tests;
+mod parsed_document_tests;
+mod suppressions_tests;
@@ -0,0 +1,129 @@
+use asmodeus_lsp::analysis::core::{DocumentCache, ParsedDocument};
+use asmodeus_lsp::analysis::utils::MemoryModel;
+use lexariel::TokenKind;
+use std::sync::Arc;
+
+#[test]
+fn test_symbol_table_collects_labels() {
+    let content = "start:\n    POB #42\n  loop: DOD #1\n    SOB loop";
+    let doc = ParsedDocument::parse(content);
+
+    let names: Vec<&str> = doc
+        .symbols()
+        .labels()
+        .iter()
+        .map(|label| label.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["start", "loop"]);
+
+    let label = doc.symbols().find_label("loop").unwrap();
+    assert_eq!(label.line, 2);
+    assert_eq!(label.start, 2);
+    assert_eq!(label.end, 6);
+
+    assert!(doc.symbols().is_definition_at(2, 2));
+    assert!(!doc.symbols().is_definition_at(3, 8));
+}
+
+#[test]
+fn test_colon_in_comment_is_not_label() {
+    let content = "    POB #1 ; note: not a label\n// also: not a label\n    STP";
+    let doc = ParsedDocument::parse(content);
+
+    assert!(doc.symbols().labels().is_empty());
+    assert!(doc.symbols().invalid_labels().is_empty());
+}
+
+#[test]
+fn test_invalid_label_names_kept_apart() {
+    let content = "123bad:\n    STP";
+    let doc = ParsedDocument::parse(content);
+
+    assert!(doc.symbols().labels().is_empty());
+    assert_eq!(doc.symbols().invalid_labels()[0].name, "123bad");
+}
+
+#[test]
+fn test_duplicate_definitions_listed() {
+    let content = "start:\n    POB #1\nstart:\n    STP";
+    let doc = ParsedDocument::parse(content);
+
+    let lines: Vec<usize> = doc
+        .symbols()
+        .definitions("start")
+        .map(|label| label.line)
+        .collect();
+    assert_eq!(lines, vec![0, 2]);
+}
+
+#[test]
+fn test_tokens_and_ast() {
+    let content = "start:\n    POB #42\n    STP";
+    let doc = ParsedDocument::parse(content);
+
+    assert!(doc.lexer_errors().is_empty());
+    assert!(doc.parser_errors().is_empty());
+    assert_eq!(doc.ast().elements.len(), 3);
+    assert_eq!(doc.tokens().last().unwrap().kind, TokenKind::Eof);
+
+    let pob = doc
+        .tokens()
+        .iter()
+        .find(|token| token.value == "POB")
+        .unwrap();
+    assert_eq!(pob.line, 2);
+}
+
+#[test]
+fn test_word_occurrences_skip_comments() {
+    let content = "loop:\n    SOB loop ; back to loop";
+    let doc = ParsedDocument::parse(content);
+
+    assert_eq!(doc.word_occurrences("loop"), vec![(0, 0, 4), (1, 8, 12)]);
+}
+
+#[test]
+fn test_cache_reuses_parsed_document() {
+    let cache = DocumentCache::new();
+
+    let first = cache.get("start:\n    STP");
+    let again = cache.get("start:\n    STP");
+    let other = cache.get("start:\n    POB #1\n    STP");
+
+    assert!(Arc::ptr_eq(&first, &again));
+    assert!(!Arc::ptr_eq(&first, &other));
+
+    // documents parsed for another memory model are not reused
+    cache.set_memory_model(MemoryModel::new(8, 8));
+    assert!(!Arc::ptr_eq(&first, &cache.get("start:\n    STP")));
+}
+
+#[test]
+fn test_flow_analyses_computed_once() {
+    let doc = ParsedDocument::parse("start:\n    POB #1\n    SDP\n    PZS\n    STP");
+
+    assert!(std::ptr::eq(doc.values(), doc.values()));
+    assert!(std::ptr::eq(doc.stack(), doc.stack()));
+    assert!(std::ptr::eq(doc.layout(), doc.layout()));
+    assert_eq!(doc.values().ak_after(0).map(|ak| ak.to_string()), Some("1".to_string()));
+}
+
+#[test]
+fn test_statements_parsed_independently() {
+    let content = "start:\n    POB\n    DOD #1\n    STP";
+    let doc = ParsedDocument::parse(content);
+
+    // POB misses its operand, the following lines still parse
+    assert_eq!(doc.parser_errors().len(), 1);
+    assert_eq!(doc.parser_errors()[0].statement_end.line, 2);
+    assert_eq!(doc.ast().elements.len(), 3);
+}
+
+#[test]
+fn test_macro_definition_spans_lines() {
+    let content = "MAKRO double x\n    POB x\n    DOD x\nKONM\n    STP";
+    let doc = ParsedDocument::parse(content);
+
+    assert!(doc.parser_errors().is_empty(), "{:?}", doc.parser_errors());
+    assert_eq!(doc.ast().elements.len(), 2);
+}
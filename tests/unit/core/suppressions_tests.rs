@@ -0,0 +1,117 @@
+use asmodeus_lsp::analysis::core::{suppression_pragmas, SuppressionKind, Suppressions};
+use asmodeus_lsp::analysis::utils::{create_diagnostic, position_to_range, DiagnosticCode};
+use asmodeus_lsp::analysis::SemanticAnalyzer;
+use tower_lsp::lsp_types::*;
+
+fn analyze(content: &str) -> Vec<Diagnostic> {
+    let uri = Url::parse("file:///test.asmod").unwrap();
+    SemanticAnalyzer::new().analyze_document(content, &uri)
+}
+
+fn with_code(diagnostics: &[Diagnostic], code: DiagnosticCode) -> Vec<&Diagnostic> {
+    diagnostics
+        .iter()
+        .filter(|diagnostic| DiagnosticCode::of(diagnostic) == Some(code))
+        .collect()
+}
+
+#[test]
+fn test_parse_suppression_pragmas() {
+    let lines = [
+        "    POB x ; asmod-disable-next-line SEM005 sem007 -- on purpose",
+        "; asmod-disable-file",
+        "; asmod-disabled SEM005",
+        "; asmod-enable SEM007",
+    ];
+    let pragmas = suppression_pragmas(lines);
+
+    assert_eq!(pragmas.len(), 4);
+    assert_eq!(pragmas[0].kind, SuppressionKind::NextLine);
+    assert_eq!(pragmas[0].code.as_deref(), Some("SEM005"));
+    assert_eq!((pragmas[0].line, pragmas[0].start, pragmas[0].end), (0, 36, 42));
+    assert_eq!(pragmas[1].code.as_deref(), Some("sem007"));
+
+    // no codes covers every code, the pragma keyword is the span
+    assert_eq!(pragmas[2].kind, SuppressionKind::File);
+    assert_eq!(pragmas[2].code, None);
+    assert_eq!((pragmas[2].line, pragmas[2].start, pragmas[2].end), (1, 2, 20));
+
+    assert_eq!(pragmas[3].kind, SuppressionKind::Enable);
+    assert_eq!(pragmas[3].line, 3);
+}
+
+#[test]
+fn test_disable_next_line() {
+    let content = "start: POB value\n       STP\nvalue: RST 5\nspare: RST 0\n";
+    assert_eq!(with_code(&analyze(content), DiagnosticCode::Sem037).len(), 1);
+
+    let suppressed =
+        "start: POB value\n       STP\nvalue: RST 5\n; asmod-disable-next-line SEM037\nspare: RST 0\n";
+    let diagnostics = analyze(suppressed);
+    assert!(with_code(&diagnostics, DiagnosticCode::Sem037).is_empty());
+    assert!(with_code(&diagnostics, DiagnosticCode::Sem038).is_empty());
+}
+
+#[test]
+fn test_pragma_in_slash_comment() {
+    let pragmas = suppression_pragmas(["    POB x // asmod-disable-next-line SEM005"]);
+    assert_eq!(pragmas.len(), 1);
+    assert_eq!((pragmas[0].start, pragmas[0].end), (37, 43));
+
+    let suppressed =
+        "start: POB value\n       STP\nvalue: RST 5\n// asmod-disable-next-line SEM037\nspare: RST 0\n";
+    let diagnostics = analyze(suppressed);
+    assert!(with_code(&diagnostics, DiagnosticCode::Sem037).is_empty());
+    assert!(with_code(&diagnostics, DiagnosticCode::Sem038).is_empty());
+}
+
+#[test]
+fn test_disable_enable_region() {
+    let suppressions = Suppressions::parse([
+        "; asmod-disable SEM016",
+        "    POB x",
+        "; asmod-enable SEM016",
+        "    POB x",
+    ]);
+    let unreachable = |line| {
+        create_diagnostic(
+            position_to_range(line, 4, 7),
+            DiagnosticCode::Sem016,
+            "Unreachable code".to_string())
+    };
+
+    assert!(suppressions.suppresses(&unreachable(1)));
+    assert!(!suppressions.suppresses(&unreachable(3)));
+
+    // other codes are not covered
+    let undefined = create_diagnostic(
+        position_to_range(1, 8, 9),
+        DiagnosticCode::Sem008,
+        "Undefined label: 'x'".to_string());
+    assert!(!suppressions.suppresses(&undefined));
+
+    let kept = suppressions.apply(vec![unreachable(1), unreachable(3)], |_| false);
+    assert_eq!(kept, vec![unreachable(3)]);
+}
+
+#[test]
+fn test_disable_file_without_codes() {
+    let content = "; asmod-disable-file\nstart: POB value\n       STP\nvalue: RST 5\nspare: RST 0\n";
+    assert!(analyze(content).is_empty());
+}
+
+#[test]
+fn test_unused_suppression_is_reported() {
+    let content = "start: POB value\n       STP\n; asmod-disable-next-line SEM008 SEM999\nvalue: RST 5\n";
+    let unused = with_code(&analyze(content), DiagnosticCode::Sem038)
+        .into_iter()
+        .cloned()
+        .collect::<Vec<_>>();
+
+    assert_eq!(unused.len(), 2);
+    assert_eq!(unused[0].severity, Some(DiagnosticSeverity::WARNING));
+    assert_eq!(unused[0].range, position_to_range(2, 26, 32));
+    assert_eq!(unused[0].message, "Suppression of 'SEM008' is never used");
+    assert_eq!(unused[1].range, position_to_range(2, 33, 39));
+    assert_eq!(unused[1].message, "Unknown diagnostic code 'SEM999' in suppression");
+}
@@ -0,0 +1,87 @@
+use super::cfg;
+use asmodeus_lsp::analysis::core::ParsedDocument;
+use asmodeus_lsp::analysis::flow::{EdgeKind, Terminator};
+use asmodeus_lsp::analysis::utils::{InstructionDatabase, MemoryModel};
+
+#[test]
+fn test_straight_line_code_is_one_block() {
+    let cfg = cfg("start:\n    POB #1\n    DOD #2\n    STP");
+
+    assert_eq!(cfg.blocks().len(), 1);
+    assert_eq!(cfg.blocks()[0].statements.len(), 3);
+    assert_eq!(cfg.blocks()[0].labels, vec!["start"]);
+    assert_eq!(cfg.blocks()[0].terminator, Terminator::Stop);
+    assert!(cfg.blocks()[0].successors.is_empty());
+}
+
+#[test]
+fn test_label_starts_block_with_fall_through() {
+    let cfg = cfg("    POB #1\nloop:\n    DOD #1\n    STP");
+
+    assert_eq!(cfg.blocks().len(), 2);
+    assert_eq!(cfg.blocks()[0].terminator, Terminator::FallThrough);
+    assert_eq!(cfg.blocks()[0].successors[0].target, 1);
+    assert_eq!(cfg.blocks()[0].successors[0].kind, EdgeKind::FallThrough);
+    assert_eq!(cfg.blocks()[1].predecessors, vec![0]);
+    assert_eq!(cfg.block_of_label("loop"), Some(1));
+}
+
+#[test]
+fn test_jump_and_branch_edges() {
+    let cfg = cfg("loop:\n    POB x\n    SOZ done\n    SOB loop\ndone:\n    STP\nx: RST 0");
+
+    let kinds: Vec<(usize, EdgeKind)> = cfg.blocks()[0]
+        .successors
+        .iter()
+        .map(|edge| (edge.target, edge.kind))
+        .collect();
+    assert_eq!(kinds, vec![(2, EdgeKind::BranchTaken), (1, EdgeKind::FallThrough)]);
+
+    assert_eq!(cfg.blocks()[1].terminator, Terminator::Jump);
+    assert_eq!(cfg.blocks()[1].successors[0].target, 0);
+    assert_eq!(cfg.blocks()[1].successors[0].kind, EdgeKind::Jump);
+    assert_eq!(cfg.data_cells().len(), 1);
+    assert_eq!(cfg.data_cells()[0].labels, vec!["x"]);
+}
+
+#[test]
+fn test_code_after_stop_is_unreachable() {
+    let cfg = cfg("    STP\n    POB #1\n    STP");
+
+    assert_eq!(cfg.reachable(), vec![true, false]);
+}
+
+#[test]
+fn test_program_end_and_data() {
+    let first_block = |content: &str| cfg(content).blocks()[0].clone();
+
+    assert_eq!(first_block("    POB #1\n    DOD #1").terminator, Terminator::FallsOffEnd);
+    assert_eq!(first_block("    POB #1\nx: RST 0").terminator, Terminator::FallsIntoData);
+    assert_eq!(first_block("    POB #1\n    SOZ start\nstart: STP").leaves_code(), None);
+    assert_eq!(
+        first_block("start:\n    POB #1\n    SOM start").leaves_code(),
+        Some(Terminator::FallsOffEnd)
+    );
+}
+
+#[test]
+fn test_unresolved_targets_recorded() {
+    let cfg = cfg("    SOB missing\n    STP");
+
+    assert_eq!(cfg.unresolved_targets(), &[(0, "missing".to_string())]);
+    assert!(cfg.blocks()[0].successors.is_empty());
+}
+
+#[test]
+fn test_opcodes_resolved_with_instruction_set() {
+    let content = "start:\n    lad x\n    sob start\nx: RST 0";
+
+    let cfg = cfg(content);
+    assert!(cfg.statements()[0].is_opcode("ŁAD"));
+    assert_eq!(cfg.blocks()[0].terminator, Terminator::Jump);
+
+    let db = InstructionDatabase::new().with_case_sensitive(true);
+    let document = ParsedDocument::parse_with(content, db, MemoryModel::default());
+    assert!(!document.cfg().statements()[0].is_opcode("ŁAD"));
+    assert_eq!(document.cfg().blocks()[0].terminator, Terminator::FallsIntoData);
+}
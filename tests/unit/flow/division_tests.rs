@@ -0,0 +1,64 @@
+use super::{cfg, diagnostics, only};
+use asmodeus_lsp::analysis::flow::{Cell, Definition, ReachingStores};
+use asmodeus_lsp::analysis::utils::DiagnosticCode;
+use tower_lsp::lsp_types::*;
+
+fn division_diagnostics(content: &str) -> Vec<Diagnostic> {
+    only(diagnostics(content), &[DiagnosticCode::Sem025, DiagnosticCode::Sem026])
+}
+
+#[test]
+fn test_reaching_stores() {
+    let content = "    SOZ skip\n    ŁAD x\nskip:\n    POB x\n    ŁAD [p]\n    POB x\n    STP\nx: RST 1\np: RST 0";
+    let reaching = ReachingStores::analyze(&cfg(content));
+    let x = Cell::Label("x".to_string());
+
+    let at_read: Vec<Definition> = reaching.reaching(2, &x).into_iter().collect();
+    assert_eq!(at_read, vec![Definition::Initial, Definition::Store(1)]);
+
+    // a store through a pointer may hit x
+    assert!(reaching.reaching(4, &x).contains(&Definition::Unknown));
+}
+
+#[test]
+fn test_literal_zero_is_an_error() {
+    let diagnostics = division_diagnostics("    POB #8\n    DZI #0\n    STP");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::ERROR));
+    assert_eq!(diagnostics[0].message, "DZI divides by zero");
+    assert_eq!(diagnostics[0].range.start, Position { line: 1, character: 8 });
+
+    let diagnostics = division_diagnostics("    POB #8\n    MOD #0x0\n    STP");
+    assert_eq!(diagnostics[0].message, "MOD divides by zero");
+}
+
+#[test]
+fn test_divisor_never_written() {
+    let diagnostics = division_diagnostics("    POB #8\n    DZI d\n    STP\nd: RPA");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::WARNING));
+    assert_eq!(diagnostics[0].message, "DZI divides by 'd', which is never written");
+}
+
+#[test]
+fn test_divisor_only_stored_zero() {
+    let content = "    WEJSCIE\n    SOZ zero\n    POB #0\n    ŁAD d\n    SOB divide\nzero:\n    ŁAD d\ndivide:\n    POB #8\n    DZI d\n    STP\nd: RPA";
+    let diagnostics = division_diagnostics(content);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        "DZI divides by 'd', whose only reaching stores are 0"
+    );
+}
+
+#[test]
+fn test_divisor_with_nonzero_store_is_fine() {
+    let content = "    WEJSCIE\n    ŁAD d\n    POB #8\n    DZI d\n    STP\nd: RPA";
+    assert!(division_diagnostics(content).is_empty());
+
+    let content = "    POB #8\n    DZI d\n    STP\nd: RST 2";
+    assert!(division_diagnostics(content).is_empty());
+}
@@ -0,0 +1,96 @@
+use super::{cfg, codes, codes_with, diagnostics};
+use asmodeus_lsp::analysis::flow::{
+    handler_pragmas, FlowAnalysisProvider, InterruptAnalysis, InterruptConfig, InterruptIssue,
+    Terminator,
+};
+use asmodeus_lsp::analysis::utils::DiagnosticCode;
+use tower_lsp::lsp_types::*;
+
+#[test]
+fn test_handler_pragmas() {
+    let pragmas = handler_pragmas(["    STP ; asmod-handler tick  key", "; asmod-handlers x"]);
+
+    let names: Vec<&str> = pragmas.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, vec!["tick", "key"]);
+    assert_eq!((pragmas[0].line, pragmas[0].start, pragmas[0].end), (0, 24, 28));
+    assert_eq!((pragmas[1].start, pragmas[1].end), (30, 33));
+
+    let pragmas = handler_pragmas(["// asmod-handler tick"]);
+    assert_eq!((pragmas[0].name.as_str(), pragmas[0].start, pragmas[0].end), ("tick", 17, 21));
+}
+
+#[test]
+fn test_handler_is_reachable_and_returns() {
+    let content = "; asmod-handler tick\n    POB #1\n    STP\ntick:\n    DOD #1\n    PWR";
+
+    assert!(codes(content).is_empty());
+}
+
+#[test]
+fn test_pwr_outside_handler() {
+    assert_eq!(codes("    POB #1\n    PWR"), vec![DiagnosticCode::Sem027]);
+
+    // branching into a handler runs its PWR from the main program
+    let content = "; asmod-handler tick\n    SOZ tick\n    STP\ntick:\n    PWR";
+    assert_eq!(codes(content), vec![DiagnosticCode::Sem027]);
+}
+
+#[test]
+fn test_handler_falls_through() {
+    let content = "    STP\ntick:\n    POB #1";
+    let handlers = vec!["tick".to_string()];
+    let analysis = InterruptAnalysis::analyze(&cfg(content), &handlers);
+    assert_eq!(
+        analysis.issues(),
+        &[InterruptIssue::HandlerFallsThrough {
+            handler: "tick".to_string(),
+            block: 1,
+            exit: Terminator::FallsOffEnd,
+            into: None,
+        }]
+    );
+
+    // configured handlers need no pragma, and SEM017 is left to the main program
+    let provider = FlowAnalysisProvider::new()
+        .with_interrupt_config(InterruptConfig::new(8, handlers));
+    assert_eq!(codes_with(&provider, content), vec![DiagnosticCode::Sem028]);
+
+    let content = "; asmod-handler a b\n    STP\na:\n    POB #1\nb:\n    PWR";
+    let diagnostics = diagnostics(content);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        "Interrupt handler 'a' falls through into handler 'b' without PWR"
+    );
+    assert_eq!(diagnostics[0].range.start.line, 3);
+}
+
+#[test]
+fn test_pragma_naming_no_label() {
+    let diagnostics = diagnostics("; asmod-handler tik\n    STP");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(DiagnosticCode::of(&diagnostics[0]), Some(DiagnosticCode::Sem030));
+    assert_eq!(diagnostics[0].range.start, Position { line: 0, character: 16 });
+}
+
+#[test]
+fn test_mask_against_interrupt_lines() {
+    assert!(codes("    MSK #255\n    STP").is_empty());
+    assert_eq!(codes("    MSK #256\n    STP"), vec![DiagnosticCode::Sem029]);
+
+    let provider = FlowAnalysisProvider::new()
+        .with_interrupt_config(InterruptConfig::new(4, Vec::new()));
+    assert_eq!(codes_with(&provider, "    MSK #0b10000\n    STP"), vec![DiagnosticCode::Sem029]);
+    assert!(codes_with(&provider, "    MSK #0b1111\n    STP").is_empty());
+}
+
+#[test]
+fn test_masked_lines() {
+    let config = InterruptConfig::new(4, Vec::new());
+
+    assert_eq!(config.masked_lines(0b0101), vec![0, 2]);
+    assert!(config.fits_mask(15));
+    assert!(!config.fits_mask(16));
+    assert!(!config.fits_mask(-1));
+}
@@ -0,0 +1,52 @@
+use super::{cfg, codes, diagnostics};
+use asmodeus_lsp::analysis::flow::{JumpAnalysis, JumpIssue};
+use asmodeus_lsp::analysis::utils::DiagnosticCode;
+use tower_lsp::lsp_types::*;
+
+#[test]
+fn test_loop_without_exit() {
+    assert_eq!(codes("x:\n    SOB x"), vec![DiagnosticCode::Sem031]);
+    assert_eq!(codes("loop:\n    POB #1\n    WYJSCIE\n    SOB loop"), vec![DiagnosticCode::Sem031]);
+
+    let diagnostic = &diagnostics("loop:\n    POB #1\n    SOB loop")[0];
+    assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+    assert_eq!(diagnostic.range.start, Position { line: 2, character: 4 });
+}
+
+#[test]
+fn test_loop_with_exit() {
+    assert!(codes("loop:\n    WEJSCIE\n    SOZ end\n    SOB loop\nend:\n    STP").is_empty());
+    assert!(codes("loop:\n    WEJSCIE\n    SOB loop").is_empty());
+}
+
+#[test]
+fn test_jump_to_next_instruction() {
+    let found = diagnostics("    SOZ next\nnext:\n    STP");
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].code, Some(NumberOrString::String("SEM032".to_string())));
+    assert_eq!(found[0].severity, Some(DiagnosticSeverity::HINT));
+    assert_eq!(found[0].tags, Some(vec![DiagnosticTag::UNNECESSARY]));
+
+    // skipping a data cell is a real jump
+    assert!(codes("    SOB next\nd: RST 1\nnext:\n    STP").is_empty());
+}
+
+#[test]
+fn test_jump_chain() {
+    let found = diagnostics("    SOZ a\n    STP\nb:\n    STP\na:\n    SOB b");
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].message, "Jump to 'a' only continues to 'b', jump there directly");
+    assert_eq!(found[0].range.start, Position { line: 0, character: 8 });
+
+    // chains are followed to their end
+    let content = "    SOB a\nc:\n    SOB b\nb:\n    STP\na:\n    SOB c";
+    let analysis = JumpAnalysis::analyze(&cfg(content));
+    assert_eq!(
+        analysis.issues()[0],
+        JumpIssue::JumpChain {
+            statement: 0,
+            via: "a".to_string(),
+            target: "b".to_string(),
+        }
+    );
+}
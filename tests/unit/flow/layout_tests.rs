@@ -0,0 +1,84 @@
+use super::{codes, codes_with, diagnostics, diagnostics_with, only};
+use asmodeus_lsp::analysis::core::ParsedDocument;
+use asmodeus_lsp::analysis::flow::{
+    AddressLayout, CheckLevel, FlowAnalysisProvider, LayoutConfig, Region,
+};
+use asmodeus_lsp::analysis::utils::DiagnosticCode;
+use tower_lsp::lsp_types::*;
+
+#[test]
+fn test_addresses_of_code_and_data() {
+    let document = ParsedDocument::parse("start:\n    POB x\n    STP\nx: RST 5\ny: RPA");
+    let layout = AddressLayout::build(document.cfg());
+
+    assert_eq!(layout.statement_address(0), Some(0));
+    assert_eq!(layout.statement_address(1), Some(1));
+    assert_eq!(layout.data_address(1), Some(3));
+    assert_eq!(layout.label("start"), Some((Region::Code, Some(0))));
+    assert_eq!(layout.label("x"), Some((Region::Data, Some(2))));
+    assert_eq!(layout.region_at(1), Some(Region::Code));
+    assert_eq!(layout.region_at(3), Some(Region::Data));
+    assert_eq!(layout.region_at(4), None);
+}
+
+#[test]
+fn test_store_into_code() {
+    let found = only(
+        diagnostics("start:\n    POB #1\n    ŁAD start\n    STP"),
+        &[DiagnosticCode::Sem034],
+    );
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].message, "ŁAD to 'start' overwrites the instruction at address 0");
+    assert_eq!(found[0].severity, Some(DiagnosticSeverity::WARNING));
+    assert_eq!(found[0].range.start, Position { line: 2, character: 8 });
+
+    let found = only(diagnostics("    POB #1\n    ŁAD 2\n    STP"), &[DiagnosticCode::Sem034]);
+    assert_eq!(found[0].message, "ŁAD to '2' overwrites the instruction at address 2");
+
+    // storing past the program image is ordinary memory use
+    assert!(!codes("    POB #1\n    ŁAD 100\n    STP").contains(&DiagnosticCode::Sem034));
+}
+
+#[test]
+fn test_jump_into_data() {
+    let found = only(diagnostics("    SOZ x\n    STP\nx: RST 0"), &[DiagnosticCode::Sem035]);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].message, "SOZ to 'x' jumps into data, not code");
+    assert_eq!(found[0].severity, Some(DiagnosticSeverity::ERROR));
+}
+
+#[test]
+fn test_execution_falls_into_data() {
+    let found = only(diagnostics("    POB #1\nx: RST 0"), &[DiagnosticCode::Sem036]);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].message, "Execution runs from code into data at address 1");
+
+    let found = only(
+        diagnostics("start:\n    POB #1\n    SOM start\nx: RST 0"),
+        &[DiagnosticCode::Sem036],
+    );
+    assert_eq!(
+        found[0].message,
+        "Execution runs from code into data at address 2 when SOM is not taken"
+    );
+
+    assert!(!codes("    POB #1\n    STP\nx: RST 0").contains(&DiagnosticCode::Sem036));
+}
+
+#[test]
+fn test_layout_check_levels() {
+    let provider = FlowAnalysisProvider::new().with_layout_config(LayoutConfig {
+        store_into_code: CheckLevel::Error,
+        jump_into_data: CheckLevel::Warning,
+        fall_into_data: CheckLevel::Off,
+    });
+
+    let found = only(
+        diagnostics_with(&provider, "start:\n    ŁAD start\n    STP"),
+        &[DiagnosticCode::Sem034],
+    );
+    assert_eq!(found[0].severity, Some(DiagnosticSeverity::ERROR));
+    let found = only(diagnostics_with(&provider, "    SOB x\nx: RST 0"), &[DiagnosticCode::Sem035]);
+    assert_eq!(found[0].severity, Some(DiagnosticSeverity::WARNING));
+    assert!(!codes_with(&provider, "    POB #1\nx: RST 0").contains(&DiagnosticCode::Sem036));
+}
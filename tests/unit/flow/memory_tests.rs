@@ -0,0 +1,83 @@
+use super::{cfg, diagnostics, only};
+use asmodeus_lsp::analysis::flow::{Cell, MemoryAnalysis};
+use asmodeus_lsp::analysis::utils::DiagnosticCode;
+use tower_lsp::lsp_types::*;
+
+#[test]
+fn test_reserved_cell_read_before_store() {
+    let analysis = MemoryAnalysis::analyze(&cfg("    POB x\n    STP\nx: RPA"));
+
+    let reads = analysis.uninitialized_reads();
+    assert_eq!(reads.len(), 1);
+    assert_eq!(reads[0].cell, Cell::Label("x".to_string()));
+    assert_eq!(reads[0].statement, 0);
+}
+
+#[test]
+fn test_store_before_read_and_initialized_data() {
+    assert!(MemoryAnalysis::analyze(&cfg("    POB #1\n    ŁAD x\n    POB x\n    STP\nx: RPA"))
+        .uninitialized_reads()
+        .is_empty());
+    assert!(MemoryAnalysis::analyze(&cfg("    POB x\n    STP\nx: RST 5"))
+        .uninitialized_reads()
+        .is_empty());
+}
+
+#[test]
+fn test_store_on_one_path_only() {
+    let content = "    SOZ skip\n    ŁAD x\nskip:\n    POB x\n    STP\nx: RPA";
+    let analysis = MemoryAnalysis::analyze(&cfg(content));
+
+    let reads = analysis.uninitialized_reads();
+    assert_eq!(reads.len(), 1);
+    // entry block branches straight to the read
+    assert_eq!(reads[0].path, vec![0, 2]);
+}
+
+#[test]
+fn test_indirect_store_may_write_anything() {
+    assert!(MemoryAnalysis::analyze(&cfg("    ŁAD [p]\n    POB x\n    STP\nx: RPA\np: RST 0"))
+        .uninitialized_reads()
+        .is_empty());
+}
+
+#[test]
+fn test_address_past_program_image() {
+    let analysis = MemoryAnalysis::analyze(&cfg("    POB 200\n    POB 0\n    STP"));
+
+    let cells: Vec<&Cell> = analysis.uninitialized_reads().iter().map(|read| &read.cell).collect();
+    assert_eq!(cells, vec![&Cell::Address(200)]);
+}
+
+#[test]
+fn test_uninitialized_read_diagnostic_shows_path() {
+    let content = "    SOZ skip\n    ŁAD x\nskip:\n    POB x\n    STP\nx: RPA";
+
+    let found = only(diagnostics(content), &[DiagnosticCode::Sem021]);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].range.start, Position { line: 3, character: 8 });
+    assert_eq!(found[0].range.end, Position { line: 3, character: 9 });
+
+    let related = found[0].related_information.as_ref().unwrap();
+    assert_eq!(related.len(), 1);
+    assert_eq!(related[0].location.range.start.line, 0);
+    assert!(related[0].message.contains("'x' not yet written"));
+
+    // a read in the entry block has no earlier blocks to show
+    let found = only(diagnostics("    POB x\n    STP\nx: RPA"), &[DiagnosticCode::Sem021]);
+    assert_eq!(found[0].related_information, None);
+}
+
+#[test]
+fn test_written_but_never_read() {
+    let content = "    POB #1\n    ŁAD x\n    ŁAD y\n    POB y\n    STP\nx: RPA\ny: RPA";
+
+    let found = only(diagnostics(content), &[DiagnosticCode::Sem022]);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].message, "'x' is written but never read");
+    assert_eq!(found[0].range.start, Position { line: 1, character: 8 });
+
+    // a read through a pointer may read it
+    let content = "    ŁAD x\n    POB [p]\n    STP\nx: RPA\np: RST 0";
+    assert!(only(diagnostics(content), &[DiagnosticCode::Sem022]).is_empty());
+}
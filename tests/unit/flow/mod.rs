@@ -0,0 +1,50 @@
+mod cfg_tests;
+mod provider_tests;
+mod stack_tests;
+mod memory_tests;
+mod values_tests;
+mod division_tests;
+mod interrupts_tests;
+mod jumps_tests;
+mod layout_tests;
+
+use asmodeus_lsp::analysis::core::ParsedDocument;
+use asmodeus_lsp::analysis::flow::{ControlFlowGraph, FlowAnalysisProvider};
+use asmodeus_lsp::analysis::utils::DiagnosticCode;
+use tower_lsp::lsp_types::*;
+
+fn uri() -> Url {
+    Url::parse("file:///test.asmod").unwrap()
+}
+
+fn cfg(content: &str) -> ControlFlowGraph {
+    ParsedDocument::parse(content).cfg().clone()
+}
+
+fn diagnostics_with(provider: &FlowAnalysisProvider, content: &str) -> Vec<Diagnostic> {
+    provider.validate_control_flow(content, &uri())
+}
+
+fn diagnostics(content: &str) -> Vec<Diagnostic> {
+    diagnostics_with(&FlowAnalysisProvider::new(), content)
+}
+
+fn codes_with(provider: &FlowAnalysisProvider, content: &str) -> Vec<DiagnosticCode> {
+    codes_of(&diagnostics_with(provider, content))
+}
+
+fn codes(content: &str) -> Vec<DiagnosticCode> {
+    codes_with(&FlowAnalysisProvider::new(), content)
+}
+
+fn codes_of(diagnostics: &[Diagnostic]) -> Vec<DiagnosticCode> {
+    diagnostics.iter().filter_map(DiagnosticCode::of).collect()
+}
+
+/// diagnostics with one of the given codes
+fn only(diagnostics: Vec<Diagnostic>, codes: &[DiagnosticCode]) -> Vec<Diagnostic> {
+    diagnostics
+        .into_iter()
+        .filter(|d| DiagnosticCode::of(d).is_some_and(|code| codes.contains(&code)))
+        .collect()
+}
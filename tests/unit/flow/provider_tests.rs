@@ -0,0 +1,65 @@
+use super::{codes_of, codes_with, uri};
+use asmodeus_lsp::analysis::flow::FlowAnalysisProvider;
+use asmodeus_lsp::analysis::utils::{DiagnosticCode, InstructionDatabase};
+use tower_lsp::lsp_types::*;
+
+#[test]
+fn test_unreachable_code_reported_once_per_run() {
+    let provider = FlowAnalysisProvider::new();
+    let content = "    STP\n    POB #1 ; dead\nnext:\n    DOD #1\n    STP";
+
+    let diagnostics = provider.validate_control_flow(content, &uri());
+    assert_eq!(codes_of(&diagnostics), vec![DiagnosticCode::Sem016]);
+
+    let diagnostic = &diagnostics[0];
+    assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+    assert_eq!(diagnostic.tags, Some(vec![DiagnosticTag::UNNECESSARY]));
+    assert_eq!(diagnostic.range.start, Position { line: 1, character: 4 });
+    assert_eq!(diagnostic.range.end, Position { line: 4, character: 7 });
+}
+
+#[test]
+fn test_jump_target_is_reachable() {
+    let provider = FlowAnalysisProvider::new();
+    let content = "    SOB end\n    POB #1\nend:\n    STP";
+
+    let diagnostics = provider.validate_control_flow(content, &uri());
+    assert_eq!(codes_of(&diagnostics), vec![DiagnosticCode::Sem016]);
+    assert_eq!(diagnostics[0].range.start.line, 1);
+    assert_eq!(diagnostics[0].range.end.line, 1);
+}
+
+#[test]
+fn test_numeric_jump_keeps_labels_reachable() {
+    let provider = FlowAnalysisProvider::new();
+    let content = "    SOB 5\nhandler:\n    STP";
+
+    assert!(provider.validate_control_flow(content, &uri()).is_empty());
+}
+
+#[test]
+fn test_falling_off_end_reported() {
+    let provider = FlowAnalysisProvider::new();
+
+    let diagnostics = provider.validate_control_flow("    POB #1\n    WYJSCIE", &uri());
+    assert_eq!(codes_of(&diagnostics), vec![DiagnosticCode::Sem017]);
+    assert_eq!(diagnostics[0].range.start, Position { line: 1, character: 4 });
+
+    let diagnostics = provider.validate_control_flow("loop:\n    WEJSCIE\n    SOM loop", &uri());
+    assert_eq!(codes_of(&diagnostics), vec![DiagnosticCode::Sem017]);
+    assert!(diagnostics[0].message.contains("SOM is not taken"));
+
+    assert!(provider.validate_control_flow("    POB #1\n    STP", &uri()).is_empty());
+}
+
+#[test]
+fn test_flow_follows_instruction_set_spelling() {
+    let content = "    POB #1\n    stp";
+
+    // lowercase `stp` stops the program unless the instruction set is case-sensitive
+    assert!(codes_with(&FlowAnalysisProvider::new(), content).is_empty());
+
+    let provider = FlowAnalysisProvider::new()
+        .with_instruction_db(InstructionDatabase::new().with_case_sensitive(true));
+    assert_eq!(codes_with(&provider, content), vec![DiagnosticCode::Sem017]);
+}
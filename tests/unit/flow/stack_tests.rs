@@ -0,0 +1,58 @@
+use super::{cfg, codes};
+use asmodeus_lsp::analysis::flow::{StackAnalysis, StackDepth, StackIssue};
+use asmodeus_lsp::analysis::utils::DiagnosticCode;
+
+#[test]
+fn test_balanced_program_has_no_issues() {
+    let analysis = StackAnalysis::analyze(&cfg("    SDP\n    POB #1\n    PZS\n    STP"));
+
+    assert!(analysis.issues().is_empty());
+    assert_eq!(analysis.depth_before(2), Some(StackDepth::exact(1)));
+    assert_eq!(analysis.depth_before(3), Some(StackDepth::exact(0)));
+}
+
+#[test]
+fn test_pop_from_empty_stack() {
+    let analysis = StackAnalysis::analyze(&cfg("    PZS\n    STP"));
+    assert_eq!(
+        analysis.issues(),
+        &[StackIssue::PopFromEmpty { statement: 0, always: true }]
+    );
+
+    // empty only when the branch is taken
+    let analysis = StackAnalysis::analyze(&cfg("    SOZ skip\n    SDP\nskip:\n    PZS\n    STP"));
+    assert!(analysis
+        .issues()
+        .contains(&StackIssue::PopFromEmpty { statement: 2, always: false }));
+}
+
+#[test]
+fn test_merge_with_different_depths() {
+    let analysis = StackAnalysis::analyze(&cfg("    SOZ skip\n    SDP\nskip:\n    STP"));
+
+    assert!(analysis.issues().contains(&StackIssue::MergeMismatch {
+        block: 2,
+        depths: vec![StackDepth::exact(0), StackDepth::exact(1)],
+    }));
+}
+
+#[test]
+fn test_pushing_loop_terminates_unbounded() {
+    let analysis = StackAnalysis::analyze(&cfg("loop:\n    SDP\n    SOZ loop\n    STP"));
+
+    let depth = analysis.depth_before(2).unwrap();
+    assert_eq!(depth.min, 1);
+    assert_eq!(depth.max, None);
+    assert_eq!(depth.to_string(), "1+");
+}
+
+#[test]
+fn test_stack_diagnostics() {
+    assert_eq!(codes("    PZS\n    STP"), vec![DiagnosticCode::Sem018]);
+    assert_eq!(codes("    SDP\n    STP"), vec![DiagnosticCode::Sem020]);
+    assert_eq!(
+        codes("    SOZ skip\n    SDP\nskip:\n    PZS\n    STP"),
+        vec![DiagnosticCode::Sem019, DiagnosticCode::Sem018]
+    );
+    assert!(codes("    SDP\n    PZS\n    STP").is_empty());
+}
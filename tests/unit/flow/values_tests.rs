@@ -0,0 +1,96 @@
+use super::codes;
+use asmodeus_lsp::analysis::core::ParsedDocument;
+use asmodeus_lsp::analysis::flow::{BranchOutcome, Effect, Interval};
+use asmodeus_lsp::analysis::utils::{DiagnosticCode, InstructionDatabase, MemoryModel};
+
+#[test]
+fn test_effects_read_from_instruction_database() {
+    let db = InstructionDatabase::new();
+    let effect = |name: &str| Effect::of_operation(&db.get_instruction(name).unwrap().operation);
+
+    assert_eq!(effect("POB"), Effect::Load);
+    assert_eq!(effect("ŁAD"), Effect::Store);
+    assert_eq!(effect("DOD"), Effect::Arithmetic('+'));
+    assert_eq!(effect("MOD"), Effect::Arithmetic('%'));
+    assert_eq!(effect("WEJSCIE"), Effect::Clobber);
+    assert_eq!(effect("PZS"), Effect::Clobber);
+    assert_eq!(effect("STP"), Effect::None);
+}
+
+#[test]
+fn test_accumulator_through_arithmetic_and_memory() {
+    let content = "    POB #5\n    DOD #3\n    ŁAD x\n    POB y\n    ODE x\n    STP\nx: RPA\ny: RST 10";
+    let document = ParsedDocument::parse(content);
+    let analysis = document.values();
+
+    assert_eq!(analysis.ak_after(0), Some(Interval::exact(5)));
+    assert_eq!(analysis.ak_after(1), Some(Interval::exact(8)));
+    assert_eq!(analysis.ak_after(3), Some(Interval::exact(10)));
+    assert_eq!(analysis.ak_after(4), Some(Interval::exact(2)));
+}
+
+#[test]
+fn test_paths_join_into_range() {
+    let content = "    WEJSCIE\n    SOZ zero\n    POB #1\n    SOB done\nzero:\n    POB #4\ndone:\n    WYJSCIE\n    STP";
+    let document = ParsedDocument::parse(content);
+    let analysis = document.values();
+
+    assert_eq!(analysis.ak_before(6), Some(Interval { min: 1, max: 4 }));
+}
+
+#[test]
+fn test_branch_refines_accumulator() {
+    let content = "    WEJSCIE\n    SOZ zero\n    STP\nzero:\n    WYJSCIE\n    STP";
+    let document = ParsedDocument::parse(content);
+    let analysis = document.values();
+
+    assert_eq!(analysis.ak_before(3), Some(Interval::exact(0)));
+}
+
+#[test]
+fn test_loop_terminates_with_unknown_value() {
+    let content = "    POB #0\nloop:\n    DOD #1\n    SOB loop";
+    let document = ParsedDocument::parse(content);
+    let analysis = document.values();
+
+    assert_eq!(analysis.ak_before(1), None);
+}
+
+#[test]
+fn test_branch_outcomes() {
+    let document = ParsedDocument::parse("    POB #0\n    SOZ done\ndone:\n    STP");
+    let analysis = document.values();
+    assert_eq!(
+        analysis.branch_outcome(document.cfg(), 1),
+        Some(BranchOutcome::AlwaysTaken)
+    );
+
+    let document = ParsedDocument::parse("    POB #3\n    SOM done\ndone:\n    STP");
+    let analysis = document.values();
+    assert_eq!(
+        analysis.branch_outcome(document.cfg(), 1),
+        Some(BranchOutcome::NeverTaken)
+    );
+}
+
+#[test]
+fn test_constant_diagnostics() {
+    assert_eq!(codes("    POB #0\n    SOZ done\ndone:\n    STP"), vec![DiagnosticCode::Sem023]);
+    assert_eq!(codes("    POB #2\n    SOZ done\ndone:\n    STP"), vec![DiagnosticCode::Sem024]);
+    assert_eq!(codes("    POB #8\n    DZI #0\n    STP"), vec![DiagnosticCode::Sem025]);
+    // unknown AK, no verdict; only the branch to the next line is hinted
+    assert_eq!(codes("    WEJSCIE\n    SOZ done\ndone:\n    STP"), vec![DiagnosticCode::Sem032]);
+}
+
+#[test]
+fn test_effects_follow_instruction_set_spelling() {
+    let content = "    pob #5\n    STP";
+
+    let document = ParsedDocument::parse(content);
+    assert_eq!(document.values().ak_after(0), Some(Interval::exact(5)));
+
+    // with case-sensitive mnemonics `pob` is an unknown instruction
+    let db = InstructionDatabase::new().with_case_sensitive(true);
+    let document = ParsedDocument::parse_with(content, db, MemoryModel::default());
+    assert_eq!(document.values().ak_after(0), None);
+}
@@ -16,6 +16,8 @@ fn test_hover_on_instruction() {
             assert!(markup.value.contains("POB"));
             assert!(markup.value.contains("Load"));
             assert!(markup.value.contains("Memory"));
+            assert!(markup.value.contains("**Cycles:** 2"));
+            assert!(markup.value.contains("POB liczba"));
         },
         _ => panic!("Expected markup content"),
     }
@@ -111,3 +113,106 @@ fn test_hover_on_unknown_word_returns_none() {
     
     assert!(hover.is_none());
 }
+
+fn hover_text(content: &str, line: u32, character: u32) -> String {
+    let provider = HoverProvider::new();
+    let hover = provider
+        .get_hover_info(content, Position { line, character })
+        .unwrap();
+    match hover.contents {
+        HoverContents::Markup(markup) => markup.value,
+        _ => panic!("Expected markup content"),
+    }
+}
+
+#[test]
+fn test_hover_on_number_literal() {
+    let text = hover_text("    POB #0xFF", 0, 10);
+    assert!(text.contains("**Decimal:** 255"));
+    assert!(text.contains("`0x00FF`"));
+    assert!(text.contains("`0b0000000011111111`"));
+    assert!(text.contains("**Two's complement (16-bit):** 255"));
+}
+
+#[test]
+fn test_hover_on_negative_literal() {
+    let provider = HoverProvider::new();
+    let hover = provider
+        .get_hover_info("    DOD #-1", Position { line: 0, character: 10 })
+        .unwrap();
+    assert_eq!(hover.range.unwrap().start.character, 9);
+
+    let text = hover_text("    DOD #-1", 0, 10);
+    assert!(text.contains("`0xFFFF`"));
+    assert!(text.contains("**Two's complement (16-bit):** -1"));
+}
+
+#[test]
+fn test_hover_on_overflowing_literal() {
+    let text = hover_text("    POB #70000", 0, 10);
+    assert!(text.contains("does not fit in a 16-bit word"));
+}
+
+#[test]
+fn test_hover_on_label_shows_stack_depth() {
+    let content = "start:\n    SDP\n    SOB next\nnext:\n    PZS\n    STP";
+
+    let info = hover_text(content, 3, 1);
+    assert!(info.contains("**Stack depth on entry:** 1"), "{}", info);
+}
+
+#[test]
+fn test_hover_on_instruction_shows_accumulator() {
+    let content = "    POB #5\n    DOD #2\n    STP";
+
+    let info = hover_text(content, 1, 5);
+    assert!(info.contains("**AK before:** 5"), "{}", info);
+    assert!(info.contains("**AK after:** 7"), "{}", info);
+}
+
+#[test]
+fn test_hover_on_mask_explains_bits() {
+    let content = "    MSK #5\n    STP";
+
+    let info = hover_text(content, 0, 5);
+    assert!(info.contains("`0b00000101`"), "{}", info);
+    assert!(info.contains("**Masked lines:** 0, 2"), "{}", info);
+    assert!(info.contains("**Enabled lines:** 1, 3, 4, 5, 6, 7"), "{}", info);
+
+    // the mask literal explains itself too
+    let info = hover_text(content, 0, 9);
+    assert!(info.contains("**Masked lines:** 0, 2"), "{}", info);
+}
+
+#[test]
+fn test_hover_on_interrupt_handler_label() {
+    let content = "; asmod-handler tick\n    STP\ntick:\n    PWR";
+
+    let info = hover_text(content, 2, 1);
+    assert!(info.contains("**Interrupt handler:**"), "{}", info);
+}
+
+#[test]
+fn test_hover_on_label_shows_address() {
+    let content = "start:\n    POB x\n    STP\nx: RST 5";
+
+    let info = hover_text(content, 1, 8);
+    assert!(info.contains("**Address:** 2 (data)"), "{}", info);
+    let info = hover_text(content, 0, 1);
+    assert!(info.contains("**Address:** 0 (code)"), "{}", info);
+}
+
+#[test]
+fn test_hover_on_alias_and_lowercase() {
+    let provider = HoverProvider::new();
+
+    for content in ["LAD wynik", "pob wynik"] {
+        let hover = provider
+            .get_hover_info(content, Position { line: 0, character: 1 })
+            .unwrap();
+        match hover.contents {
+            HoverContents::Markup(markup) => assert!(markup.value.contains("**Operation:**")),
+            _ => panic!("Expected markup content"),
+        }
+    }
+}
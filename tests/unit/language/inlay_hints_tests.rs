@@ -0,0 +1,88 @@
+use asmodeus_lsp::analysis::language::InlayHintProvider;
+use asmodeus_lsp::analysis::utils::InstructionDatabase;
+use tower_lsp::lsp_types::*;
+
+fn whole(lines: u32) -> Range {
+    Range {
+        start: Position { line: 0, character: 0 },
+        end: Position { line: lines, character: 0 },
+    }
+}
+
+fn labels(hints: &[InlayHint]) -> Vec<(u32, String)> {
+    hints
+        .iter()
+        .map(|hint| match &hint.label {
+            InlayHintLabel::String(label) => (hint.position.line, label.clone()),
+            _ => panic!("Expected string label"),
+        })
+        .collect()
+}
+
+#[test]
+fn test_stack_depth_after_push_and_pop() {
+    let provider = InlayHintProvider::new();
+    let content = "    SDP\n    SDP ; save\n    PZS\n    PZS\n    STP";
+
+    let hints = provider.get_inlay_hints(content, whole(5));
+    assert_eq!(
+        labels(&hints),
+        vec![
+            (0, "stack: 1".to_string()),
+            (1, "stack: 2".to_string()),
+            (2, "stack: 1".to_string()),
+            (3, "stack: 0".to_string()),
+        ]
+    );
+    // placed after the code, before the comment
+    assert_eq!(hints[1].position.character, 7);
+}
+
+#[test]
+fn test_hints_limited_to_range() {
+    let provider = InlayHintProvider::new();
+    let content = "    SDP\n    PZS\n    STP";
+    let range = Range {
+        start: Position { line: 1, character: 0 },
+        end: Position { line: 1, character: 7 },
+    };
+
+    let hints = provider.get_inlay_hints(content, range);
+    assert_eq!(labels(&hints), vec![(1, "stack: 0".to_string())]);
+}
+
+#[test]
+fn test_depth_range_after_merge() {
+    let provider = InlayHintProvider::new();
+    let content = "    SOZ skip\n    SDP\nskip:\n    SDP\n    STP";
+
+    let hints = provider.get_inlay_hints(content, whole(5));
+    assert_eq!(labels(&hints)[1], (3, "stack: 1..2".to_string()));
+}
+
+#[test]
+fn test_accumulator_hints() {
+    let provider = InlayHintProvider::new();
+    let content = "    POB #5\n    DOD #2\n    WYJSCIE\n    WEJSCIE\n    STP";
+
+    let hints = provider.get_inlay_hints(content, whole(5));
+    assert_eq!(
+        labels(&hints),
+        vec![(0, "AK = 5".to_string()), (1, "AK = 7".to_string())]
+    );
+}
+
+#[test]
+fn test_accumulator_hints_follow_instruction_set_spelling() {
+    let content = "    pob #5\n    dod #2\n    STP";
+
+    let hints = InlayHintProvider::new().get_inlay_hints(content, whole(3));
+    assert_eq!(
+        labels(&hints),
+        vec![(0, "AK = 5".to_string()), (1, "AK = 7".to_string())]
+    );
+
+    let provider = InlayHintProvider::new()
+        .with_instruction_db(InstructionDatabase::new().with_case_sensitive(true));
+    assert!(provider.get_inlay_hints(content, whole(3)).is_empty());
+}
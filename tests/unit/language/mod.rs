@@ -1,5 +1,6 @@
 mod completion_tests;
 mod hover_tests;
+mod inlay_hints_tests;
 mod navigation_tests;
 mod symbols_tests;
 mod semantic_tokens_tests;
@@ -90,3 +90,21 @@ fn test_find_references_invalid_position() {
     
     assert!(references.is_empty());
 }
+
+#[test]
+fn test_goto_definition_refuses_duplicate_label() {
+    let provider = NavigationProvider::new();
+    let content = r#"loop:
+    POB #1
+loop:
+    SOB loop"#;
+    let uri = Url::parse("file:///test.asmod").unwrap();
+    let position = Position { line: 3, character: 8 };
+
+    assert!(provider.get_definition(content, position, &uri).is_none());
+
+    let error = provider.check_unambiguous(content, position).unwrap_err();
+    let message = error.to_string();
+    assert!(message.contains("'loop'"));
+    assert!(message.contains("lines 1, 3"));
+}
@@ -1,4 +1,6 @@
+use asmodeus_lsp::analysis::language::semantic_tokens::{TOKEN_MODIFIERS, TOKEN_TYPES};
 use asmodeus_lsp::analysis::language::SemanticTokensProvider;
+use tower_lsp::lsp_types::{SemanticTokenModifier, SemanticTokenType};
 
 #[test]
 fn test_basic_semantic_tokens() {
@@ -77,7 +79,7 @@ fn test_operator_tokens() {
 #[test]
 fn test_number_tokens() {
     let provider = SemanticTokensProvider::new();
-    let content = "POB #42 0xFF 0b1010";
+    let content = "POB #42\nDOD #0xFF\nODE #0b1010";
 
     let tokens = provider.get_semantic_tokens(content);
 
@@ -101,3 +103,135 @@ fn test_whitespace_only() {
 
     assert!(tokens.is_empty());
 }
+
+#[test]
+fn test_rejected_line_tokens() {
+    // the lexer rejects '@', the line still gets its instruction and label
+    let tokens = decoded("start:\n    POB @x\n    SOB start");
+
+    assert_eq!(token_at(&tokens, 1, 4).0, SemanticTokenType::KEYWORD);
+    assert_eq!(token_at(&tokens, 2, 8).0, SemanticTokenType::FUNCTION);
+}
+
+#[test]
+fn test_legend_has_no_string_type() {
+    assert!(!TOKEN_TYPES.contains(&SemanticTokenType::STRING));
+}
+
+/// (line, start, type, modifiers) of every token, with absolute positions
+fn decoded(content: &str) -> Vec<(u32, u32, SemanticTokenType, Vec<SemanticTokenModifier>)> {
+    let provider = SemanticTokensProvider::new();
+    let (mut line, mut start) = (0, 0);
+    provider
+        .get_semantic_tokens(content)
+        .into_iter()
+        .map(|token| {
+            if token.delta_line > 0 {
+                start = 0;
+            }
+            line += token.delta_line;
+            start += token.delta_start;
+            let modifiers = TOKEN_MODIFIERS
+                .iter()
+                .enumerate()
+                .filter(|(bit, _)| token.token_modifiers_bitset & (1 << bit) != 0)
+                .map(|(_, modifier)| modifier.clone())
+                .collect();
+            (
+                line,
+                start,
+                TOKEN_TYPES[token.token_type as usize].clone(),
+                modifiers,
+            )
+        })
+        .collect()
+}
+
+fn token_at(
+    tokens: &[(u32, u32, SemanticTokenType, Vec<SemanticTokenModifier>)],
+    line: u32,
+    start: u32,
+) -> (SemanticTokenType, Vec<SemanticTokenModifier>) {
+    let (_, _, token_type, modifiers) = tokens
+        .iter()
+        .find(|(l, s, _, _)| *l == line && *s == start)
+        .unwrap_or_else(|| panic!("no token at {}:{}", line, start));
+    (token_type.clone(), modifiers.clone())
+}
+
+#[test]
+fn test_token_types_from_lexer() {
+    let content = "MAKRO dodaj a\n    DOD a\nKONM\nstart: POB 100\n    dodaj x\n    MNO #2\n    LAD x\n    SOB start\nx: RPA\nstala: RST 5\nzbedna: RST 1";
+    let tokens = decoded(content);
+    let unused = SemanticTokenModifier::new("unused");
+
+    // macro name and parameter, declared and used
+    assert_eq!(
+        token_at(&tokens, 0, 6),
+        (
+            SemanticTokenType::MACRO,
+            vec![SemanticTokenModifier::DECLARATION]
+        )
+    );
+    assert_eq!(
+        token_at(&tokens, 0, 12),
+        (
+            SemanticTokenType::PARAMETER,
+            vec![SemanticTokenModifier::DECLARATION]
+        )
+    );
+    assert_eq!(token_at(&tokens, 1, 8).0, SemanticTokenType::PARAMETER);
+    assert_eq!(token_at(&tokens, 4, 4).0, SemanticTokenType::MACRO);
+
+    // code label, numeric address and immediate
+    assert_eq!(
+        token_at(&tokens, 3, 0),
+        (
+            SemanticTokenType::FUNCTION,
+            vec![SemanticTokenModifier::DECLARATION]
+        )
+    );
+    assert_eq!(
+        token_at(&tokens, 3, 11).0,
+        SemanticTokenType::new("address")
+    );
+    assert_eq!(token_at(&tokens, 5, 9).0, SemanticTokenType::NUMBER);
+    assert_eq!(token_at(&tokens, 7, 8).0, SemanticTokenType::FUNCTION);
+
+    // extended instruction and alias
+    assert_eq!(
+        token_at(&tokens, 5, 4),
+        (
+            SemanticTokenType::KEYWORD,
+            vec![SemanticTokenModifier::DEFAULT_LIBRARY]
+        )
+    );
+    assert_eq!(
+        token_at(&tokens, 6, 4),
+        (SemanticTokenType::KEYWORD, vec![])
+    );
+
+    // data labels: written, constant, and constant nothing refers to
+    assert_eq!(
+        token_at(&tokens, 6, 8),
+        (SemanticTokenType::VARIABLE, vec![])
+    );
+    assert_eq!(
+        token_at(&tokens, 8, 0),
+        (
+            SemanticTokenType::VARIABLE,
+            vec![SemanticTokenModifier::DECLARATION]
+        )
+    );
+    assert_eq!(
+        token_at(&tokens, 10, 0),
+        (
+            SemanticTokenType::VARIABLE,
+            vec![
+                SemanticTokenModifier::DECLARATION,
+                SemanticTokenModifier::READONLY,
+                unused
+            ]
+        )
+    );
+}
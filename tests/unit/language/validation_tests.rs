@@ -72,3 +72,192 @@ fn test_validate_symbol_usage_allowed_characters() {
     let diagnostics = provider.validate_symbol_usage(content);
     assert!(diagnostics.is_empty());
 }
+
+#[test]
+fn test_validate_label_references_undefined() {
+    let provider = ValidationProvider::new();
+    let content = r#"start:
+    POB counter
+    SOZ finish
+loop:
+    SOB lop
+counter: RST 0"#;
+    let uri = Url::parse("file:///test.asmod").unwrap();
+
+    let diagnostics = provider.validate_label_references(content, &uri);
+    assert_eq!(diagnostics.len(), 2);
+
+    let diagnostic = &diagnostics[0];
+    assert_eq!(diagnostic.code, Some(NumberOrString::String("SEM008".to_string())));
+    assert_eq!(diagnostic.message, "Undefined label: 'finish'");
+    assert_eq!(
+        diagnostic.data,
+        Some(serde_json::json!({ "code": "SEM008", "label": "finish" }))
+    );
+    assert_eq!(diagnostic.range.start, Position { line: 2, character: 8 });
+    assert_eq!(diagnostic.range.end, Position { line: 2, character: 14 });
+    assert!(diagnostic.related_information.is_none());
+
+    // similar label listed as related information
+    let related = diagnostics[1].related_information.as_ref().unwrap();
+    assert_eq!(related.len(), 1);
+    assert_eq!(related[0].location.range.start, Position { line: 3, character: 0 });
+    assert!(related[0].message.contains("loop"));
+}
+
+#[test]
+fn test_validate_label_references_ignores_immediates_and_comments() {
+    let provider = ValidationProvider::new();
+    let content = r#"start:
+    POB #5      ; SOB nowhere
+    ŁAD [value]
+    STP
+value: RST 0"#;
+    let uri = Url::parse("file:///test.asmod").unwrap();
+
+    let diagnostics = provider.validate_label_references(content, &uri);
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+}
+
+#[test]
+fn test_validate_label_definitions_duplicates() {
+    let provider = ValidationProvider::new();
+    let content = r#"loop:
+    POB #1
+loop:
+    SOB loop
+end:
+    STP"#;
+    let uri = Url::parse("file:///test.asmod").unwrap();
+
+    let diagnostics = provider.validate_label_definitions(content, &uri);
+    assert_eq!(diagnostics.len(), 2);
+
+    for (diagnostic, other_line) in diagnostics.iter().zip([2, 0]) {
+        assert_eq!(diagnostic.code, Some(NumberOrString::String("SEM009".to_string())));
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::ERROR));
+
+        let related = diagnostic.related_information.as_ref().unwrap();
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].location.range.start.line, other_line);
+    }
+}
+
+fn semantic_codes(content: &str) -> Vec<(String, String)> {
+    ValidationProvider::new()
+        .validate_semantics(content)
+        .into_iter()
+        .map(|d| match d.code {
+            Some(NumberOrString::String(code)) => (code, d.message),
+            _ => (String::new(), d.message),
+        })
+        .collect()
+}
+
+#[test]
+fn test_validate_operand_kinds() {
+    let content = r#"loop:
+    SOB #5
+    MSK loop
+    ŁAD #100
+    STP"#;
+    let diagnostics = ValidationProvider::new().validate_semantics(content);
+    let codes: Vec<_> = semantic_codes(content).into_iter().map(|(code, _)| code).collect();
+    assert_eq!(codes, vec!["SEM010", "SEM011", "SEM012"]);
+
+    // operand itself is underlined
+    assert_eq!(diagnostics[0].range.start, Position { line: 1, character: 8 });
+    assert_eq!(diagnostics[0].range.end, Position { line: 1, character: 10 });
+    assert!(diagnostics[2].message.contains("'#100'"));
+
+    let codes = semantic_codes("loop:\n    SOB #loop");
+    assert_eq!(codes[0].0, "SEM010");
+}
+
+#[test]
+fn test_validate_operand_kinds_accepts_matching_operands() {
+    let content = r#"loop:
+    SOB loop
+    MSK #3
+    ŁAD 100
+    ŁAD [ptr]
+    POB #1
+    POB value
+    STP"#;
+    assert!(semantic_codes(content).is_empty(), "{:?}", semantic_codes(content));
+}
+
+#[test]
+fn test_validate_numeric_literals() {
+    let provider = ValidationProvider::new();
+    let content = r#"start:
+    POB #70000
+    DOD #-32768
+    ŁAD 300
+    POB [255]
+big: RST 0x10000
+    STP"#;
+
+    let diagnostics = provider.validate_numeric_literals(content);
+    let found: Vec<(u32, String)> = diagnostics
+        .iter()
+        .map(|d| match &d.code {
+            Some(NumberOrString::String(code)) => (d.range.start.line, code.clone()),
+            _ => (d.range.start.line, String::new()),
+        })
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (1, "SEM013".to_string()),
+            (3, "SEM014".to_string()),
+            (5, "SEM013".to_string()),
+        ]
+    );
+    assert_eq!(diagnostics[0].range.start.character, 9);
+    assert_eq!(diagnostics[0].range.end.character, 14);
+}
+
+#[test]
+fn test_validate_numeric_literals_with_custom_memory_model() {
+    use asmodeus_lsp::analysis::utils::MemoryModel;
+
+    let provider = ValidationProvider::new().with_memory_model(MemoryModel::new(8, 5));
+    let content = "    POB #300\n    ŁAD 40\n    STP";
+
+    let diagnostics = provider.validate_numeric_literals(content);
+    assert_eq!(diagnostics.len(), 2);
+    assert!(diagnostics[0].message.contains("8-bit word"));
+    assert!(diagnostics[1].message.contains("32 words"));
+}
+
+#[test]
+fn test_validate_malformed_literals() {
+    let provider = ValidationProvider::new();
+    let content = "    POB #0xZZ\n    DOD 0b102 ; 0xQQ in comment\n    STP";
+
+    let diagnostics = provider.validate_numeric_literals(content);
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].message, "Malformed hexadecimal literal '0xZZ'");
+    assert_eq!(diagnostics[1].message, "Malformed binary literal '0b102'");
+    assert_eq!(diagnostics[1].code, Some(NumberOrString::String("SEM015".to_string())));
+}
+
+#[test]
+fn test_validate_unused_labels() {
+    let provider = ValidationProvider::new();
+    let content = "start:\n    POB x\n    SOB done\ndone:\n    STP\nspare:\n    STP\nx: RST 1";
+
+    let diagnostics = provider.validate_unused_labels(content);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "Label 'spare' is never used");
+    assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::HINT));
+    assert_eq!(diagnostics[0].tags, Some(vec![DiagnosticTag::UNNECESSARY]));
+    assert_eq!(diagnostics[0].range.start, Position { line: 5, character: 0 });
+
+    // exported labels and interrupt handlers are entered from outside
+    let provider = ValidationProvider::new().with_exported_labels(vec!["spare".to_string()]);
+    assert!(provider.validate_unused_labels(content).is_empty());
+    let content = "; asmod-handler tick\nstart:\n    STP\ntick:\n    PWR";
+    assert!(ValidationProvider::new().validate_unused_labels(content).is_empty());
+}
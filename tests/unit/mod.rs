@@ -2,3 +2,4 @@ mod utils;
 mod language;
 mod refactoring;
 mod core;
+mod flow;
@@ -14,6 +14,7 @@ fn test_get_code_actions_with_diagnostics() {
         },
         severity: Some(DiagnosticSeverity::ERROR),
         message: "Unknown instruction: 'DOX'".to_string(),
+        code: Some(NumberOrString::String("SEM001".to_string())),
         source: Some("asmodeus-lsp".to_string()),
         ..Default::default()
     };
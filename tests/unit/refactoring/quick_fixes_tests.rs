@@ -1,5 +1,6 @@
+use asmodeus_lsp::analysis::language::ValidationProvider;
 use asmodeus_lsp::analysis::refactoring::QuickFixProvider;
-use asmodeus_lsp::analysis::utils::{create_diagnostic, position_to_range};
+use asmodeus_lsp::analysis::utils::{create_diagnostic, position_to_range, DiagnosticCode};
 use tower_lsp::lsp_types::*;
 
 #[test]
@@ -10,10 +11,8 @@ fn test_create_quick_fix_unknown_instruction() {
 
     let diagnostic = create_diagnostic(
         position_to_range(0, 0, 3),
-        DiagnosticSeverity::ERROR,
-        "SEM001",
-        "Unknown instruction: 'DOX'".to_string(),
-    );
+        DiagnosticCode::Sem001,
+        "Unknown instruction: 'DOX'".to_string());
 
     let action = provider.create_quick_fix(&diagnostic, content, &uri);
 
@@ -29,21 +28,19 @@ fn test_create_quick_fix_unknown_instruction() {
 }
 
 #[test]
-fn test_create_quick_fix_invalid_message_format() {
+fn test_create_quick_fix_reads_mnemonic_from_text() {
     let provider = QuickFixProvider::new();
-    let content = "DOX #42";
+    let content = "    DOX #42";
     let uri = Url::parse("file:///test.asmod").unwrap();
 
+    // the wording of the message does not matter
     let diagnostic = create_diagnostic(
-        position_to_range(0, 0, 3),
-        DiagnosticSeverity::ERROR,
-        "SEM001",
-        "Unknown instruction DOX".to_string(),
-    );
+        position_to_range(0, 4, 7),
+        DiagnosticCode::Sem001,
+        "Unknown instruction DOX".to_string());
 
-    let action = provider.create_quick_fix(&diagnostic, content, &uri);
-
-    assert!(action.is_none()); // cannot extract instruction name without quotes
+    let action = provider.create_quick_fix(&diagnostic, content, &uri).unwrap();
+    assert_eq!(edits_of(&action, &uri)[0].new_text, "DOD");
 }
 
 #[test]
@@ -65,6 +62,7 @@ fn test_create_quick_fix_no_suggestions() {
         },
         severity: Some(DiagnosticSeverity::ERROR),
         message: "Unknown instruction: 'COMPLETELY_UNKNOWN'".to_string(),
+        code: Some(NumberOrString::String("SEM001".to_string())),
         source: Some("asmodeus-lsp".to_string()),
         ..Default::default()
     };
@@ -121,6 +119,7 @@ fn test_workspace_edit_structure() {
         },
         severity: Some(DiagnosticSeverity::ERROR),
         message: "Unknown instruction: 'DOX'".to_string(),
+        code: Some(NumberOrString::String("SEM001".to_string())),
         source: Some("asmodeus-lsp".to_string()),
         ..Default::default()
     };
@@ -165,6 +164,7 @@ fn test_create_quick_fix_with_context_from_comment() {
         },
         severity: Some(DiagnosticSeverity::ERROR),
         message: "Unknown instruction: 'UNKNOWN_INST'".to_string(),
+        code: Some(NumberOrString::String("SEM001".to_string())),
         source: Some("asmodeus-lsp".to_string()),
         ..Default::default()
     };
@@ -203,6 +203,7 @@ fn test_create_quick_fix_with_context_from_surrounding_lines() {
         },
         severity: Some(DiagnosticSeverity::ERROR),
         message: "Unknown instruction: 'UNKNOWN_INST'".to_string(),
+        code: Some(NumberOrString::String("SEM001".to_string())),
         source: Some("asmodeus-lsp".to_string()),
         ..Default::default()
     };
@@ -244,6 +245,7 @@ fn test_create_quick_fix_validates_suggestions() {
         },
         severity: Some(DiagnosticSeverity::ERROR),
         message: "Unknown instruction: 'INVALID_COMPLETELY_WRONG'".to_string(),
+        code: Some(NumberOrString::String("SEM001".to_string())),
         source: Some("asmodeus-lsp".to_string()),
         ..Default::default()
     };
@@ -271,3 +273,307 @@ fn test_create_quick_fix_validates_suggestions() {
         }
     }
 }
+
+fn edits_of(action: &CodeActionOrCommand, uri: &Url) -> Vec<TextEdit> {
+    match action {
+        CodeActionOrCommand::CodeAction(action) => action
+            .edit
+            .as_ref()
+            .and_then(|edit| edit.changes.as_ref())
+            .and_then(|changes| changes.get(uri))
+            .cloned()
+            .unwrap_or_default(),
+        _ => panic!("Expected CodeAction"),
+    }
+}
+
+#[test]
+fn test_undefined_label_fixes() {
+    let provider = QuickFixProvider::new();
+    let content = "loop:\n    SOB lop\n    STP";
+    let uri = Url::parse("file:///test.asmod").unwrap();
+
+    // the label name comes from the diagnostic's data
+    let diagnostic = ValidationProvider::new()
+        .validate_label_references(content, &uri)
+        .remove(0);
+
+    let fixes = provider.create_quick_fixes(&diagnostic, content, &uri);
+    assert_eq!(fixes.len(), 2);
+
+    // closest match first
+    let rename = edits_of(&fixes[0], &uri);
+    assert_eq!(rename[0].range, diagnostic.range);
+    assert_eq!(rename[0].new_text, "loop");
+
+    // jump target created as bare label after the last line
+    let create = edits_of(&fixes[1], &uri);
+    assert_eq!(create[0].range.start, Position { line: 2, character: 7 });
+    assert_eq!(create[0].new_text, "\nlop:");
+}
+
+#[test]
+fn test_undefined_label_creates_data_cell() {
+    let provider = QuickFixProvider::new();
+    let content = "start:\n    POB counter\n    STP\n";
+    let uri = Url::parse("file:///test.asmod").unwrap();
+
+    let diagnostic = ValidationProvider::new()
+        .validate_label_references(content, &uri)
+        .remove(0);
+
+    let fixes = provider.create_quick_fixes(&diagnostic, content, &uri);
+    assert_eq!(fixes.len(), 1);
+
+    let create = edits_of(&fixes[0], &uri);
+    assert_eq!(create[0].range.start, Position { line: 3, character: 0 });
+    assert_eq!(create[0].new_text, "counter: RST 0\n");
+}
+
+fn single_edit(provider: &QuickFixProvider, content: &str, range: Range, code: DiagnosticCode, message: &str) -> Option<TextEdit> {
+    let uri = Url::parse("file:///test.asmod").unwrap();
+    let diagnostic = create_diagnostic(range, code, message.to_string());
+    provider
+        .create_quick_fix(&diagnostic, content, &uri)
+        .map(|action| edits_of(&action, &uri).remove(0))
+}
+
+#[test]
+fn test_operand_kind_fixes() {
+    let provider = QuickFixProvider::new();
+
+    let content = "loop:\n    SOB #loop\n    MSK 5\n    ŁAD #100";
+    let edit = single_edit(&provider, content, position_to_range(1, 8, 13), DiagnosticCode::Sem010,
+        "Instruction 'SOB' expects a label operand, found '#loop'").unwrap();
+    assert_eq!(edit.new_text, "loop");
+
+    let edit = single_edit(&provider, content, position_to_range(2, 8, 9), DiagnosticCode::Sem011,
+        "Instruction 'MSK' expects an immediate operand, found '5'").unwrap();
+    assert_eq!(edit.new_text, "#5");
+
+    let edit = single_edit(&provider, content, position_to_range(3, 8, 12), DiagnosticCode::Sem012,
+        "Instruction 'ŁAD' cannot store to an immediate value, found '#100'").unwrap();
+    assert_eq!(edit.new_text, "100");
+}
+
+#[test]
+fn test_operand_kind_without_obvious_rewrite() {
+    let provider = QuickFixProvider::new();
+    let content = "loop:\n    SOB #5\n    MSK loop";
+
+    assert!(single_edit(&provider, content, position_to_range(1, 8, 10), DiagnosticCode::Sem010,
+        "Instruction 'SOB' expects a label operand, found '#5'").is_none());
+    assert!(single_edit(&provider, content, position_to_range(2, 8, 12), DiagnosticCode::Sem011,
+        "Instruction 'MSK' expects an immediate operand, found 'loop'").is_none());
+}
+
+#[test]
+fn test_division_guard_fix() {
+    let provider = QuickFixProvider::new();
+    let uri = Url::parse("file:///test.asmod").unwrap();
+    let content = "    POB #8\n    DZI d ; halve\n    STP\nd: RPA";
+    let diagnostic = create_diagnostic(
+        position_to_range(1, 8, 9),
+        DiagnosticCode::Sem026,
+        "DZI divides by 'd', which is never written".to_string());
+
+    let action = provider.create_quick_fix(&diagnostic, content, &uri).unwrap();
+    let edits = edits_of(&action, &uri);
+    assert_eq!(edits.len(), 2);
+    assert_eq!(edits[0].range, position_to_range(1, 4, 9));
+    assert_eq!(
+        edits[0].new_text,
+        "ŁAD div_temp\n    POB d\n    SOZ div_skip\n    POB div_temp\n    DZI d\ndiv_skip:"
+    );
+    assert_eq!(edits[1].range, position_to_range(3, 6, 6));
+    assert_eq!(edits[1].new_text, "\ndiv_temp: RPA");
+}
+
+#[test]
+fn test_division_guard_avoids_existing_labels() {
+    let provider = QuickFixProvider::new();
+    let uri = Url::parse("file:///test.asmod").unwrap();
+    let content = "div_skip:\n    MOD d\n    STP\nd: RPA\n";
+    let diagnostic = create_diagnostic(
+        position_to_range(1, 8, 9),
+        DiagnosticCode::Sem026,
+        "MOD divides by 'd', which is never written".to_string());
+
+    let action = provider.create_quick_fix(&diagnostic, content, &uri).unwrap();
+    let edits = edits_of(&action, &uri);
+    assert!(edits[0].new_text.contains("SOZ div_skip_2"));
+    assert!(edits[0].new_text.ends_with("div_skip_2:"));
+    assert_eq!(edits[1].new_text, "div_temp: RPA");
+}
+
+#[test]
+fn test_remove_jump_to_next_instruction() {
+    let provider = QuickFixProvider::new();
+    let content = "    SOZ next\nnext:\n    STP";
+
+    let edit = single_edit(&provider, content, position_to_range(0, 4, 12), DiagnosticCode::Sem032,
+        "Jump to 'next' goes to the next instruction anyway").unwrap();
+    assert_eq!(edit.range.start, Position { line: 0, character: 0 });
+    assert_eq!(edit.range.end, Position { line: 1, character: 0 });
+    assert_eq!(edit.new_text, "");
+}
+
+#[test]
+fn test_retarget_jump_chain() {
+    let provider = QuickFixProvider::new();
+    let uri = Url::parse("file:///test.asmod").unwrap();
+    let content = "    SOZ a\n    STP\nb:\n    STP\na:\n    SOB b";
+    let diagnostic = create_diagnostic(
+        position_to_range(0, 8, 9),
+        DiagnosticCode::Sem033,
+        "Jump to 'a' only continues to 'b', jump there directly".to_string());
+
+    let action = provider.create_quick_fix(&diagnostic, content, &uri).unwrap();
+    match &action {
+        CodeActionOrCommand::CodeAction(action) => {
+            assert_eq!(action.title, "Jump directly to 'b'")
+        }
+        _ => panic!("Expected CodeAction"),
+    }
+    let edits = edits_of(&action, &uri);
+    assert_eq!(edits[0].range, position_to_range(0, 8, 9));
+    assert_eq!(edits[0].new_text, "b");
+}
+
+#[test]
+fn test_remove_unused_label() {
+    let provider = QuickFixProvider::new();
+    let message = "Label 'spare' is never used";
+
+    // alone on its line, the line goes
+    let content = "start:\n    STP\nspare:\n    STP";
+    let edit = single_edit(&provider, content, position_to_range(2, 0, 5), DiagnosticCode::Sem037, message).unwrap();
+    assert_eq!(edit.range.start, Position { line: 2, character: 0 });
+    assert_eq!(edit.range.end, Position { line: 3, character: 0 });
+    assert_eq!(edit.new_text, "");
+
+    // sharing its line with code, the code stays indented
+    let content = "start:\n    STP\nspare: STP";
+    let edit = single_edit(&provider, content, position_to_range(2, 0, 5), DiagnosticCode::Sem037, message).unwrap();
+    assert_eq!(edit.range, position_to_range(2, 0, 7));
+    assert_eq!(edit.new_text, "    ");
+}
+
+#[test]
+fn test_quick_fixes_follow_code_not_message() {
+    let provider = QuickFixProvider::new();
+    let content = "    SOZ next\nnext:\n    STP";
+
+    let edit = single_edit(&provider, content, position_to_range(0, 4, 12), DiagnosticCode::Sem032, "Reworded").unwrap();
+    assert_eq!(edit.range.end, Position { line: 1, character: 0 });
+
+    // the same message under an unrelated code gets no fix
+    assert!(single_edit(&provider, content, position_to_range(0, 4, 12), DiagnosticCode::Sem016,
+        "Jump to 'next' goes to the next instruction anyway").is_none());
+}
+
+#[test]
+fn test_suggest_suppression() {
+    let provider = QuickFixProvider::new();
+    let uri = Url::parse("file:///test.asmod").unwrap();
+    let diagnostic = create_diagnostic(
+        position_to_range(1, 4, 7),
+        DiagnosticCode::Sem016,
+        "Unreachable code".to_string());
+
+    // a new pragma above the line, at its indentation
+    let content = "    STP\n    POB x";
+    let fix = provider.suggest_suppression(&diagnostic, content, &uri).unwrap();
+    let edits = edits_of(&fix, &uri);
+    assert_eq!(edits[0].range, position_to_range(1, 0, 0));
+    assert_eq!(edits[0].new_text, "    ; asmod-disable-next-line SEM016\n");
+
+    // an existing pragma above gets the code appended
+    let content = "; asmod-disable-next-line SEM021 -- demo\n    POB x";
+    let fix = provider.suggest_suppression(&diagnostic, content, &uri).unwrap();
+    let edits = edits_of(&fix, &uri);
+    assert_eq!(edits[0].range, position_to_range(0, 32, 32));
+    assert_eq!(edits[0].new_text, " SEM016");
+
+    // nothing to suppress an unused suppression with
+    let unused = create_diagnostic(
+        position_to_range(0, 26, 32),
+        DiagnosticCode::Sem038,
+        "Suppression of 'SEM021' is never used".to_string());
+    assert!(provider.suggest_suppression(&unused, content, &uri).is_none());
+}
+
+#[test]
+fn test_rewrite_multiplication_for_base_isa() {
+    let provider = QuickFixProvider::new();
+    let uri = Url::parse("file:///test.asmod").unwrap();
+    let content = "start: POB a\n       MNO #3 ; triple\n       STP\na: RST 5";
+    let diagnostic = create_diagnostic(
+        position_to_range(1, 7, 10),
+        DiagnosticCode::Sem039,
+        "'MNO' is an extended instruction, not part of the base instruction set".to_string());
+
+    let action = provider.create_quick_fix(&diagnostic, content, &uri).unwrap();
+    let edits = edits_of(&action, &uri);
+    assert_eq!(edits.len(), 2);
+    assert_eq!(edits[0].range, position_to_range(1, 7, 13));
+    assert_eq!(
+        edits[0].new_text,
+        "ŁAD mul_a\n       POB #3\n       ŁAD mul_n\n       POB #0\n       ŁAD mul_r\n\
+         mul_loop:\n       POB mul_n\n       SOZ mul_done\n       ODE #1\n       ŁAD mul_n\n\
+         \x20      POB mul_r\n       DOD mul_a\n       ŁAD mul_r\n       SOB mul_loop\n\
+         mul_done:\n       POB mul_r"
+    );
+    assert_eq!(edits[1].range, position_to_range(3, 8, 8));
+    assert_eq!(edits[1].new_text, "\nmul_a: RPA\nmul_n: RPA\nmul_r: RPA");
+}
+
+#[test]
+fn test_rewrite_division_for_base_isa() {
+    let provider = QuickFixProvider::new();
+    let uri = Url::parse("file:///test.asmod").unwrap();
+    let diagnostic = create_diagnostic(
+        position_to_range(1, 4, 7),
+        DiagnosticCode::Sem039,
+        "'MOD' is an extended instruction, not part of the base instruction set".to_string());
+
+    // MOD keeps what is left of AK, fresh names avoid existing labels
+    let content = "    POB #7\n    MOD d\n    STP\nd: RST 2\ndiv_r: RST 0\n";
+    let action = provider.create_quick_fix(&diagnostic, content, &uri).unwrap();
+    let edits = edits_of(&action, &uri);
+    assert!(edits[0].new_text.starts_with("ŁAD div_r_2\n    POB #0\n    ŁAD div_q\ndiv_loop:"));
+    assert!(edits[0].new_text.contains("\n    ODE d\n    SOM div_done\n"));
+    assert!(edits[0].new_text.ends_with("\ndiv_done:\n    POB div_r_2"));
+    assert_eq!(edits[1].new_text, "div_r_2: RPA\ndiv_q: RPA");
+
+    // DZI keeps the quotient
+    let content = "    POB #7\n    DZI d\n    STP\nd: RST 2";
+    let action = provider.create_quick_fix(&diagnostic, content, &uri).unwrap();
+    assert!(edits_of(&action, &uri)[0].new_text.ends_with("\ndiv_done:\n    POB div_q"));
+}
+
+#[test]
+fn test_normalize_mnemonics() {
+    let provider = QuickFixProvider::new();
+    let uri = Url::parse("file:///test.asmod").unwrap();
+    let content = "start: pob a\n       LAD b\n       WEJŚCIE\n       STP\na: RST 5\nb: RPA";
+    let diagnostic = create_diagnostic(
+        position_to_range(1, 7, 10),
+        DiagnosticCode::Sem041,
+        "'LAD' is not the canonical spelling of 'ŁAD'".to_string());
+
+    let actions = provider.create_quick_fixes(&diagnostic, content, &uri);
+    assert_eq!(actions.len(), 2);
+
+    let edits = edits_of(&actions[0], &uri);
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].range, position_to_range(1, 7, 10));
+    assert_eq!(edits[0].new_text, "ŁAD");
+
+    let edits = edits_of(&actions[1], &uri);
+    let fixed: Vec<(u32, &str)> = edits
+        .iter()
+        .map(|edit| (edit.range.start.line, edit.new_text.as_str()))
+        .collect();
+    assert_eq!(fixed, vec![(0, "POB"), (1, "ŁAD"), (2, "WEJSCIE")]);
+}
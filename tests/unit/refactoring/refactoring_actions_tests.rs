@@ -220,3 +220,42 @@ fn test_no_uppercase_action_for_already_uppercase() {
 
     assert!(uppercase_action.is_none());
 }
+
+#[test]
+fn test_remove_all_unused_labels_action() {
+    let provider = RefactoringActionsProvider::new();
+    let content = "start:\n    STP\na:\n    STP\nb: STP";
+    let uri = Url::parse("file:///test.asmod").unwrap();
+    let range = Range {
+        start: Position {
+            line: 0,
+            character: 0,
+        },
+        end: Position {
+            line: 0,
+            character: 0,
+        },
+    };
+
+    let actions = provider.get_refactoring_actions(content, range, &uri);
+    let action = actions
+        .iter()
+        .find_map(|action| match action {
+            CodeActionOrCommand::CodeAction(action)
+                if action.title == "Remove all unused labels" =>
+            {
+                Some(action)
+            }
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(action.kind, Some(CodeActionKind::new("source.removeUnusedLabels")));
+
+    let edits = &action.edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+    assert_eq!(edits.len(), 2);
+    assert_eq!(edits[0].range.start, Position { line: 2, character: 0 });
+    assert_eq!(edits[1].range, Range {
+        start: Position { line: 4, character: 0 },
+        end: Position { line: 4, character: 3 },
+    });
+}
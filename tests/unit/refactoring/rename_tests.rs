@@ -155,3 +155,18 @@ loop:
     
     assert_eq!(file_edits.len(), 4); // 1 definition + 3 references
 }
+
+#[test]
+fn test_rename_refuses_duplicate_label() {
+    let provider = RenameProvider::new();
+    let content = r#"loop:
+    POB #1
+loop:
+    SOB loop"#;
+    let uri = Url::parse("file:///test.asmod").unwrap();
+    let position = Position { line: 0, character: 1 };
+
+    assert!(provider.get_rename_range(content, position).is_none());
+    assert!(provider.rename_symbol(content, position, "again", &uri).is_none());
+    assert!(provider.check_unambiguous(content, position).is_err());
+}
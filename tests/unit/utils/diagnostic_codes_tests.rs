@@ -0,0 +1,56 @@
+use asmodeus_lsp::analysis::utils::{create_diagnostic, position_to_range, DiagnosticCode};
+use tower_lsp::lsp_types::*;
+
+const DIAGNOSTICS_DOC: &str = include_str!("../../../docs/diagnostics.md");
+
+#[test]
+fn test_codes_round_trip() {
+    for &code in DiagnosticCode::ALL {
+        assert_eq!(DiagnosticCode::from_code(code.as_str()), Some(code));
+        assert!(!code.title().is_empty());
+        assert!(!code.explanation().is_empty());
+    }
+    assert_eq!(DiagnosticCode::from_code("TEST001"), None);
+}
+
+#[test]
+fn test_every_code_is_documented() {
+    for &code in DiagnosticCode::ALL {
+        assert!(
+            DIAGNOSTICS_DOC.contains(&format!("### {}\n", code)),
+            "{} has no section in docs/diagnostics.md",
+            code
+        );
+    }
+}
+
+#[test]
+fn test_registered_code_fills_description_and_data() {
+    let diagnostic = create_diagnostic(
+        position_to_range(0, 0, 5),
+        DiagnosticCode::Sem037,
+        "Label 'spare' is never used".to_string());
+
+    assert_eq!(diagnostic.code, Some(NumberOrString::String("SEM037".to_string())));
+    assert!(diagnostic
+        .code_description
+        .unwrap()
+        .href
+        .as_str()
+        .ends_with("docs/diagnostics.md#sem037"));
+    assert_eq!(diagnostic.data, Some(serde_json::json!({ "code": "SEM037" })));
+    assert_eq!(diagnostic.tags, Some(vec![DiagnosticTag::UNNECESSARY]));
+    assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::HINT));
+}
+
+#[test]
+fn test_code_read_back_from_data() {
+    let mut diagnostic = create_diagnostic(
+        position_to_range(0, 0, 5),
+        DiagnosticCode::Sem008,
+        "Undefined label: 'lop'".to_string());
+    assert_eq!(DiagnosticCode::of(&diagnostic), Some(DiagnosticCode::Sem008));
+
+    diagnostic.code = None;
+    assert_eq!(DiagnosticCode::of(&diagnostic), Some(DiagnosticCode::Sem008));
+}
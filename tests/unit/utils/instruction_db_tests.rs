@@ -78,7 +78,7 @@ fn test_all_instructions_covered() {
         all_instructions.extend(db.get_instructions_by_category(category));
     }
 
-    let instruction_names: Vec<&str> = all_instructions.iter().map(|i| i.name).collect();
+    let instruction_names: Vec<&str> = all_instructions.iter().map(|i| i.name.as_str()).collect();
 
     assert!(instruction_names.contains(&"POB"));
     assert!(instruction_names.contains(&"DOD"));
@@ -91,3 +91,86 @@ fn test_all_instructions_covered() {
     assert!(instruction_names.contains(&"WEJSCIE"));
     assert!(instruction_names.contains(&"WYJSCIE"));
 }
+
+const CUSTOM_SET: &str = r#"
+[[instruction]]
+mnemonic = "INC"
+aliases = ["INK"]
+operand = "address"
+category = "arithmetic"
+description = "Increment a memory cell"
+operation = "(address) + 1 → (address)"
+cycles = 3
+examples = ["INC licznik"]
+
+[[instruction]]
+mnemonic = "STP"
+operand = "none"
+category = "control-flow"
+description = "Stop program execution"
+operation = "Halt execution"
+"#;
+
+#[test]
+fn test_embedded_instruction_set() {
+    let db = InstructionDatabase::new();
+
+    let lad = db.get_instruction("ŁAD").unwrap();
+    assert_eq!(lad.aliases, vec!["LAD".to_string()]);
+    assert_eq!(lad.cycles, 2);
+    assert_eq!(lad.parameter().unwrap().name, "address");
+
+    let msk = db.get_instruction("MSK").unwrap();
+    assert_eq!(msk.parameter().unwrap().name, "mask");
+    assert_eq!(db.get_instruction("SOB").unwrap().parameter().unwrap().name, "label");
+    assert!(db.get_instruction("STP").unwrap().parameter().is_none());
+
+    assert_eq!(db.instructions().next().unwrap().name, "DOD");
+}
+
+#[test]
+fn test_custom_instruction_set() {
+    let db = InstructionDatabase::from_toml(CUSTOM_SET).unwrap();
+    assert!(db.is_valid_instruction("INC"));
+    assert!(!db.is_valid_instruction("POB"));
+
+    let inc = db.get_instruction("INC").unwrap();
+    assert_eq!(inc.cycles, 3);
+    assert_eq!(inc.examples, vec!["INC licznik".to_string()]);
+    assert_eq!(db.get_instruction("STP").unwrap().cycles, 1);
+
+    let json = r#"{ "instruction": [ { "mnemonic": "NOP", "operand": "none",
+        "category": "control-flow", "description": "Do nothing", "operation": "Nothing" } ] }"#;
+    let db = InstructionDatabase::from_json(json).unwrap();
+    assert!(db.is_valid_instruction("NOP"));
+}
+
+#[test]
+fn test_invalid_instruction_set_is_rejected() {
+    // alias clashing with another mnemonic
+    let clash = CUSTOM_SET.replace(r#"aliases = ["INK"]"#, r#"aliases = ["stp"]"#);
+    assert!(InstructionDatabase::from_toml(&clash).is_err());
+
+    let unknown_operand = CUSTOM_SET.replace(r#"operand = "address""#, r#"operand = "register""#);
+    assert!(InstructionDatabase::from_toml(&unknown_operand).is_err());
+
+    assert!(InstructionDatabase::from_toml("[[instruction]]\nmnemonic = \"X\"").is_err());
+}
+
+#[test]
+fn test_aliases_and_case() {
+    let db = InstructionDatabase::new();
+
+    assert_eq!(db.canonical_name("LAD"), Some("ŁAD"));
+    assert_eq!(db.canonical_name("lad"), Some("ŁAD"));
+    assert_eq!(db.canonical_name("WEJŚCIE"), Some("WEJSCIE"));
+    assert_eq!(db.canonical_name("pob"), Some("POB"));
+    assert_eq!(db.get_instruction("wyj").unwrap().name, "WYJSCIE");
+    assert!(db.canonical_name("POBB").is_none());
+
+    let db = db.with_case_sensitive(true);
+    assert_eq!(db.canonical_name("LAD"), Some("ŁAD"));
+    assert_eq!(db.canonical_name("WEJŚCIE"), Some("WEJSCIE"));
+    assert!(!db.is_valid_instruction("pob"));
+    assert!(!db.is_valid_instruction("lad"));
+}
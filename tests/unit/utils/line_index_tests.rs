@@ -0,0 +1,58 @@
+use asmodeus_lsp::analysis::utils::{LineIndex, PositionEncoding};
+use tower_lsp::lsp_types::*;
+
+#[test]
+fn test_line_index_lines() {
+    let text = "start:\n    POB #42\r\n    STP";
+    let index = LineIndex::new(text);
+
+    assert_eq!(index.line_count(), 3);
+    assert_eq!(index.line_text(text, 0), Some("start:"));
+    assert_eq!(index.line_text(text, 1), Some("    POB #42"));
+    assert_eq!(index.line_text(text, 2), Some("    STP"));
+    assert_eq!(index.line_text(text, 3), None);
+}
+
+#[test]
+fn test_line_index_offset() {
+    let text = "start:\n    POB #42";
+    let index = LineIndex::new(text);
+
+    assert_eq!(index.offset(text, Position { line: 0, character: 0 }, PositionEncoding::Utf16), 0);
+    assert_eq!(index.offset(text, Position { line: 1, character: 4 }, PositionEncoding::Utf16), 11);
+
+    // clamped to line end and document end
+    assert_eq!(index.offset(text, Position { line: 0, character: 100 }, PositionEncoding::Utf16), 6);
+    assert_eq!(index.offset(text, Position { line: 9, character: 0 }, PositionEncoding::Utf16), text.len());
+}
+
+#[test]
+fn test_line_index_offset_utf16_columns() {
+    let text = "    ŁAD wynik ; wejście";
+    let index = LineIndex::new(text);
+
+    // 'Ł' is one UTF-16 unit but two bytes
+    assert_eq!(index.offset(text, Position { line: 0, character: 5 }, PositionEncoding::Utf16), 6);
+    assert_eq!(&text[index.offset(text, Position { line: 0, character: 8 }, PositionEncoding::Utf16)..][..5], "wynik");
+}
+
+#[test]
+fn test_line_index_apply_edit_matches_rebuild() {
+    let mut text = String::from("start:\n    POB #42\n    STP\n");
+    let mut index = LineIndex::new(&text);
+
+    let edits: [(usize, usize, &str); 4] = [
+        (7, 7, "loop:\n"),                          // insert a line
+        (13, 24, "    DOD #1\n    SOB loop"),        // replace across lines
+        (0, 7, ""),                                 // delete first line
+        (text.len() - 2, text.len() - 2, "\n\n"), // insert blank lines
+    ];
+
+    for (start, end, new_text) in edits {
+        let end = end.min(text.len());
+        let start = start.min(end);
+        text.replace_range(start..end, new_text);
+        index.apply_edit(start, end, new_text);
+        assert_eq!(index, LineIndex::new(&text));
+    }
+}
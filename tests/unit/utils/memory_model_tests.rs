@@ -0,0 +1,50 @@
+use asmodeus_lsp::analysis::utils::*;
+
+#[test]
+fn test_parse_number_literal() {
+    assert_eq!(parse_number_literal("42"), Some(42));
+    assert_eq!(parse_number_literal("-5"), Some(-5));
+    assert_eq!(parse_number_literal("0xFF"), Some(255));
+    assert_eq!(parse_number_literal("0b1010"), Some(10));
+
+    assert_eq!(parse_number_literal("0xZZ"), None);
+    assert_eq!(parse_number_literal("0b102"), None);
+    assert_eq!(parse_number_literal("12ab"), None);
+    assert_eq!(parse_number_literal("0x"), None);
+
+    // too large for i128 still overflows any word
+    assert_eq!(parse_number_literal(&"9".repeat(60)), Some(i128::MAX));
+}
+
+#[test]
+fn test_memory_model_ranges() {
+    let model = MemoryModel::default();
+    assert_eq!(model.word_bits, 16);
+    assert_eq!(model.memory_size(), 256);
+
+    assert!(model.fits_word(65535));
+    assert!(model.fits_word(-32768));
+    assert!(!model.fits_word(65536));
+    assert!(!model.fits_word(-32769));
+
+    assert!(model.fits_address(255));
+    assert!(!model.fits_address(256));
+    assert!(!model.fits_address(-1));
+}
+
+#[test]
+fn test_twos_complement() {
+    let model = MemoryModel::new(8, 5);
+    assert_eq!(model.word_pattern(-1), 0xFF);
+    assert_eq!(model.signed_value(0xFF), -1);
+    assert_eq!(model.signed_value(0x7F), 127);
+    assert_eq!(model.memory_size(), 32);
+}
+
+#[test]
+fn test_find_number_literals() {
+    let literals = find_number_literals("    POB #-5 DOD 0xZZ x1: 1abc: value");
+    let texts: Vec<&str> = literals.iter().map(|(text, _, _)| text.as_str()).collect();
+    assert_eq!(texts, vec!["-5", "0xZZ"]);
+    assert_eq!((literals[0].1, literals[0].2), (9, 11));
+}
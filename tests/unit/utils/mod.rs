@@ -1,3 +1,7 @@
+mod diagnostic_codes_tests;
 mod instruction_db_tests;
+mod line_index_tests;
+mod memory_model_tests;
+mod position_encoding_tests;
 mod text_utils_tests;
 mod position_utils_tests;
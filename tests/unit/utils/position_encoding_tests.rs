@@ -0,0 +1,167 @@
+use asmodeus_lsp::analysis::language::{HoverProvider, SemanticTokensProvider, ValidationProvider};
+use asmodeus_lsp::analysis::refactoring::RenameProvider;
+use asmodeus_lsp::analysis::utils::*;
+use tower_lsp::lsp_types::*;
+
+// "ŁAD" is 3 chars, 3 UTF-16 units and 4 UTF-8 bytes
+const POLISH_LINE: &str = "    ŁAD wynik    ; zapisz wejście";
+
+#[test]
+fn test_negotiate_position_encoding() {
+    assert_eq!(PositionEncoding::negotiate(None), PositionEncoding::Utf16);
+    assert_eq!(PositionEncoding::negotiate(Some(&[])), PositionEncoding::Utf16);
+    assert_eq!(
+        PositionEncoding::negotiate(Some(&[PositionEncodingKind::UTF8, PositionEncodingKind::UTF16])),
+        PositionEncoding::Utf8
+    );
+    assert_eq!(
+        PositionEncoding::negotiate(Some(&[
+            PositionEncodingKind::new("utf-7"),
+            PositionEncodingKind::UTF32
+        ])),
+        PositionEncoding::Utf32
+    );
+    assert_eq!(PositionEncoding::Utf8.kind(), PositionEncodingKind::UTF8);
+}
+
+#[test]
+fn test_char_column_round_trip() {
+    // "wynik" starts at char 8
+    assert_eq!(PositionEncoding::Utf8.from_char_column(POLISH_LINE, 8), 9);
+    assert_eq!(PositionEncoding::Utf16.from_char_column(POLISH_LINE, 8), 8);
+    assert_eq!(PositionEncoding::Utf32.from_char_column(POLISH_LINE, 8), 8);
+
+    assert_eq!(PositionEncoding::Utf8.to_char_column(POLISH_LINE, 9), 8);
+    assert_eq!(PositionEncoding::Utf16.to_char_column(POLISH_LINE, 8), 8);
+
+    // column inside the two bytes of 'Ł' snaps to its start
+    assert_eq!(PositionEncoding::Utf8.to_char_column(POLISH_LINE, 5), 4);
+
+    // beyond the line end is clamped
+    assert_eq!(
+        PositionEncoding::Utf16.to_char_column(POLISH_LINE, 500),
+        POLISH_LINE.chars().count()
+    );
+}
+
+#[test]
+fn test_surrogate_pairs_in_utf16() {
+    let line = "; 🦀 ok";
+
+    // the crab is one char but two UTF-16 units
+    assert_eq!(PositionEncoding::Utf16.from_char_column(line, 4), 5);
+    assert_eq!(PositionEncoding::Utf16.to_char_column(line, 5), 4);
+    assert_eq!(PositionEncoding::Utf8.to_byte_offset(line, 7), 7);
+    assert_eq!(PositionEncoding::Utf16.to_byte_offset(line, 5), 7);
+}
+
+#[test]
+fn test_converter_ranges() {
+    let content = format!("start:\n{}", POLISH_LINE);
+    let converter = PositionConverter::new(&content, PositionEncoding::Utf8);
+
+    let internal = Range {
+        start: Position { line: 1, character: 8 },
+        end: Position { line: 1, character: 13 },
+    };
+    let lsp = converter.to_lsp_range(internal);
+    assert_eq!(lsp.start.character, 9);
+    assert_eq!(lsp.end.character, 14);
+    assert_eq!(converter.to_internal_range(lsp), internal);
+}
+
+#[test]
+fn test_hover_range_after_multibyte_instruction() {
+    let provider = HoverProvider::new();
+    let content = "wynik:\n    ŁAD wynik ; wejście";
+
+    let hover = provider
+        .get_hover_info(content, Position { line: 1, character: 10 })
+        .unwrap();
+    let range = hover.range.unwrap();
+
+    // char columns internally
+    assert_eq!(range.start.character, 8);
+    assert_eq!(range.end.character, 13);
+
+    // client sees byte columns with UTF-8
+    let converter = PositionConverter::new(content, PositionEncoding::Utf8);
+    let lsp_range = converter.to_lsp_range(range);
+    assert_eq!(lsp_range.start.character, 9);
+    assert_eq!(lsp_range.end.character, 14);
+}
+
+#[test]
+fn test_hover_on_polish_instruction() {
+    let provider = HoverProvider::new();
+    let content = "    ŁAD 100";
+
+    let hover = provider
+        .get_hover_info(content, Position { line: 0, character: 5 })
+        .unwrap();
+    let range = hover.range.unwrap();
+    assert_eq!(range.start.character, 4);
+    assert_eq!(range.end.character, 7);
+}
+
+#[test]
+fn test_rename_edits_on_polish_lines() {
+    let provider = RenameProvider::new();
+    let uri = Url::parse("file:///test.asmod").unwrap();
+    let content = "wynik:\n    ŁAD wynik ; wejście wynik\n    POB wynik";
+
+    let edit = provider
+        .rename_symbol(content, Position { line: 0, character: 1 }, "suma", &uri)
+        .unwrap();
+    let changes = edit.changes.unwrap();
+    let edits = changes.get(&uri).unwrap();
+
+    let on_polish_line: Vec<&TextEdit> =
+        edits.iter().filter(|edit| edit.range.start.line == 1).collect();
+    assert!(!on_polish_line.is_empty());
+    assert_eq!(on_polish_line[0].range.start.character, 8);
+    assert_eq!(on_polish_line[0].range.end.character, 13);
+}
+
+#[test]
+fn test_semantic_tokens_after_multibyte_chars() {
+    let provider = SemanticTokensProvider::new();
+    let content = "    ŁAD wynik ; wejście";
+
+    let tokens = provider.get_semantic_tokens(content);
+    assert_eq!(tokens.len(), 3);
+    assert_eq!((tokens[0].delta_start, tokens[0].length), (4, 3)); // ŁAD
+    assert_eq!((tokens[1].delta_start, tokens[1].length), (4, 5)); // wynik
+    assert_eq!((tokens[2].delta_start, tokens[2].length), (6, 9)); // comment
+
+    let converter = PositionConverter::new(content, PositionEncoding::Utf8);
+    let utf8_tokens = converter.to_lsp_semantic_tokens(tokens);
+    assert_eq!((utf8_tokens[0].delta_start, utf8_tokens[0].length), (4, 4));
+    assert_eq!((utf8_tokens[1].delta_start, utf8_tokens[1].length), (5, 5));
+    assert_eq!((utf8_tokens[2].delta_start, utf8_tokens[2].length), (6, 10));
+}
+
+#[test]
+fn test_invalid_character_column_after_multibyte_chars() {
+    let provider = ValidationProvider::new();
+    let content = "    ŁAD wynik@";
+
+    let diagnostics = provider.validate_symbol_usage(content);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].range.start.character, 13);
+}
+
+#[test]
+fn test_text_helpers_use_char_columns() {
+    assert_eq!(text_before_column(POLISH_LINE, 7), "    ŁAD");
+    assert_eq!(slice_columns(POLISH_LINE, 4, 7), "ŁAD");
+    assert_eq!(char_len("wejście"), 7);
+    assert_eq!(
+        find_word_occurrences("    ŁAD wynik", "wynik"),
+        vec![(0, 8, 13)]
+    );
+    assert_eq!(
+        get_word_at_position("ŁAD wynik", 5),
+        Some(("wynik".to_string(), 4, 9))
+    );
+}
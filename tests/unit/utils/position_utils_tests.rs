@@ -76,14 +76,12 @@ fn test_create_diagnostic() {
     
     let diagnostic = create_diagnostic(
         range,
-        DiagnosticSeverity::ERROR,
-        "TEST001",
-        "Test message".to_string(),
-    );
+        DiagnosticCode::Sem001,
+        "Test message".to_string());
     
     assert_eq!(diagnostic.range, range);
     assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::ERROR));
-    assert_eq!(diagnostic.code, Some(NumberOrString::String("TEST001".to_string())));
+    assert_eq!(diagnostic.code, Some(NumberOrString::String("SEM001".to_string())));
     assert_eq!(diagnostic.message, "Test message");
     assert_eq!(diagnostic.source, Some("asmodeus-lsp".to_string()));
 }
@@ -91,7 +89,7 @@ fn test_create_diagnostic() {
 #[test]
 fn test_create_semantic_diagnostic() {
     let diagnostic = create_semantic_diagnostic(
-        5, 10, 3, "SEM001", "Semantic error".to_string()
+        5, 10, 3, DiagnosticCode::Sem001, "Semantic error".to_string()
     );
     
     assert_eq!(diagnostic.range.start.line, 4); // line - 1
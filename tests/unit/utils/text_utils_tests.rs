@@ -128,3 +128,11 @@ loop:
     
     assert!(get_label_info("nonexistent", content).is_none());
 }
+
+#[test]
+fn test_expand_snippet() {
+    assert_eq!(expand_snippet("SOB ${1:label}", &["loop"]), "SOB loop");
+    assert_eq!(expand_snippet("SOB ${1:label}", &[]), "SOB label");
+    assert_eq!(expand_snippet("ŁAD ${1:tmp}\nPOB $1", &["t"]), "ŁAD t\nPOB t");
+    assert_eq!(expand_snippet("cost: $ 5", &[]), "cost: $ 5");
+}